@@ -0,0 +1,57 @@
+use std::str::FromStr;
+
+/// The share of their time a resource spends on the chart's work, e.g. `50`
+/// for everyone or `alice=50` for one resource
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allocation {
+    /// The resource, or `None` for the default
+    pub resource: Option<String>,
+    pub percent: f64,
+}
+
+impl FromStr for Allocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (resource, percent) = match s.rsplit_once('=') {
+            Some((resource, percent)) => (Some(resource.trim().to_owned()), percent),
+            None => (None, s),
+        };
+        let percent = percent
+            .trim()
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .ok()
+            .filter(|percent| *percent > 0.0 && *percent <= 100.0)
+            .ok_or_else(|| {
+                format!(
+                    "'{}' must be a percentage over 0 and up to 100, optionally after a resource and '=', e.g. alice=50",
+                    s
+                )
+            })?;
+
+        Ok(Allocation { resource, percent })
+    }
+}
+
+/// The allocation for a resource, which is the last one given for it, or
+/// else the last one given without a resource, or else 100%
+pub fn percent_for(allocations: &[Allocation], names: &[&str]) -> f64 {
+    allocations
+        .iter()
+        .rev()
+        .find(|allocation| {
+            allocation
+                .resource
+                .as_deref()
+                .is_some_and(|resource| names.contains(&resource))
+        })
+        .or_else(|| {
+            allocations
+                .iter()
+                .rev()
+                .find(|allocation| allocation.resource.is_none())
+        })
+        .map(|allocation| allocation.percent)
+        .unwrap_or(100.0)
+}
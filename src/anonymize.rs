@@ -0,0 +1,139 @@
+use crate::chart::Chart;
+use ring::hmac;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// A pseudonym for a person, the same for the same name and salt on every
+/// run. The name is hashed with HMAC-SHA256 keyed by the salt, so without
+/// the salt the pseudonyms can't be matched to names by hashing a list of
+/// guesses.
+pub fn pseudonym(salt: &str, name: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, salt.as_bytes());
+    let tag = hmac::sign(&key, name.as_bytes());
+
+    format!(
+        "Person {}",
+        tag.as_ref()[..4]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>()
+    )
+}
+
+/// Replace the names of the people in a chart with their pseudonyms. These
+/// are the names of lanes and sections that are assignees, and the assignees
+/// of items. Tooltips repeat the assignee and can name people anywhere in
+/// the description, so they are removed, as are included descriptions.
+pub fn apply(chart: &mut Chart, salt: &str) {
+    let pseudonyms: HashMap<String, String> = people(chart)
+        .into_iter()
+        .map(|name| {
+            let pseudonym = pseudonym(salt, &name);
+
+            (name, pseudonym)
+        })
+        .collect();
+
+    rename(chart, &pseudonyms);
+}
+
+/// Replace the names of the people in a chart with `Resource 1` to
+/// `Resource N`, numbered in the order their lanes are in, in the same
+/// places as `apply`
+pub fn number(chart: &mut Chart) {
+    let names = people(chart);
+    let mut order: Vec<&String> = names.iter().collect();
+
+    order.sort_by_key(|name| {
+        chart
+            .resources
+            .iter()
+            .position(|resource| resource.split(": ").any(|part| part == name.as_str()))
+            .unwrap_or(usize::MAX)
+    });
+
+    let numbers: HashMap<String, String> = order
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| (name.to_owned(), format!("Resource {}", i + 1)))
+        .collect();
+
+    rename(chart, &numbers);
+}
+
+/// Replace the keys of the issues in a chart with `Issue 1` to `Issue N`, in
+/// the order the items are in, wherever an item is named or identified by
+/// its key, including the items it depends on. Items that don't stand for
+/// one issue, such as epics, keep their names.
+pub fn number_keys(chart: &mut Chart) {
+    let keys: HashMap<String, String> = chart
+        .items
+        .iter()
+        .filter(|item| item.id.as_deref() == Some(item.data.title.as_str()))
+        .enumerate()
+        .map(|(i, item)| (item.data.title.to_owned(), format!("Issue {}", i + 1)))
+        .collect();
+
+    for item in chart.items.iter_mut() {
+        if let Some(number) = keys.get(&item.data.title) {
+            item.data.title = number.to_owned();
+        }
+
+        item.id = item
+            .id
+            .as_deref()
+            .map(|id| keys.get(id).map_or(id, |number| number.as_str()).to_owned());
+        // Dependencies on issues that aren't on the chart were already dropped
+        item.depends_on = item
+            .depends_on
+            .iter()
+            .map(|id| keys.get(id).map_or(id, |number| number).to_owned())
+            .collect();
+
+        if let Some(Value::String(key)) = item.fields.get_mut("key") {
+            if let Some(number) = keys.get(key.as_str()) {
+                *key = number.to_owned();
+            }
+        }
+    }
+}
+
+/// The people the items of a chart are assigned to, or were before they left
+fn people(chart: &Chart) -> HashSet<String> {
+    chart
+        .items
+        .iter()
+        .flat_map(|item| [item.assignee.clone(), item.former_assignee.clone()])
+        .flatten()
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Rename the people in a chart and remove what can name them
+fn rename(chart: &mut Chart, names: &HashMap<String, String>) {
+    let rename = |name: &str| {
+        // A resource in a section is named `section: lane`
+        name.split(": ")
+            .map(|part| names.get(part).map_or(part, |p| p.as_str()))
+            .collect::<Vec<&str>>()
+            .join(": ")
+    };
+
+    chart.resources = chart.resources.iter().map(|name| rename(name)).collect();
+
+    for group in chart.groups.iter_mut() {
+        group.name = rename(&group.name);
+    }
+
+    for item in chart.items.iter_mut() {
+        item.assignee = item.assignee.as_deref().map(rename);
+        item.former_assignee = item.former_assignee.as_deref().map(rename);
+        item.tooltip = None;
+
+        if let Some(Value::String(assignee)) = item.fields.get_mut("assignee") {
+            *assignee = rename(assignee);
+        }
+
+        item.fields.remove("description");
+    }
+}
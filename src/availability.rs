@@ -0,0 +1,79 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use easy_error::{self, ResultExt};
+use serde::Deserialize;
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+const DEFAULT_DAYS_PER_WEEK: f64 = 5.0;
+
+/// How many days each resource can work each week
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Availability {
+    /// The days per week of any resource not listed in `resources`
+    #[serde(default = "default_days_per_week")]
+    pub days_per_week: f64,
+    /// Days per week by resource name
+    #[serde(default)]
+    pub resources: HashMap<String, f64>,
+    /// Days available by resource name for particular weeks, keyed by the
+    /// date of the Monday of the week, e.g. for holidays
+    #[serde(default)]
+    pub weeks: HashMap<NaiveDate, HashMap<String, f64>>,
+}
+
+fn default_days_per_week() -> f64 {
+    DEFAULT_DAYS_PER_WEEK
+}
+
+impl Default for Availability {
+    fn default() -> Self {
+        Availability {
+            days_per_week: DEFAULT_DAYS_PER_WEEK,
+            resources: HashMap::new(),
+            weeks: HashMap::new(),
+        }
+    }
+}
+
+/// The Monday of the week a date is in
+pub fn week_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+impl Availability {
+    pub fn load(path: &Path) -> Result<Availability, Box<dyn Error>> {
+        let content = fs::read_to_string(path).context(format!(
+            "Unable to read availability file '{}'",
+            path.to_string_lossy()
+        ))?;
+        let availability: Availability = json5::from_str(&content).map_err(|err| {
+            format!(
+                "Invalid availability file '{}': {}",
+                path.to_string_lossy(),
+                err
+            )
+        })?;
+
+        for week in availability.weeks.keys() {
+            if week_of(*week) != *week {
+                return Err(From::from(format!(
+                    "Week '{}' in availability file '{}' is not a Monday",
+                    week,
+                    path.to_string_lossy()
+                )));
+            }
+        }
+
+        Ok(availability)
+    }
+
+    /// The days a resource can work in the week starting on `week`
+    pub fn capacity(&self, resource: &str, week: NaiveDate) -> f64 {
+        self.weeks
+            .get(&week)
+            .and_then(|resources| resources.get(resource))
+            .or_else(|| self.resources.get(resource))
+            .copied()
+            .unwrap_or(self.days_per_week)
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{
+    chart::{self, Chart, Item},
+    i18n::Translations,
+    schedule,
+};
+use chrono::{Duration, NaiveDate};
+use gantt_chart::ItemData;
+use std::{collections::BTreeMap, str::FromStr};
+
+/// The first and last day that a chart's time axis should cover
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AxisRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl FromStr for AxisRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "'{}' must be a first and last day, e.g. 2023-01-01:2023-12-31",
+                s
+            )
+        };
+        let (from, to) = s.split_once(':').ok_or_else(invalid)?;
+        let from = NaiveDate::parse_from_str(from.trim(), "%Y-%m-%d").map_err(|_| invalid())?;
+        let to = NaiveDate::parse_from_str(to.trim(), "%Y-%m-%d").map_err(|_| invalid())?;
+
+        if from > to {
+            return Err(invalid());
+        }
+
+        Ok(AxisRange { from, to })
+    }
+}
+
+/// Widen the time axis of a chart to cover a range. `gantt_chart` makes the
+/// axis just wide enough for the items, so an untitled milestone is added on
+/// the first or last day of the range when the items don't reach it. Items
+/// outside the range still widen the axis.
+pub fn align(chart: &mut Chart, range: AxisRange) {
+    if chart.resources.is_empty() {
+        return;
+    }
+
+    let (first, last) = {
+        let items = schedule::schedule(chart);
+
+        (
+            items.iter().filter_map(|item| item.start).min(),
+            items.iter().filter_map(|item| item.end).max(),
+        )
+    };
+
+    if first.is_none_or(|first| first > range.from) {
+        chart
+            .items
+            .insert(0, milestone("axis-start", range.from, 0));
+    }
+
+    if last.is_none_or(|last| last < range.to) {
+        chart
+            .items
+            .push(milestone("axis-end", range.to, chart.resources.len() - 1));
+    }
+}
+
+/// Add a lane of milestones on the first day of each sprint with dates,
+/// titled with its name, and on its last day when the next sprint doesn't
+/// start straight after it, so sprint boundaries can be seen on the chart
+pub fn sprint_boundaries(chart: &mut Chart, text: &Translations) {
+    let mut sprints: Vec<_> = chart
+        .sprints
+        .iter()
+        .filter_map(|sprint| sprint.start_date.map(|start| (start, sprint)))
+        .collect();
+
+    if sprints.is_empty() {
+        return;
+    }
+
+    sprints.sort_by_key(|(start, _)| *start);
+
+    let lane = chart.resources.len();
+    let mut items = vec![];
+
+    for (start, sprint) in sprints.iter() {
+        let mut item = milestone(&chart::synthetic_id("sprint", &sprint.name), *start, lane);
+
+        item.data.title = sprint.name.to_owned();
+        items.push(item);
+
+        if let Some(end) = sprint.end_date {
+            let next_day = end + Duration::days(1);
+
+            if sprints
+                .iter()
+                .any(|(other, _)| other > start && *other <= next_day)
+            {
+                continue;
+            }
+
+            let mut item = milestone(&chart::synthetic_id("sprint-end", &sprint.name), end, lane);
+
+            item.data.title = text.format("sprint-end", &sprint.name);
+            items.push(item);
+        }
+    }
+
+    chart.resources.push(text.get("sprints").to_owned());
+    chart.items.extend(items);
+}
+
+/// An untitled milestone that only widens the time axis
+pub fn milestone(id: &str, date: NaiveDate, resource_index: usize) -> Item {
+    Item {
+        id: Some(id.to_owned()),
+        data: ItemData {
+            title: String::new(),
+            start_date: Some(date),
+            duration: None,
+            resource_index: Some(resource_index),
+            open: None,
+        },
+        tooltip: None,
+        sprint: None,
+        risk: None,
+        progress: None,
+        health: None,
+        color: None,
+        blocked: vec![],
+        depends_on: vec![],
+        stretch: None,
+        fields: BTreeMap::new(),
+        duration_source: None,
+        estimate: None,
+        epic: None,
+        assignee: None,
+        created: None,
+        summary: None,
+        due_date: None,
+        former_assignee: None,
+        fix_versions: vec![],
+        sla_remaining: None,
+    }
+}
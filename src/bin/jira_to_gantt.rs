@@ -20,6 +20,9 @@ impl JiraToGanttLog for JiraToGanttLogger {
     fn error(self: &Self, args: Arguments) {
         eprintln!("{}", format!("error: {}", args).red());
     }
+    fn note(self: &Self, args: Arguments) {
+        eprintln!("note: {}", args);
+    }
 }
 
 fn main() {
@@ -0,0 +1,94 @@
+use crate::{chart::Chart, schedule};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::str::FromStr;
+
+/// Planning periods to snap items to for a coarse roadmap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bucket {
+    /// Calendar quarters
+    Quarter,
+    /// Planning increments of a number of weeks, counting from a first day
+    Increment { start: NaiveDate, weeks: i64 },
+}
+
+impl FromStr for Bucket {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "quarter" {
+            return Ok(Bucket::Quarter);
+        }
+
+        let invalid = || {
+            format!(
+                "'{}' must be quarter, or pi:<start>:<weeks> for planning increments, e.g. pi:2023-01-09:10",
+                s
+            )
+        };
+        let mut parts = s.split(':');
+
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some("pi"), Some(start), Some(weeks), None) => Ok(Bucket::Increment {
+                start: NaiveDate::parse_from_str(start, "%Y-%m-%d").map_err(|_| invalid())?,
+                weeks: weeks
+                    .parse()
+                    .ok()
+                    .filter(|weeks| *weeks > 0)
+                    .ok_or_else(invalid)?,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Bucket {
+    /// The first day of the period a date is in and the first day of the
+    /// next period
+    fn bounds(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match *self {
+            Bucket::Quarter => {
+                let first_month = (date.month0() / 3) * 3 + 1;
+                let start = NaiveDate::from_ymd_opt(date.year(), first_month, 1).unwrap();
+                let end = if first_month == 10 {
+                    NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(date.year(), first_month + 3, 1)
+                }
+                .unwrap();
+
+                (start, end)
+            }
+            Bucket::Increment { start, weeks } => {
+                let length = weeks * 7;
+                let index = (date - start).num_days().div_euclid(length);
+                let from = start + Duration::days(index * length);
+
+                (from, from + Duration::days(length))
+            }
+        }
+    }
+
+    /// Snap each item to the periods it falls in, so it starts at the start
+    /// of the period it starts in and ends at the end of the period it ends
+    /// in. Milestones move to the start of their period.
+    pub fn apply(&self, chart: &mut Chart) {
+        let dates: Vec<_> = schedule::schedule(chart)
+            .iter()
+            .map(|item| (item.start, item.last_day()))
+            .collect();
+
+        for (item, (start, last_day)) in chart.items.iter_mut().zip(dates) {
+            if let (Some(start), Some(last_day)) = (start, last_day) {
+                let (from, _) = self.bounds(start);
+
+                item.data.start_date = Some(from);
+
+                if item.data.duration.is_some() {
+                    let (_, to) = self.bounds(last_day);
+
+                    item.data.duration = Some((to - from).num_days());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use crate::{health::Health, hours::Granularity};
+use chrono::{Duration, NaiveDate, Weekday};
+use easy_error::{self, ResultExt};
+use gantt_chart::ItemData;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, error::Error, fs, path::Path};
+
+/// Chart data as written to the output file. This is a superset of the
+/// `gantt_chart` crate's `ChartData`, so the extra fields are simply ignored
+/// by tools that don't understand them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Chart {
+    pub title: String,
+    #[serde(rename = "markedDate")]
+    pub marked_date: Option<NaiveDate>,
+    pub resources: Vec<String>,
+    pub items: Vec<Item>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<Group>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sprints: Vec<Sprint>,
+    /// Days that aren't worked, for renderers to shade
+    #[serde(default, rename = "nonWorking", skip_serializing_if = "Vec::is_empty")]
+    pub non_working: Vec<NonWorking>,
+    /// What the dates and durations of the items were worked out in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<Units>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Item {
+    /// An identifier that stays the same each time the chart is made, so
+    /// other tools can follow an item from one chart to the next
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(flatten)]
+    pub data: ItemData,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sprint: Option<String>,
+    /// How risky the item is, e.g. `high`, for renderers to mark it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk: Option<String>,
+    /// The percentage of the work that is done, for items that stand for
+    /// several issues
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<u8>,
+    /// Whether the progress of an open item is keeping up with its dates,
+    /// for renderers to style it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<Health>,
+    /// A color for renderers to draw the item in, e.g. `#e34a33`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// When the issue was flagged or blocked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked: Vec<Period>,
+    /// The identifiers of the items that must finish before this one starts,
+    /// for renderers to draw arrows from
+    #[serde(default, rename = "dependsOn", skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Whether the item is stretch scope rather than committed, for charts
+    /// that tell them apart, for renderers to style it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stretch: Option<bool>,
+    /// The fields of `--include-fields` as they were read, by name, so
+    /// other tools don't have to join the chart with the export
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, Value>,
+    /// Where the duration came from. This isn't written to the chart file.
+    #[serde(skip)]
+    pub duration_source: Option<DurationSource>,
+    /// The original estimate of the issue in seconds, or the total of the
+    /// estimates of the issues an item stands for. This isn't written to
+    /// the chart file.
+    #[serde(skip)]
+    pub estimate: Option<u32>,
+    /// The epic of the issue. This isn't written to the chart file.
+    #[serde(skip)]
+    pub epic: Option<String>,
+    /// The assignee of the issue, or its team in a plan. This isn't written
+    /// to the chart file.
+    #[serde(skip)]
+    pub assignee: Option<String>,
+    /// When the issue was created. This isn't written to the chart file.
+    #[serde(skip)]
+    pub created: Option<NaiveDate>,
+    /// The summary of the issue. This isn't written to the chart file.
+    #[serde(skip)]
+    pub summary: Option<String>,
+    /// When the issue is due. This isn't written to the chart file.
+    #[serde(skip)]
+    pub due_date: Option<NaiveDate>,
+    /// The assignee who has left, for open issues that need someone else.
+    /// This isn't written to the chart file.
+    #[serde(skip)]
+    pub former_assignee: Option<String>,
+    /// The releases the issue is to go out in. This isn't written to the
+    /// chart file.
+    #[serde(skip)]
+    pub fix_versions: Vec<String>,
+    /// The time left to resolve the issue within its SLA when the export
+    /// was made, in seconds, which is negative once it has been breached.
+    /// This isn't written to the chart file.
+    #[serde(skip)]
+    pub sla_remaining: Option<i64>,
+}
+
+impl Item {
+    /// The item's identifier, or its title for charts written before items
+    /// had one
+    pub fn id(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.data.title)
+    }
+
+    /// Whether the item is placed on its start and end dates as they are,
+    /// so dependencies and time off don't move or stretch it
+    pub fn has_fixed_dates(&self) -> bool {
+        matches!(
+            self.duration_source,
+            Some(DurationSource::TargetDates | DurationSource::Actual)
+        )
+    }
+
+    /// The last day to resolve the issue within its SLA, taking the time
+    /// left as from the start of `today`
+    pub fn sla_due(&self, today: NaiveDate) -> Option<NaiveDate> {
+        self.sla_remaining
+            .map(|seconds| today + Duration::days(seconds.div_euclid(24 * 3600)))
+    }
+}
+
+/// An identifier for an item that doesn't stand for one issue, made from a
+/// kind such as `epic` and a name. This uses FNV-1a rather than the standard
+/// library's hasher, which may change between Rust releases.
+pub fn synthetic_id(kind: &str, name: &str) -> String {
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+
+    format!("{}-{:016x}", kind, hash)
+}
+
+/// A period of days, which may not have ended yet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Period {
+    #[serde(rename = "startDate")]
+    pub start_date: NaiveDate,
+    #[serde(rename = "endDate", default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<NaiveDate>,
+}
+
+impl Period {
+    /// The days from the start to the end, or to `today` if it hasn't ended
+    pub fn days(&self, today: NaiveDate) -> i64 {
+        (self.end_date.unwrap_or(today) - self.start_date)
+            .num_days()
+            .max(0)
+    }
+}
+
+/// Where the duration of an item came from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationSource {
+    /// The issue's original estimate
+    Estimate,
+    /// The issue's remaining estimate
+    RemainingEstimate,
+    /// The issue's story points
+    StoryPoints,
+    /// The target start and end dates of a plan
+    TargetDates,
+    /// The span of the issues the item stands for
+    Issues,
+    /// The dates of the sprint the issue is in
+    Sprint,
+    /// The default duration for the type of issue from the config file
+    Default,
+    /// When a closed issue was actually created and resolved
+    Actual,
+    /// The timebox of a spike
+    Spike,
+}
+
+impl DurationSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DurationSource::Estimate => "estimate",
+            DurationSource::RemainingEstimate => "remaining estimate",
+            DurationSource::StoryPoints => "story points",
+            DurationSource::TargetDates => "target dates",
+            DurationSource::Issues => "issues",
+            DurationSource::Sprint => "sprint",
+            DurationSource::Default => "default",
+            DurationSource::Actual => "actual",
+            DurationSource::Spike => "spike",
+        }
+    }
+}
+
+/// A named group of consecutive resources, e.g. one portfolio source
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Group {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    pub resources: Vec<usize>,
+}
+
+/// A sprint and its planned dates
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sprint {
+    pub name: String,
+    #[serde(rename = "startDate")]
+    pub start_date: Option<NaiveDate>,
+    #[serde(rename = "endDate")]
+    pub end_date: Option<NaiveDate>,
+}
+
+/// A run of days that aren't worked, by everyone or by some resources
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NonWorking {
+    #[serde(rename = "startDate")]
+    pub start_date: NaiveDate,
+    #[serde(rename = "endDate")]
+    pub end_date: NaiveDate,
+    /// Why the days aren't worked, `weekend`, `holiday` or `time-off`
+    pub kind: String,
+    /// The resources that are off, for time off
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<usize>,
+}
+
+/// The units and calendar a chart was made with, so renderers and other
+/// tools can tell what its durations mean without assuming
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Units {
+    /// What item durations count, always `calendarDays` from the start date,
+    /// including any days off that items were stretched over
+    pub duration: String,
+    /// What durations and starts were worked out in before they were
+    /// rounded to days, `days` or `hours`
+    pub granularity: Granularity,
+    /// The working hours in a day that estimates were turned into days with
+    pub hours_per_day: f64,
+    /// The working hours of each weekday, for the `hours` granularity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_hours: Option<String>,
+    /// Whether only working days count as work, so items were stretched over
+    /// the weekend days
+    pub working_days: bool,
+    pub weekend_days: Vec<Weekday>,
+    /// The days nobody works, that items are stretched over
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holidays: Vec<NaiveDate>,
+    /// Whether the time off of resources was taken into account
+    pub time_off: bool,
+}
+
+impl Chart {
+    /// Read a chart data file, e.g. one written earlier by the tool
+    pub fn load(path: &Path) -> Result<Chart, Box<dyn Error>> {
+        let content = fs::read_to_string(path).context(format!(
+            "Unable to read chart file '{}'",
+            path.to_string_lossy()
+        ))?;
+
+        json5::from_str(&content).map_err(|err| {
+            From::from(format!(
+                "Invalid chart file '{}': {}",
+                path.to_string_lossy(),
+                err
+            ))
+        })
+    }
+
+    pub fn new(title: &str) -> Chart {
+        Chart {
+            title: title.to_owned(),
+            marked_date: None,
+            resources: vec![],
+            items: vec![],
+            groups: vec![],
+            sprints: vec![],
+            non_working: vec![],
+            units: None,
+        }
+    }
+
+    /// Append the resources, items and groups of another chart, prefixing the
+    /// resource and group names. Returns the indices of the appended resources.
+    pub fn append(&mut self, other: Chart, prefix: &str) -> Vec<usize> {
+        let offset = self.resources.len();
+
+        self.resources.extend(
+            other
+                .resources
+                .iter()
+                .map(|resource| format!("{}: {}", prefix, resource)),
+        );
+        self.items.extend(other.items.into_iter().map(|mut item| {
+            item.data.resource_index = item.data.resource_index.map(|index| index + offset);
+            item
+        }));
+
+        self.groups
+            .extend(other.groups.into_iter().map(|group| Group {
+                name: format!("{}: {}", prefix, group.name),
+                color: group.color,
+                resources: group.resources.iter().map(|index| index + offset).collect(),
+            }));
+
+        for sprint in other.sprints.into_iter() {
+            if !self.sprints.iter().any(|s| s.name == sprint.name) {
+                self.sprints.push(sprint);
+            }
+        }
+
+        (offset..self.resources.len()).collect()
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{chart::Chart, schedule};
+use chrono::NaiveDate;
+use std::{collections::HashMap, error::Error, fmt, str::FromStr};
+
+/// A value of the schedule that can be checked
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Metric {
+    /// The number of items
+    Issues,
+    /// The total number of days of all items
+    TotalDays,
+    /// The number of days of the busiest resource
+    MaxResourceDays,
+    /// The number of days of one resource
+    ResourceDays(String),
+    /// The last day of the last item
+    EndDate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+enum Value {
+    Number(i64),
+    Date(NaiveDate),
+}
+
+/// A condition on the schedule that fails the run, e.g. `total-days > 400`
+#[derive(Clone, Debug)]
+pub struct Threshold {
+    text: String,
+    metric: Metric,
+    operator: Operator,
+    value: Value,
+}
+
+impl FromStr for Threshold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let operators = [
+            (">=", Operator::GreaterOrEqual),
+            ("<=", Operator::LessOrEqual),
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            (">", Operator::Greater),
+            ("<", Operator::Less),
+        ];
+        let (position, symbol, operator) = operators
+            .iter()
+            .filter_map(|(symbol, operator)| s.find(symbol).map(|p| (p, *symbol, *operator)))
+            .min_by_key(|(position, symbol, _)| (*position, usize::MAX - symbol.len()))
+            .ok_or_else(|| {
+                format!(
+                    "'{}' must be a metric, one of >, >=, <, <=, == or !=, and a value",
+                    s
+                )
+            })?;
+        let name = s[..position].trim();
+        let value = s[position + symbol.len()..].trim();
+        let metric = match name {
+            "issues" => Metric::Issues,
+            "total-days" => Metric::TotalDays,
+            "max-resource-days" => Metric::MaxResourceDays,
+            "end-date" => Metric::EndDate,
+            _ => match name
+                .strip_prefix("resource-days[")
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                Some(resource) => Metric::ResourceDays(resource.trim().to_owned()),
+                None => {
+                    return Err(format!(
+                        "Unknown metric '{}', use issues, total-days, max-resource-days, \
+                         resource-days[<name>] or end-date",
+                        name
+                    ))
+                }
+            },
+        };
+        let value = match metric {
+            Metric::EndDate => Value::Date(
+                NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .map_err(|_| format!("'{}' is not a date like 2023-06-30", value))?,
+            ),
+            _ => Value::Number(
+                value
+                    .parse::<i64>()
+                    .map_err(|_| format!("'{}' is not a whole number", value))?,
+            ),
+        };
+
+        Ok(Threshold {
+            text: s.trim().to_owned(),
+            metric,
+            operator,
+            value,
+        })
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Date(date) => write!(f, "{}", date),
+        }
+    }
+}
+
+impl Threshold {
+    fn measure(&self, chart: &Chart) -> Result<Value, Box<dyn Error>> {
+        let items = schedule::schedule(chart);
+        let days = |item: &schedule::ScheduledItem| item.item.data.duration.unwrap_or(0);
+        let mut resource_days: HashMap<usize, i64> = HashMap::new();
+
+        for item in items.iter() {
+            if let Some(resource) = item.resource {
+                *resource_days.entry(resource).or_insert(0) += days(item);
+            }
+        }
+
+        Ok(match self.metric {
+            Metric::Issues => Value::Number(items.len() as i64),
+            Metric::TotalDays => Value::Number(items.iter().map(days).sum()),
+            Metric::MaxResourceDays => {
+                Value::Number(resource_days.values().copied().max().unwrap_or(0))
+            }
+            Metric::ResourceDays(ref name) => {
+                let index = chart
+                    .resources
+                    .iter()
+                    .position(|resource| resource == name)
+                    .ok_or_else(|| format!("There is no resource named '{}'", name))?;
+
+                Value::Number(resource_days.get(&index).copied().unwrap_or(0))
+            }
+            Metric::EndDate => Value::Date(
+                items
+                    .iter()
+                    .filter_map(|item| item.last_day())
+                    .max()
+                    .ok_or("The chart has no dated items to find the end date of")?,
+            ),
+        })
+    }
+
+    /// Check the threshold against a chart, returning a description of the
+    /// failure if it is exceeded
+    pub fn check(&self, chart: &Chart) -> Result<Option<String>, Box<dyn Error>> {
+        let actual = self.measure(chart)?;
+        let failed = match self.operator {
+            Operator::Greater => actual > self.value,
+            Operator::GreaterOrEqual => actual >= self.value,
+            Operator::Less => actual < self.value,
+            Operator::LessOrEqual => actual <= self.value,
+            Operator::Equal => actual == self.value,
+            Operator::NotEqual => actual != self.value,
+        };
+
+        Ok(if failed {
+            Some(format!(
+                "'{}' failed, the actual value is {}",
+                self.text, actual
+            ))
+        } else {
+            None
+        })
+    }
+}
@@ -0,0 +1,114 @@
+use std::{
+    env,
+    error::Error,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// The commands that read and write the clipboard on this platform, in the
+/// order to try them. Using the platform's own tools avoids linking against
+/// a windowing system.
+fn commands(copy: bool) -> Vec<(&'static str, Vec<&'static str>)> {
+    if cfg!(target_os = "macos") {
+        vec![if copy {
+            ("pbcopy", vec![])
+        } else {
+            ("pbpaste", vec![])
+        }]
+    } else if cfg!(windows) {
+        vec![(
+            "powershell",
+            vec![
+                "-NoProfile",
+                "-Command",
+                if copy {
+                    "[Console]::In.ReadToEnd() | Set-Clipboard"
+                } else {
+                    "Get-Clipboard -Raw"
+                },
+            ],
+        )]
+    } else {
+        let mut commands = vec![];
+
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            commands.push(if copy {
+                ("wl-copy", vec![])
+            } else {
+                ("wl-paste", vec!["--no-newline"])
+            });
+        }
+
+        commands.push((
+            "xclip",
+            vec!["-selection", "clipboard", copy_flag(copy, "-i", "-o")],
+        ));
+        commands.push((
+            "xsel",
+            vec!["--clipboard", copy_flag(copy, "--input", "--output")],
+        ));
+        commands
+    }
+}
+
+fn copy_flag(copy: bool, input: &'static str, output: &'static str) -> &'static str {
+    if copy {
+        input
+    } else {
+        output
+    }
+}
+
+fn tools() -> String {
+    commands(false)
+        .iter()
+        .map(|(program, _)| *program)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+/// Read the text on the clipboard
+pub fn read() -> Result<String, Box<dyn Error>> {
+    for (program, args) in commands(false) {
+        if let Ok(output) = Command::new(program)
+            .args(args)
+            .stderr(Stdio::null())
+            .output()
+        {
+            if output.status.success() {
+                return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+            }
+        }
+    }
+
+    Err(From::from(format!(
+        "Unable to read the clipboard, this needs one of: {}",
+        tools()
+    )))
+}
+
+/// Replace the text on the clipboard
+pub fn write(text: &str) -> Result<(), Box<dyn Error>> {
+    for (program, args) in commands(true) {
+        if let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(text.as_bytes())?;
+            }
+
+            if child.wait()?.success() {
+                return Ok(());
+            }
+        }
+    }
+
+    Err(From::from(format!(
+        "Unable to write to the clipboard, this needs one of: {}",
+        tools()
+    )))
+}
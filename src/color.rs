@@ -0,0 +1,91 @@
+use crate::{chart::Chart, schedule};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+/// What the colors of items show
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorBy {
+    /// How long each open item has been open, from light for the newest to
+    /// dark red for the oldest
+    Age,
+    /// The risk of breaching each open item's SLA: red once it has been
+    /// breached, orange when it is scheduled to finish after it runs out
+    /// and green when it is on track
+    Sla,
+}
+
+/// The heat scale, from the youngest to the oldest
+const HEAT: [(u8, u8, u8); 3] = [(0xfe, 0xe8, 0xc8), (0xfd, 0xbb, 0x84), (0xe3, 0x4a, 0x33)];
+
+const SLA_BREACHED_COLOR: &str = "#d62728";
+const SLA_AT_RISK_COLOR: &str = "#ff7f0e";
+const SLA_ON_TRACK_COLOR: &str = "#2ca02c";
+
+/// Give the open items a color on a heat scale by the days since they were
+/// created, relative to the oldest, or by the risk of breaching their SLA.
+/// Closed items and items without a Created date or SLA aren't colored.
+pub fn apply(chart: &mut Chart, by: ColorBy, today: NaiveDate) {
+    match by {
+        ColorBy::Age => {
+            let age = |created: Option<NaiveDate>| {
+                created.map(|created| (today - created).num_days().max(0))
+            };
+            let oldest = chart
+                .items
+                .iter()
+                .filter(|item| item.data.open != Some(false))
+                .filter_map(|item| age(item.created))
+                .max()
+                .unwrap_or_default();
+
+            for item in chart.items.iter_mut() {
+                item.color = match age(item.created) {
+                    Some(days) if item.data.open != Some(false) => Some(heat(if oldest > 0 {
+                        days as f64 / oldest as f64
+                    } else {
+                        0.0
+                    })),
+                    _ => None,
+                };
+            }
+        }
+        ColorBy::Sla => {
+            let colors: Vec<Option<&str>> = schedule::schedule(chart)
+                .iter()
+                .map(|item| {
+                    let due = item.item.sla_due(today)?;
+
+                    if item.item.data.open == Some(false) {
+                        None
+                    } else if item.item.sla_remaining.is_some_and(|seconds| seconds < 0) {
+                        Some(SLA_BREACHED_COLOR)
+                    } else if item.last_day().is_some_and(|last_day| last_day > due) {
+                        Some(SLA_AT_RISK_COLOR)
+                    } else {
+                        Some(SLA_ON_TRACK_COLOR)
+                    }
+                })
+                .collect();
+
+            for (item, color) in chart.items.iter_mut().zip(colors) {
+                item.color = color.map(|color| color.to_owned());
+            }
+        }
+    }
+}
+
+/// The color a share of the way along the heat scale
+fn heat(share: f64) -> String {
+    let position = share.clamp(0.0, 1.0) * (HEAT.len() - 1) as f64;
+    let index = (position.floor() as usize).min(HEAT.len() - 2);
+    let fraction = position - index as f64;
+    let (from, to) = (HEAT[index], HEAT[index + 1]);
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * fraction).round() as u8;
+
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        mix(from.0, to.0),
+        mix(from.1, to.1),
+        mix(from.2, to.2)
+    )
+}
@@ -0,0 +1,637 @@
+use crate::record::IssueLink;
+use clap::ValueEnum;
+use csv::StringRecord;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, error::Error, str::FromStr};
+
+/// The tool that produced the export
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// A Jira issue search CSV export
+    Jira,
+    /// A Jira Advanced Roadmaps (Plans) CSV or JSON export
+    Plan,
+    /// A YouTrack issues CSV export
+    #[clap(name = "youtrack")]
+    YouTrack,
+    /// A GitLab issues CSV export
+    #[clap(name = "gitlab")]
+    GitLab,
+    /// A CSV from any other tool, with columns such as Key, Title, Status,
+    /// Assignee, Estimate, Start and End
+    Generic,
+}
+
+/// The tool that produced the issues, and the Jira site in the config file
+/// they are fetched from, if it is named, e.g. `jira:acme`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct SourceSite {
+    pub source: Source,
+    pub site: Option<String>,
+}
+
+impl FromStr for SourceSite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, site) = match s.split_once(':') {
+            Some((name, site)) => (name, Some(site.trim())),
+            None => (s, None),
+        };
+        let source = <Source as ValueEnum>::from_str(name.trim(), true).map_err(|_| {
+            format!(
+                "'{}' isn't a source, use jira, plan, youtrack, gitlab or generic",
+                name.trim()
+            )
+        })?;
+
+        match site {
+            Some("") => Err(format!("'{}' doesn't name a site after the ':'", s)),
+            site => Ok(SourceSite {
+                source,
+                site: site.map(str::to_owned),
+            }),
+        }
+    }
+}
+
+impl TryFrom<String> for SourceSite {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl Source {
+    /// Guess which tool produced an export from the distinctive column
+    /// names in its header row
+    pub fn detect(headers: &StringRecord) -> Source {
+        let has = |name: &str| headers.iter().any(|header| header.trim() == name);
+
+        if has("Issue key") && (has("Target start") || has("Target end") || has("Team")) {
+            Source::Plan
+        } else if has("Issue key") {
+            Source::Jira
+        } else if has("Issue Id") && (has("State") || has("Estimation")) {
+            Source::YouTrack
+        } else if has("Issue ID") && (has("Created At (UTC)") || has("Time Estimate")) {
+            Source::GitLab
+        } else {
+            Source::Generic
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Source::Jira => "Jira",
+            Source::Plan => "Jira Advanced Roadmaps plan",
+            Source::YouTrack => "YouTrack",
+            Source::GitLab => "GitLab",
+            Source::Generic => "generic",
+        }
+    }
+}
+
+/// A pair of custom fields holding the start and end dates of issues, such
+/// as Jira's *Start date* and an *End date* added by the site. These are the
+/// column names in exports, or the field ids, e.g. `customfield_10015`, when
+/// fetching from Jira.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DateFields {
+    pub start: String,
+    pub end: String,
+}
+
+impl FromStr for DateFields {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(',') {
+            Some((start, end)) if !start.trim().is_empty() && !end.trim().is_empty() => {
+                Ok(DateFields {
+                    start: start.trim().to_owned(),
+                    end: end.trim().to_owned(),
+                })
+            }
+            _ => Err(format!(
+                "'{}' must be a start and an end field, e.g. \"Custom field (Start date),Custom field (End date)\"",
+                s
+            )),
+        }
+    }
+}
+
+/// The logical fields the tool reads from an export
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Field {
+    Key,
+    Summary,
+    Status,
+    Assignee,
+    #[serde(alias = "estimate")]
+    OriginalEstimate,
+    /// The work still to do
+    RemainingEstimate,
+    StoryPoints,
+    /// The work logged so far
+    TimeSpent,
+    Created,
+    /// When the issue was resolved, if it has been
+    Resolved,
+    /// When the issue last moved to another status category
+    StatusChanged,
+    /// When work on the issue started, usually from a custom field
+    Started,
+    Description,
+    TargetStart,
+    TargetEnd,
+    /// When the issue is due
+    DueDate,
+    Team,
+    IssueType,
+    Epic,
+    Risk,
+    /// Labels or tags, which may be in several columns with the same name
+    Labels,
+    /// The components of the project the issue belongs to, which may be in
+    /// several columns with the same name
+    Components,
+    /// The sprints of the issue, which may be in several columns
+    Sprint,
+    /// The releases the issue is to go out in, which may be in several
+    /// columns
+    FixVersions,
+    /// The time left to resolve the issue within its SLA, from Jira Service
+    /// Management
+    SlaRemaining,
+    /// The name of the project the issue is in
+    Project,
+}
+
+impl Field {
+    /// The field with a name in camel case, e.g. `issueType`
+    pub fn from_name(name: &str) -> Option<Field> {
+        serde_json::from_value(serde_json::Value::String(name.to_owned())).ok()
+    }
+
+    /// The name of the field in camel case
+    pub fn name(&self) -> String {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(name)) => name,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Field::from_name(s.trim()).ok_or_else(|| format!("'{}' isn't a field that can be read", s))
+    }
+}
+
+/// A column to read a field from, given as `field=column`, e.g.
+/// `estimate=Σ Original Estimate`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub field: Field,
+    pub column: String,
+}
+
+impl FromStr for ColumnMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, column) = s.split_once('=').ok_or_else(|| {
+            format!(
+                "'{}' must be a field and a column, e.g. \"estimate=Σ Original Estimate\"",
+                s
+            )
+        })?;
+        let field = Field::from_name(name.trim())
+            .ok_or_else(|| format!("'{}' isn't a field that can be read", name.trim()))?;
+
+        match column.trim() {
+            "" => Err(format!("'{}' doesn't give a column for {}", s, name.trim())),
+            column => Ok(ColumnMapping {
+                field,
+                column: column.to_owned(),
+            }),
+        }
+    }
+}
+
+/// Whether a field must be in an export
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Presence {
+    Required,
+    /// The chart is much less useful without it, so warn when it is missing
+    Recommended,
+    Optional,
+}
+
+/// Maps each field onto the header names it may appear under, in order of
+/// preference, and whether the field must be present.
+pub struct ColumnMap {
+    source: Source,
+    columns: Vec<(Field, Vec<String>, Presence)>,
+    /// The number of seconds in one unit of a plain number estimate
+    estimate_unit: u32,
+}
+
+impl ColumnMap {
+    pub fn for_source(source: Source) -> ColumnMap {
+        use Presence::*;
+
+        let columns: Vec<(Field, &[&str], Presence)> = match source {
+            Source::Jira => vec![
+                (Field::Key, &["Issue key"], Required),
+                (Field::Summary, &["Summary"], Optional),
+                (Field::Status, &["Status"], Required),
+                (Field::Assignee, &["Assignee"], Required),
+                (Field::OriginalEstimate, &["Original Estimate"], Recommended),
+                (Field::RemainingEstimate, &["Remaining Estimate"], Optional),
+                (
+                    Field::StoryPoints,
+                    &[
+                        "Custom field (Story Points)",
+                        "Custom field (Story point estimate)",
+                        "Story Points",
+                    ],
+                    Optional,
+                ),
+                (Field::TimeSpent, &["Time Spent"], Optional),
+                (Field::Created, &["Created"], Required),
+                (Field::Resolved, &["Resolved"], Optional),
+                (Field::StatusChanged, &["Status Category Changed"], Optional),
+                (
+                    Field::Started,
+                    &["Custom field (Start date)", "Start date"],
+                    Optional,
+                ),
+                (Field::Description, &["Description"], Optional),
+                (Field::DueDate, &["Due Date", "Due date"], Optional),
+                (Field::IssueType, &["Issue Type"], Optional),
+                (
+                    Field::Epic,
+                    &[
+                        "Parent summary",
+                        "Custom field (Epic Link)",
+                        "Epic Link",
+                        "Parent",
+                    ],
+                    Optional,
+                ),
+                (Field::Risk, &["Custom field (Risk)", "Risk"], Optional),
+                (Field::Labels, &["Labels"], Optional),
+                (Field::Components, &["Component/s", "Components"], Optional),
+                (Field::Sprint, &["Sprint"], Optional),
+                (
+                    Field::FixVersions,
+                    &["Fix Version/s", "Fix versions"],
+                    Optional,
+                ),
+                (
+                    Field::SlaRemaining,
+                    &["Custom field (Time to resolution)", "Time to resolution"],
+                    Optional,
+                ),
+                (Field::Project, &["Project name"], Optional),
+            ],
+            Source::Plan => vec![
+                (Field::Key, &["Issue key", "Key"], Required),
+                (Field::Summary, &["Summary", "Title"], Optional),
+                (Field::Status, &["Status", "Issue status"], Optional),
+                (Field::Assignee, &["Assignee"], Optional),
+                (Field::OriginalEstimate, &["Original Estimate"], Optional),
+                (Field::RemainingEstimate, &["Remaining Estimate"], Optional),
+                (
+                    Field::StoryPoints,
+                    &["Story Points", "Story points", "Story point estimate"],
+                    Optional,
+                ),
+                (Field::Created, &["Created"], Optional),
+                (Field::Resolved, &["Resolved"], Optional),
+                (Field::Description, &["Description"], Optional),
+                (Field::TargetStart, &["Target start"], Optional),
+                (Field::TargetEnd, &["Target end"], Optional),
+                (Field::DueDate, &["Due date", "Due Date"], Optional),
+                (Field::Team, &["Team"], Optional),
+                (
+                    Field::IssueType,
+                    &["Issue Type", "Issue type", "Type"],
+                    Optional,
+                ),
+                (Field::Epic, &["Parent summary", "Parent"], Optional),
+                (Field::Risk, &["Risk"], Optional),
+                (Field::Labels, &["Labels"], Optional),
+                (Field::Components, &["Components", "Component/s"], Optional),
+                (Field::Sprint, &["Sprint", "Sprints"], Optional),
+                (Field::FixVersions, &["Fix versions", "Releases"], Optional),
+                (Field::Project, &["Project", "Project name"], Optional),
+            ],
+            Source::YouTrack => vec![
+                (Field::Key, &["Issue Id"], Required),
+                (Field::Summary, &["Summary"], Optional),
+                (Field::Status, &["State"], Optional),
+                (Field::Assignee, &["Assignee"], Optional),
+                (Field::OriginalEstimate, &["Estimation"], Optional),
+                (Field::StoryPoints, &["Story points"], Optional),
+                (Field::TimeSpent, &["Spent time"], Optional),
+                (Field::Created, &["Created"], Optional),
+                (Field::Resolved, &["Resolved"], Optional),
+                (Field::Description, &["Description"], Optional),
+                (Field::DueDate, &["Due Date"], Optional),
+                (Field::IssueType, &["Type"], Optional),
+                (Field::Epic, &["Epic"], Optional),
+                (Field::Risk, &["Risk"], Optional),
+                (Field::Labels, &["Tags"], Optional),
+                (Field::Components, &["Subsystem"], Optional),
+                (Field::Sprint, &["Sprint", "Sprints"], Optional),
+                (Field::FixVersions, &["Fix versions"], Optional),
+                (Field::Project, &["Project"], Optional),
+            ],
+            Source::GitLab => vec![
+                (Field::Key, &["Issue ID"], Required),
+                (Field::Summary, &["Title"], Optional),
+                (Field::Status, &["State"], Optional),
+                (Field::Assignee, &["Assignee"], Optional),
+                (Field::OriginalEstimate, &["Time Estimate"], Optional),
+                (Field::StoryPoints, &["Weight"], Optional),
+                (Field::TimeSpent, &["Time Spent"], Optional),
+                (Field::Created, &["Created At (UTC)"], Optional),
+                (Field::Resolved, &["Closed At (UTC)"], Optional),
+                (Field::Description, &["Description"], Optional),
+                (Field::DueDate, &["Due Date"], Optional),
+                (Field::IssueType, &["Type"], Optional),
+                (Field::Epic, &["Epic Title"], Optional),
+                (Field::Risk, &["Risk"], Optional),
+                (Field::Labels, &["Labels"], Optional),
+                (Field::Sprint, &["Iteration"], Optional),
+                (Field::FixVersions, &["Milestone"], Optional),
+            ],
+            Source::Generic => vec![
+                (Field::Key, &["Key", "ID", "Id", "Issue"], Required),
+                (Field::Summary, &["Summary", "Title", "Name"], Optional),
+                (Field::Status, &["Status", "State"], Optional),
+                (
+                    Field::Assignee,
+                    &["Assignee", "Owner", "Resource"],
+                    Optional,
+                ),
+                (
+                    Field::OriginalEstimate,
+                    &["Estimate", "Original Estimate"],
+                    Optional,
+                ),
+                (
+                    Field::RemainingEstimate,
+                    &["Remaining Estimate", "Remaining"],
+                    Optional,
+                ),
+                (Field::StoryPoints, &["Story Points", "Points"], Optional),
+                (Field::TimeSpent, &["Time Spent", "Spent"], Optional),
+                (Field::Created, &["Created"], Optional),
+                (Field::Resolved, &["Resolved", "Closed"], Optional),
+                (
+                    Field::StatusChanged,
+                    &["Status changed", "Status Changed"],
+                    Optional,
+                ),
+                (Field::Started, &["Started"], Optional),
+                (Field::Description, &["Description"], Optional),
+                (
+                    Field::TargetStart,
+                    &["Start", "Start date", "Start Date"],
+                    Optional,
+                ),
+                (Field::TargetEnd, &["End", "End date", "End Date"], Optional),
+                (Field::DueDate, &["Due", "Due date", "Due Date"], Optional),
+                (Field::Team, &["Team"], Optional),
+                (
+                    Field::IssueType,
+                    &["Type", "Issue Type", "Issue type"],
+                    Optional,
+                ),
+                (Field::Epic, &["Epic", "Parent"], Optional),
+                (Field::Risk, &["Risk"], Optional),
+                (Field::Labels, &["Labels", "Tags"], Optional),
+                (
+                    Field::Components,
+                    &["Component/s", "Components", "Component"],
+                    Optional,
+                ),
+                (Field::Sprint, &["Sprint", "Iteration"], Optional),
+                (
+                    Field::FixVersions,
+                    &["Fix Version/s", "Fix versions", "Release", "Milestone"],
+                    Optional,
+                ),
+                (
+                    Field::SlaRemaining,
+                    &["Time to resolution", "SLA remaining"],
+                    Optional,
+                ),
+                (Field::Project, &["Project name", "Project"], Optional),
+            ],
+        };
+
+        ColumnMap {
+            source,
+            columns: columns
+                .into_iter()
+                .map(|(field, names, presence)| {
+                    (
+                        field,
+                        names.iter().map(|name| name.to_string()).collect(),
+                        presence,
+                    )
+                })
+                .collect(),
+            // YouTrack estimates are in minutes, the others in seconds
+            estimate_unit: match source {
+                Source::YouTrack => 60,
+                _ => 1,
+            },
+        }
+    }
+
+    /// Read a field from other columns, in preference to those the source
+    /// normally has for it
+    fn prefer(&mut self, field: Field, preferred: Vec<String>) {
+        match self.columns.iter_mut().find(|(f, _, _)| *f == field) {
+            Some((_, names, _)) => {
+                names.splice(0..0, preferred);
+            }
+            None => self.columns.push((field, preferred, Presence::Optional)),
+        }
+    }
+
+    /// Read the start and end dates from a pair of custom fields, in
+    /// preference to the columns the source normally has for them
+    pub fn with_date_fields(mut self, date_fields: Option<&DateFields>) -> ColumnMap {
+        if let Some(date_fields) = date_fields {
+            self.prefer(Field::TargetStart, vec![date_fields.start.to_owned()]);
+            self.prefer(Field::TargetEnd, vec![date_fields.end.to_owned()]);
+        }
+
+        self
+    }
+
+    /// Read fields from the columns a config file gives for them, in
+    /// preference to those the source normally has
+    pub fn with_columns(mut self, columns: &[(Field, Vec<String>)]) -> ColumnMap {
+        for (field, names) in columns.iter() {
+            self.prefer(*field, names.to_owned());
+        }
+
+        self
+    }
+
+    /// Explain how to get a missing column into the export
+    fn export_hint(&self, name: &str) -> String {
+        match self.source {
+            Source::Jira => format!(
+                "Export with Jira's 'Export Excel CSV (all fields)' option, or add the '{}' column \
+                 to the issue search results before using 'Export Excel CSV (current fields)'",
+                name
+            ),
+            Source::Plan => format!(
+                "Show the '{}' field in the plan before exporting it to CSV",
+                name
+            ),
+            _ => format!("Include the '{}' column when exporting", name),
+        }
+    }
+
+    /// Find the column index of each field in the header row. Missing
+    /// recommended columns are returned as warnings.
+    pub fn index(
+        &self,
+        headers: &StringRecord,
+    ) -> Result<(ColumnIndex, Vec<String>), Box<dyn Error>> {
+        let mut indices = HashMap::new();
+        let mut warnings = vec![];
+
+        for (field, names, presence) in self.columns.iter() {
+            // Every column with the first name that is in the export
+            let positions: Vec<usize> = names
+                .iter()
+                .map(|name| {
+                    headers
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, header)| header.trim() == name)
+                        .map(|(position, _)| position)
+                        .collect::<Vec<usize>>()
+                })
+                .find(|positions| !positions.is_empty())
+                .unwrap_or_default();
+
+            match (positions.is_empty(), presence) {
+                (false, _) => {
+                    indices.insert(*field, positions);
+                }
+                (true, Presence::Required) => {
+                    return Err(From::from(format!(
+                        "Column '{}' is missing from the export. {}",
+                        names[0],
+                        self.export_hint(&names[0])
+                    )));
+                }
+                (true, Presence::Recommended) => warnings.push(format!(
+                    "Column '{}' is missing from the export, so items will have no duration. {}",
+                    names[0],
+                    self.export_hint(&names[0])
+                )),
+                (true, Presence::Optional) => (),
+            }
+        }
+
+        // Jira names link columns after the link type, e.g. `Outward issue
+        // link (Blocks)`, with a column for each link of the type
+        let links = headers
+            .iter()
+            .enumerate()
+            .filter_map(|(position, header)| {
+                let header = header.trim();
+                let (outward, kind) =
+                    if let Some(kind) = header.strip_prefix("Outward issue link (") {
+                        (true, kind)
+                    } else {
+                        (false, header.strip_prefix("Inward issue link (")?)
+                    };
+
+                Some((position, kind.strip_suffix(')')?.to_owned(), outward))
+            })
+            .collect();
+
+        Ok((
+            ColumnIndex {
+                indices,
+                links,
+                estimate_unit: self.estimate_unit,
+            },
+            warnings,
+        ))
+    }
+}
+
+/// The column positions of the fields found in a particular export
+pub struct ColumnIndex {
+    indices: HashMap<Field, Vec<usize>>,
+    /// The position, link type and direction of each issue link column
+    links: Vec<(usize, String, bool)>,
+    /// The number of seconds in one unit of a plain number estimate
+    pub estimate_unit: u32,
+}
+
+impl ColumnIndex {
+    /// Get the value of a field in a row, or an empty string if the field
+    /// isn't in the export
+    pub fn get<'r>(&self, record: &'r StringRecord, field: Field) -> &'r str {
+        self.indices
+            .get(&field)
+            .and_then(|indices| record.get(indices[0]))
+            .map(|value| value.trim())
+            .unwrap_or("")
+    }
+
+    /// Get the issues a row links to
+    pub fn get_links(&self, record: &StringRecord) -> Vec<IssueLink> {
+        self.links
+            .iter()
+            .filter_map(|(position, kind, outward)| {
+                record
+                    .get(*position)
+                    .map(|key| key.trim())
+                    .filter(|key| !key.is_empty())
+                    .map(|key| IssueLink {
+                        kind: kind.to_owned(),
+                        outward: *outward,
+                        key: key.to_owned(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Get the non-empty values of a field that can be in several columns
+    pub fn get_all<'r>(&self, record: &'r StringRecord, field: Field) -> Vec<&'r str> {
+        self.indices
+            .get(&field)
+            .map(|indices| {
+                indices
+                    .iter()
+                    .filter_map(|index| record.get(*index))
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,455 @@
+use crate::{
+    columns::{ColumnMapping, Field},
+    convert::JIRA_DAY_IN_SECONDS,
+    fetch::Auth,
+    filter,
+};
+use chrono::{NaiveDate, Weekday};
+use clap::ValueEnum;
+use easy_error::{self, ResultExt};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The config files looked for in the current directory when `--config`
+/// isn't given, in order
+const DEFAULT_FILES: [&str; 2] = ["jira_to_gantt.toml", "jira_to_gantt.json5"];
+
+/// Whether issues in a status are still to be done
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusKind {
+    Open,
+    Closed,
+}
+
+/// Where the duration of an issue can come from, in the order they are tried
+#[derive(Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EstimateSource {
+    /// The work still to do
+    RemainingEstimate,
+    OriginalEstimate,
+    /// Story points, of `daysPerPoint` days each
+    StoryPoints,
+    /// The default duration for the type of issue, tried after sprints
+    Default,
+}
+
+/// One column name or several, in order of preference
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ColumnNames {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl ColumnNames {
+    pub fn names(&self) -> Vec<String> {
+        match self {
+            ColumnNames::One(name) => vec![name.to_owned()],
+            ColumnNames::Many(names) => names.to_owned(),
+        }
+    }
+}
+
+/// A team of people, which may be one of the members of another team through
+/// its manager
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Team {
+    pub manager: Option<String>,
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+/// A Jira site to fetch issues from, for companies with more than one. The
+/// secrets are read from environment variables so they aren't kept in the
+/// file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Site {
+    pub url: String,
+    /// The Jira Cloud account email or Data Center username
+    pub user: Option<String>,
+    /// The environment variable with the user's API token or password
+    pub token_env: Option<String>,
+    /// The environment variable with a Data Center personal access token
+    pub pat_env: Option<String>,
+}
+
+impl Site {
+    pub fn auth(&self, name: &str) -> Result<Auth, Box<dyn Error>> {
+        let secret = |var: &String| {
+            env::var(var).map_err(|_| {
+                format!(
+                    "Site '{}' in the config file needs its secret in the environment variable {}",
+                    name, var
+                )
+            })
+        };
+
+        match (&self.user, &self.token_env, &self.pat_env) {
+            (None, None, Some(var)) => Ok(Auth::PersonalAccessToken(secret(var)?)),
+            (Some(user), Some(var), None) => Ok(Auth::Basic {
+                user: user.to_owned(),
+                secret: secret(var)?,
+            }),
+            (None, None, None) => Ok(Auth::Anonymous),
+            _ => Err(From::from(format!(
+                "Site '{}' in the config file needs either a user and tokenEnv, or patEnv",
+                name
+            ))),
+        }
+    }
+}
+
+/// Settings a team uses for every export, so they don't have to be passed on
+/// each run
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Config {
+    /// The title of the chart, unless the source of the issues gives one
+    pub title: Option<String>,
+    /// Column names to read each field from, tried before the usual ones,
+    /// e.g. `summary = "Headline"`
+    #[serde(default, rename = "columns")]
+    column_names: HashMap<String, ColumnNames>,
+    /// The fields of `column_names`, once they are checked
+    #[serde(skip)]
+    pub columns: Vec<(Field, Vec<String>)>,
+    /// More `chrono` formats for dates and date times, tried before the
+    /// usual ones, e.g. `%d.%m.%Y`
+    #[serde(default)]
+    pub date_formats: Vec<String>,
+    /// Whether issues in each status are open or closed, e.g. `Done =
+    /// "closed"`. Statuses are matched ignoring case, and only `Closed` is
+    /// closed unless given here.
+    #[serde(default)]
+    pub statuses: HashMap<String, StatusKind>,
+    /// The statuses of closed issues, or patterns where `*` matches any run
+    /// of characters, e.g. `["Done", "Resolved", "*Closed*"]`, for those not
+    /// in `statuses`. Without these only `Closed` is closed.
+    #[serde(default)]
+    pub closed_statuses: Vec<String>,
+    /// The days an issue of each type takes when nothing else gives it a
+    /// duration, with `*` for any other type
+    #[serde(default)]
+    pub default_durations: HashMap<String, i64>,
+    /// People who have left or whose accounts are deactivated, whose open
+    /// issues need someone else
+    #[serde(default)]
+    pub former_assignees: Vec<String>,
+    /// The names to show for people, by their account id, username, email
+    /// or another spelling of their name, matched ignoring case
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    /// The days of work each lane gets through in a sprint, with `*` for any
+    /// other lane, for suggesting sprints
+    #[serde(default)]
+    pub sprint_capacity: HashMap<String, f64>,
+    /// The hours in a working day, for turning estimates into days, if not
+    /// Jira's usual eight
+    pub hours_per_day: Option<f64>,
+    /// The days of the week that nobody works, e.g. `["Fri", "Sat"]`
+    #[serde(default, rename = "weekendDays")]
+    weekend_day_names: Vec<String>,
+    /// The days of `weekend_day_names`, once they are checked
+    #[serde(skip)]
+    weekend_days: Vec<Weekday>,
+    /// Days that nobody works, e.g. `"2023-12-25"`, as well as those of
+    /// `--holidays`
+    #[serde(default)]
+    pub holidays: Vec<NaiveDate>,
+    /// Skip weekends and holidays when counting the days of an item, the same
+    /// as `--working-days`
+    #[serde(default)]
+    pub working_days: bool,
+    /// The sources of an issue's duration, in the order they are tried, e.g.
+    /// `["remaining-estimate", "original-estimate", "default"]`
+    #[serde(default)]
+    pub estimate_order: Vec<EstimateSource>,
+    /// The days of work in a story point
+    pub days_per_point: Option<f64>,
+    /// The days that research issues are timeboxed to, whatever their
+    /// estimates, the same as `--spike-duration`
+    pub spike_duration: Option<u32>,
+    /// The issue types and labels of research issues, e.g. `["Spike",
+    /// "Investigation"]`, matched ignoring case. Without these only `Spike`
+    /// is.
+    #[serde(default)]
+    pub spikes: Vec<String>,
+    /// The teams people are in, by name, for rolling their lanes up. These
+    /// are sorted so that someone in two teams is always put in the same one.
+    #[serde(default)]
+    pub teams: BTreeMap<String, Team>,
+    /// The Jira sites sources can be fetched from, by name, e.g. with
+    /// `--source jira:acme`
+    #[serde(default)]
+    pub sites: BTreeMap<String, Site>,
+}
+
+impl Config {
+    /// Read the config file given, or else the first default file in the
+    /// current directory, or else use the defaults
+    pub fn load(path: Option<&Path>) -> Result<Config, Box<dyn Error>> {
+        let path = match path {
+            Some(path) => path.to_owned(),
+            None => match DEFAULT_FILES
+                .iter()
+                .map(PathBuf::from)
+                .find(|path| path.is_file())
+            {
+                Some(path) => path,
+                None => return Ok(Config::default()),
+            },
+        };
+        let content = fs::read_to_string(&path).context(format!(
+            "Unable to read config file '{}'",
+            path.to_string_lossy()
+        ))?;
+        let invalid = |err: String| {
+            format!(
+                "Config file '{}' is invalid: {}",
+                path.to_string_lossy(),
+                err
+            )
+        };
+        let mut config: Config = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|err| invalid(err.to_string()))?,
+            _ => json5::from_str(&content).map_err(|err| invalid(err.to_string()))?,
+        };
+
+        // Fields are in camel case like the rest of the file, e.g. issueType
+        for (name, names) in config.column_names.iter() {
+            let field = Field::from_name(name)
+                .ok_or_else(|| invalid(format!("'{}' isn't a field that can be read", name)))?;
+
+            config.columns.push((field, names.names()));
+        }
+
+        if let Some((issue_type, days)) =
+            config.default_durations.iter().find(|(_, days)| **days < 1)
+        {
+            return Err(From::from(invalid(format!(
+                "the default duration of '{}' must be at least one day, not {}",
+                issue_type, days
+            ))));
+        }
+
+        if let Some((lane, days)) = config
+            .sprint_capacity
+            .iter()
+            .find(|(_, days)| !days.is_finite() || **days <= 0.0)
+        {
+            return Err(From::from(invalid(format!(
+                "the sprint capacity of '{}' must be more than zero days, not {}",
+                lane, days
+            ))));
+        }
+
+        if let Some(days) = config
+            .days_per_point
+            .filter(|days| !days.is_finite() || *days <= 0.0)
+        {
+            return Err(From::from(invalid(format!(
+                "the days per story point must be more than zero, not {}",
+                days
+            ))));
+        }
+
+        for name in config.weekend_day_names.iter() {
+            let day = name
+                .parse::<Weekday>()
+                .map_err(|_| invalid(format!("'{}' isn't a day of the week", name)))?;
+
+            config.weekend_days.push(day);
+        }
+
+        if (0..7).all(|n| {
+            config
+                .weekend_days
+                .iter()
+                .any(|day| day.num_days_from_monday() == n)
+        }) {
+            return Err(From::from(invalid(
+                "every day of the week can't be a weekend day".to_owned(),
+            )));
+        }
+
+        if let Some(hours) = config.hours_per_day {
+            check_hours_per_day(hours).map_err(invalid)?;
+        }
+
+        if config.spike_duration == Some(0) {
+            return Err(From::from(invalid(
+                "the spike duration must be at least one day".to_owned(),
+            )));
+        }
+
+        Ok(config)
+    }
+
+    /// Read fields from columns given on the command line, in preference to
+    /// those in the file. Columns for the same field are tried in the order
+    /// they are given.
+    pub fn map_columns(&mut self, mappings: &[ColumnMapping]) {
+        let mut columns: Vec<(Field, Vec<String>)> = vec![];
+
+        for mapping in mappings.iter() {
+            match columns
+                .iter_mut()
+                .find(|(field, _)| *field == mapping.field)
+            {
+                Some((_, names)) => names.push(mapping.column.to_owned()),
+                None => columns.push((mapping.field, vec![mapping.column.to_owned()])),
+            }
+        }
+
+        // Columns given later are preferred
+        self.columns.extend(columns);
+    }
+
+    /// The seconds in a working day, which Jira's estimates of days are in
+    pub fn day_seconds(&self) -> f64 {
+        self.hours_per_day
+            .map_or(JIRA_DAY_IN_SECONDS as f64, |hours| hours * 3600.0)
+    }
+
+    /// The sources of an issue's duration in the order they are tried, the
+    /// original estimate and then the default duration unless the file says
+    /// otherwise
+    pub fn estimate_order(&self) -> &[EstimateSource] {
+        if self.estimate_order.is_empty() {
+            &[EstimateSource::OriginalEstimate, EstimateSource::Default]
+        } else {
+            &self.estimate_order
+        }
+    }
+
+    /// The seconds of work in a story point, a day unless the file says
+    /// otherwise
+    pub fn point_seconds(&self) -> f64 {
+        self.days_per_point.unwrap_or(1.0) * self.day_seconds()
+    }
+
+    /// The days of the week that nobody works, Saturday and Sunday unless
+    /// the file says otherwise
+    pub fn weekend_days(&self) -> Vec<Weekday> {
+        if self.weekend_days.is_empty() {
+            vec![Weekday::Sat, Weekday::Sun]
+        } else {
+            self.weekend_days.clone()
+        }
+    }
+
+    /// Whether an issue in a status is still to be done
+    pub fn is_open(&self, status: &str) -> bool {
+        match self
+            .statuses
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(status))
+        {
+            Some((_, kind)) => *kind == StatusKind::Open,
+            None if self.closed_statuses.is_empty() => status != "Closed",
+            None => !filter::any_match(&self.closed_statuses, status),
+        }
+    }
+
+    /// Whether an issue is research, to timebox, from its type or labels
+    pub fn is_spike(&self, issue_type: &str, labels: &[String]) -> bool {
+        let is = |name: &str| {
+            if self.spikes.is_empty() {
+                name.eq_ignore_ascii_case("spike")
+            } else {
+                self.spikes
+                    .iter()
+                    .any(|spike| spike.to_lowercase() == name.to_lowercase())
+            }
+        };
+
+        is(issue_type) || labels.iter().any(|label| is(label))
+    }
+
+    /// The Jira site with a name
+    pub fn site(&self, name: &str) -> Result<&Site, Box<dyn Error>> {
+        self.sites
+            .get(name)
+            .ok_or_else(|| From::from(format!("There is no site '{}' in the config file", name)))
+    }
+
+    /// The name to show for someone, if they have an alias
+    pub fn alias(&self, name: &str) -> Option<&str> {
+        self.aliases
+            .iter()
+            .find(|(alias, _)| alias.to_lowercase() == name.to_lowercase())
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// Whether someone has left, matched ignoring case
+    pub fn is_former(&self, assignee: &str) -> bool {
+        self.former_assignees
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(assignee))
+    }
+
+    /// The team someone is in, either as its manager or as one of its
+    /// members, matched ignoring case. A manager is in the team they manage
+    /// rather than the one they are a member of.
+    pub fn team_of(&self, person: &str) -> Option<&str> {
+        let is = |name: &String| name.eq_ignore_ascii_case(person);
+
+        self.teams
+            .iter()
+            .find(|(_, team)| team.manager.as_ref().is_some_and(is))
+            .or_else(|| {
+                self.teams
+                    .iter()
+                    .find(|(_, team)| team.members.iter().any(is))
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The team that the manager of a team is a member of
+    pub fn parent_team(&self, team: &str) -> Option<&str> {
+        let manager = self.teams.get(team)?.manager.as_ref()?;
+
+        self.teams
+            .iter()
+            .find(|(name, parent)| {
+                name.as_str() != team
+                    && parent
+                        .members
+                        .iter()
+                        .any(|member| member.eq_ignore_ascii_case(manager))
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The days an issue of a type takes when nothing else gives it a
+    /// duration
+    pub fn default_duration(&self, issue_type: &str) -> Option<i64> {
+        self.default_durations
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(issue_type))
+            .or_else(|| self.default_durations.get_key_value("*"))
+            .map(|(_, days)| *days)
+    }
+}
+
+/// Check the hours in a working day are in a day
+pub fn check_hours_per_day(hours: f64) -> Result<(), String> {
+    if hours.is_finite() && hours > 0.0 && hours <= 24.0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "the hours per day must be more than 0 and at most 24, not {}",
+            hours
+        ))
+    }
+}
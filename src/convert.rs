@@ -0,0 +1,769 @@
+use crate::{
+    allocation::{self, Allocation},
+    chart::{Chart, DurationSource, Group, Item, Sprint},
+    columns::{DateFields, Field, Source},
+    config::{Config, EstimateSource},
+    filter::Filter,
+    hours::{Granularity, WorkingHours},
+    i18n::Translations,
+    ignore::IgnoreList,
+    labels::LabelLanes,
+    names::NameResolver,
+    number::NumberFormat,
+    pto::Pto,
+    record::{GroupBy, JiraRecord},
+    rejects::Rejects,
+    spill::{MemorySize, Spill},
+    start::InferStart,
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use gantt_chart::ItemData;
+use std::{cell::RefCell, cmp::Ordering, collections::HashMap, error::Error};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// The length of a working day in Jira's estimates, unless the config says
+/// otherwise
+pub const JIRA_DAY_IN_SECONDS: f32 = 8.0 * 60.0 * 60.0;
+/// The color of items that need reassigning, so their lane stands out
+const REASSIGN_COLOR: &str = "#d62728";
+
+/// Options controlling how an export is converted into a chart
+#[derive(Clone, Copy)]
+pub struct ConvertOptions<'a> {
+    /// The tool that produced the export, or `None` to detect it
+    pub source: Option<Source>,
+    pub tooltips: bool,
+    /// What the lanes of the chart are. With two levels the first gives
+    /// sections of the chart, each with its own lanes for the second.
+    pub group_by: &'a [GroupBy],
+    /// Sort lanes by name and items by key instead of keeping export order
+    pub stable_sort: bool,
+    /// Put the issues of each lane in the order their SLAs run out, with
+    /// those without an SLA last
+    pub sla_order: bool,
+    /// Only convert this many issues from each export
+    pub head: Option<usize>,
+    /// How numbers such as estimates are written in the export
+    pub numbers: NumberFormat,
+    /// The language of generated strings
+    pub text: &'a Translations,
+    /// How much of their time resources spend on this work, which spreads
+    /// estimates over more days
+    pub allocations: &'a [Allocation],
+    /// Whether to work in days or working hours before rounding to days
+    pub granularity: Granularity,
+    pub working_hours: &'a WorkingHours,
+    /// The weekends, holidays and time off that working hours go around
+    pub pto: &'a Pto,
+    /// Where to write rows that are skipped or can't be read, instead of
+    /// stopping at the first bad row
+    pub rejects: Option<&'a Rejects>,
+    /// Lanes from labels with a prefix, instead of from the assignee
+    pub label_lanes: Option<&'a LabelLanes>,
+    /// The settings from the config file
+    pub config: &'a Config,
+    /// Custom fields to read the start and end dates from
+    pub date_fields: Option<&'a DateFields>,
+    /// The types of issue link, e.g. `Blocks`, that make the issue at the
+    /// outward end depend on the one at the inward end
+    pub link_types: &'a [String],
+    /// The fields to pass through to each item as they were read
+    pub include_fields: &'a [Field],
+    /// The most days an item can take
+    pub max_duration: Option<i64>,
+    /// Chart closed issues from when they were created to when they were
+    /// resolved, rather than from their estimates
+    pub actuals: bool,
+    /// The label of issues that are stretch scope rather than committed
+    pub stretch_label: Option<&'a str>,
+    /// Looks up the display names of assignees given as account ids or
+    /// usernames
+    pub names: Option<&'a NameResolver<'a>>,
+    /// Issues to leave out of the chart
+    pub ignore: Option<&'a IgnoreList>,
+    /// Which issues go on the chart, by their fields
+    pub filter: &'a Filter,
+    /// Put assignees whose names differ only in case in the same lane
+    pub ignore_name_case: bool,
+    /// Roughly how much memory the items may take up while the export is
+    /// read, before they are written to temporary files
+    pub max_memory: Option<MemorySize>,
+    /// Problems with the issues that don't stop the conversion, to warn
+    /// about once the chart is built
+    pub warnings: &'a RefCell<Vec<String>>,
+    /// How to infer when work on issues without planned dates starts
+    pub start: &'a dyn InferStart,
+    /// The day the chart is made
+    pub today: NaiveDate,
+}
+
+/// Builds a chart one record at a time, so records can be converted as they
+/// are read or fetched
+///
+/// Resources are in the order their first issue is added, and the items of
+/// each resource are in the order they are added. With sections, the
+/// resources of each section are kept together and the sections are in the
+/// order their first issue is added. With `stable_sort` the records are
+/// instead held until the end and sorted, so that the order of the export
+/// doesn't matter. With `sla_order` they are also held, and sorted by the
+/// time left on their SLAs before anything but their lanes.
+pub struct ChartBuilder<'a> {
+    options: ConvertOptions<'a>,
+    /// The section, if there are sections, and lane of each resource
+    resources: Vec<(Option<String>, String)>,
+    resource_items: Vec<Vec<Item>>,
+    /// When the next item of each resource can start, working in hours
+    cursors: Vec<Option<NaiveDateTime>>,
+    /// The day after the last item so far of each resource, working in days
+    lane_ends: Vec<Option<NaiveDate>>,
+    /// The sprints with dates that issues are in
+    sprints: Vec<Sprint>,
+    pending: Vec<JiraRecord>,
+    /// Issues that must finish before others, from links found on the
+    /// issues before them, as the keys before and after
+    successors: Vec<(String, String)>,
+    /// Where the items go when they take up too much memory
+    spill: Option<Spill>,
+    /// The first error writing the items out, which `finish` returns
+    spill_error: Option<Box<dyn Error>>,
+    /// The first spelling of each assignee, by their name in lower case, for
+    /// `ignore_name_case`
+    spellings: HashMap<String, String>,
+    /// The projects of the issues, in the order their first issue is added,
+    /// which name the chart
+    projects: Vec<String>,
+}
+
+impl<'a> ChartBuilder<'a> {
+    pub fn new(options: ConvertOptions<'a>) -> ChartBuilder<'a> {
+        ChartBuilder {
+            options,
+            resources: vec![],
+            resource_items: vec![],
+            cursors: vec![],
+            lane_ends: vec![],
+            sprints: vec![],
+            pending: vec![],
+            successors: vec![],
+            spill: options.max_memory.map(Spill::new),
+            spill_error: None,
+            spellings: HashMap::new(),
+            projects: vec![],
+        }
+    }
+
+    /// Add an issue to the chart, unless it is ignored or filtered out.
+    /// Returns whether it was kept.
+    pub fn add(&mut self, record: &JiraRecord) -> bool {
+        if self
+            .options
+            .ignore
+            .is_some_and(|ignore| ignore.ignores(&record.key))
+        {
+            return false;
+        }
+
+        let resolved;
+        let config = self.options.config;
+        let alias = |name: &str| config.alias(name).map(|alias| alias.to_owned());
+        // An alias can be for the account id or for the name Jira gives it
+        let name = alias(&record.assignee).or_else(|| {
+            self.options
+                .names
+                .and_then(|names| names.resolve(&record.assignee))
+                .map(|name| alias(&name).unwrap_or(name))
+        });
+        let assignee = self.spelling(name.as_deref().unwrap_or(&record.assignee));
+        let record = if assignee != record.assignee {
+            resolved = JiraRecord {
+                assignee,
+                ..record.clone()
+            };
+            &resolved
+        } else {
+            record
+        };
+
+        // Filter on display names rather than account ids
+        if !self.options.filter.keeps(record) {
+            return false;
+        }
+
+        if !record.project.is_empty() && !self.projects.contains(&record.project) {
+            self.projects.push(record.project.to_owned());
+        }
+
+        if self.options.stable_sort || self.options.sla_order {
+            self.pending.push(record.clone());
+        } else {
+            self.add_item(record);
+        }
+
+        true
+    }
+
+    /// The one spelling of an assignee's name, with accents composed (NFC)
+    /// so that those typed differently are the same, and with
+    /// `ignore_name_case` the first capitalization that is added
+    fn spelling(&mut self, name: &str) -> String {
+        let name: String = if is_nfc(name) {
+            name.to_owned()
+        } else {
+            name.nfc().collect()
+        };
+
+        if !self.options.ignore_name_case {
+            return name;
+        }
+
+        self.spellings
+            .entry(name.to_lowercase())
+            .or_insert(name)
+            .to_owned()
+    }
+
+    /// The section and lane of a record
+    fn lane<'r>(&self, record: &'r JiraRecord) -> (Option<&'r str>, &'r str)
+    where
+        'a: 'r,
+    {
+        let lane = |group_by: GroupBy| self.lane_by(record, group_by);
+
+        match self.options.group_by {
+            [section, lane_by, ..] => (Some(lane(*section)), lane(*lane_by)),
+            [lane_by] => (None, lane(*lane_by)),
+            [] => (None, lane(GroupBy::Assignee)),
+        }
+    }
+
+    /// The stretch label of a record, as it is written on the record, if it
+    /// is stretch scope
+    fn stretch_label<'r>(&self, record: &'r JiraRecord) -> Option<&'r str> {
+        let stretch_label = self.options.stretch_label?;
+
+        record
+            .labels
+            .iter()
+            .find(|label| label.eq_ignore_ascii_case(stretch_label))
+            .map(|label| label.as_str())
+    }
+
+    /// Whether a record is open but its assignee has left
+    fn needs_reassignment(&self, record: &JiraRecord) -> bool {
+        !record.assignee.is_empty()
+            && (record.assignee_inactive || self.options.config.is_former(&record.assignee))
+            && self.options.config.is_open(&record.status)
+    }
+
+    /// The name of a record's lane for one way of grouping
+    fn lane_by<'r>(&self, record: &'r JiraRecord, group_by: GroupBy) -> &'r str
+    where
+        'a: 'r,
+    {
+        match (group_by, self.options.label_lanes) {
+            (GroupBy::Assignee, Some(label_lanes)) => label_lanes.lane(record),
+            (GroupBy::Assignee, None) if self.needs_reassignment(record) => {
+                self.options.text.get("needs-reassignment")
+            }
+            // Stretch issues are named after the label and committed ones
+            // are left empty, to be named 'committed'
+            (GroupBy::Commitment, _) => self.stretch_label(record).unwrap_or_default(),
+            _ => record.lane(group_by),
+        }
+    }
+
+    /// The seconds of work in an issue, from the first source of the config's
+    /// estimate order that the issue has
+    fn estimate(&self, record: &JiraRecord) -> Option<(u32, DurationSource)> {
+        let config = self.options.config;
+
+        // Research is timeboxed, so it is on the plan even when nobody can
+        // estimate it
+        if let Some(days) = config.spike_duration {
+            if config.is_spike(&record.issue_type, &record.labels) {
+                return Some((
+                    (days as f64 * config.day_seconds()).round() as u32,
+                    DurationSource::Spike,
+                ));
+            }
+        }
+
+        config
+            .estimate_order()
+            .iter()
+            .find_map(|source| match source {
+                EstimateSource::RemainingEstimate => record
+                    .remaining_estimate
+                    .map(|seconds| (seconds, DurationSource::RemainingEstimate)),
+                EstimateSource::OriginalEstimate => record
+                    .original_estimate
+                    .map(|seconds| (seconds, DurationSource::Estimate)),
+                EstimateSource::StoryPoints => record.story_points.map(|points| {
+                    (
+                        (points * config.point_seconds()).round() as u32,
+                        DurationSource::StoryPoints,
+                    )
+                }),
+                EstimateSource::Default => None,
+            })
+    }
+
+    fn add_item(&mut self, record: &JiraRecord) {
+        if let Some(label_lanes) = self.options.label_lanes {
+            label_lanes.check(record);
+        }
+
+        let (section, lane) = self.lane(record);
+        let earliest = self
+            .options
+            .start
+            .earliest_start(record, self.options.today);
+        let mut start_date = earliest.or(record.created);
+        let resource_index;
+
+        // Update resources and get the index into the array
+        if let Some(index) = self
+            .resources
+            .iter()
+            .position(|(s, l)| s.as_deref() == section && l == lane)
+        {
+            resource_index = index;
+            start_date = None;
+        } else {
+            resource_index = self.resources.len();
+            self.resources
+                .push((section.map(|s| s.to_owned()), lane.to_owned()));
+            self.resource_items.push(vec![]);
+            self.cursors.push(None);
+            self.lane_ends.push(None);
+        }
+
+        let mut duration: Option<i64> = None;
+        let mut duration_source = None;
+        // Someone working part time on this takes longer to finish it
+        let percent = allocation::percent_for(self.options.allocations, &[lane, &record.assignee]);
+
+        let estimate = self.estimate(record);
+
+        if let Some((seconds, source)) = estimate {
+            let day_seconds = self.options.config.day_seconds();
+            // A timebox is in whole days already
+            let days = if source == DurationSource::Spike {
+                (seconds as f64 / day_seconds).round()
+            } else {
+                ((seconds as f64 + 1.0) / day_seconds).ceil()
+            };
+
+            duration = Some((days * 100.0 / percent).ceil() as i64);
+            duration_source = Some(source);
+        }
+
+        // Planned dates override anything inferred from the estimate
+        if let Some(target_start) = record.target_start {
+            start_date = Some(target_start);
+
+            if let Some(target_end) = record.target_end {
+                if target_end < target_start {
+                    self.options.warnings.borrow_mut().push(format!(
+                        "Issue '{}' ends on {} before it starts on {}, so it takes one day",
+                        record.key, target_end, target_start
+                    ));
+                }
+
+                duration = Some((target_end - target_start).num_days().max(0) + 1);
+                duration_source = Some(DurationSource::TargetDates);
+            }
+        }
+
+        // What actually happened overrides what was planned
+        let actual = match (record.created, record.resolved) {
+            (Some(created), Some(resolved))
+                if self.options.actuals && !self.options.config.is_open(&record.status) =>
+            {
+                if resolved < created {
+                    self.options.warnings.borrow_mut().push(format!(
+                        "Issue '{}' was resolved on {} before it was created on {}, so it takes one day",
+                        record.key, resolved, created
+                    ));
+                }
+
+                start_date = Some(created);
+                duration = Some((resolved - created).num_days().max(0) + 1);
+                duration_source = Some(DurationSource::Actual);
+                Some((created, resolved.max(created)))
+            }
+            _ => None,
+        };
+
+        if self.options.granularity == Granularity::Hours {
+            let cursor = &mut self.cursors[resource_index];
+            let from = match (
+                cursor.or_else(|| earliest.or(record.created).and_then(midnight)),
+                earliest.and_then(midnight),
+            ) {
+                (Some(from), Some(earliest)) => Some(from.max(earliest)),
+                (from, _) => from,
+            };
+            let fixed = actual.or_else(|| {
+                record
+                    .target_start
+                    .map(|start| (start, record.target_end.unwrap_or(start)))
+            });
+
+            match (fixed, from) {
+                // Work in planned or actual dates takes up the whole of those
+                // days
+                (Some((_, end)), _) => {
+                    let end = midnight(end + Duration::days(1));
+
+                    *cursor = (*cursor).max(end);
+                }
+                // Every item gets a start date, because short items can
+                // start on the same day as the one before
+                (None, Some(from)) => {
+                    let seconds = estimate
+                        .map(|(seconds, _)| seconds)
+                        .map(|seconds| (seconds as f64 * 100.0 / percent).ceil() as i64);
+                    let pto = self.options.pto;
+                    let (start, end) =
+                        self.options
+                            .working_hours
+                            .add(from, seconds.unwrap_or(0), &|date| {
+                                pto.is_working_day(lane, date)
+                            });
+
+                    start_date = Some(start.date());
+                    duration = seconds.map(|_| (end.date() - start.date()).num_days() + 1);
+                    *cursor = Some(end);
+                }
+                // Without a date to start from, fall back to whole days
+                (None, None) => (),
+            }
+        }
+
+        // Scrum teams plan unestimated work by sprint, so let it fill its
+        // sprint rather than putting a milestone on its Created date
+        if let (None, None, Some(sprint_start), Some(sprint_end)) = (
+            duration,
+            record.target_start,
+            record.sprint_start,
+            record.sprint_end,
+        ) {
+            start_date = Some(sprint_start);
+            duration = Some((sprint_end - sprint_start).num_days().max(0) + 1);
+            duration_source = Some(DurationSource::Sprint);
+        }
+
+        // Without anything else to go on, the team's usual duration for the
+        // type of issue is better than a milestone
+        if duration.is_none()
+            && self
+                .options
+                .config
+                .estimate_order()
+                .contains(&EstimateSource::Default)
+        {
+            if let Some(days) = self.options.config.default_duration(&record.issue_type) {
+                duration = Some(days);
+                duration_source = Some(DurationSource::Default);
+            }
+        }
+
+        // One typo'd estimate would otherwise stretch the time axis of the
+        // whole chart
+        if let (Some(days), Some(max)) = (duration, self.options.max_duration) {
+            if days > max {
+                self.options.warnings.borrow_mut().push(format!(
+                    "Issue '{}' takes {} days, so it is cut to {} days",
+                    record.key, days, max
+                ));
+                duration = Some(max);
+            }
+        }
+
+        // An issue that can't start yet waits rather than following straight
+        // on from the one before
+        let lane_end = &mut self.lane_ends[resource_index];
+
+        if let (None, Some(earliest)) = (start_date, earliest) {
+            if lane_end.is_none_or(|end| end < earliest) {
+                start_date = Some(earliest);
+            }
+        }
+
+        *lane_end = start_date
+            .or(*lane_end)
+            .map(|start| start + Duration::days(duration.unwrap_or(0)));
+
+        let current = Sprint {
+            name: record.sprint.to_owned(),
+            start_date: record.sprint_start,
+            end_date: record.sprint_end,
+        };
+
+        // The sprints an issue was carried over from are on the chart too
+        for sprint in record.earlier_sprints.iter().chain([&current]) {
+            if sprint.start_date.is_some() && !self.sprints.iter().any(|s| s.name == sprint.name) {
+                self.sprints.push(sprint.clone());
+            }
+        }
+
+        let tooltip = if self.options.tooltips {
+            Some(record.tooltip(
+                self.options.text,
+                if record.assignee.is_empty() {
+                    self.options.text.get("unassigned")
+                } else {
+                    &record.assignee
+                },
+                duration,
+            ))
+        } else {
+            None
+        };
+
+        let assignee = self.lane_by(record, GroupBy::Assignee).to_owned();
+        let former_assignee =
+            if self.needs_reassignment(record) && self.options.label_lanes.is_none() {
+                Some(record.assignee.to_owned())
+            } else {
+                None
+            };
+        let stretch = self
+            .options
+            .stretch_label
+            .map(|_| self.stretch_label(record).is_some());
+        let link_types = self.options.link_types;
+        let mut depends_on: Vec<String> = vec![];
+
+        for link in record.links.iter().filter(|link| {
+            link_types
+                .iter()
+                .any(|kind| kind.eq_ignore_ascii_case(&link.kind))
+        }) {
+            if link.outward {
+                self.successors
+                    .push((record.key.to_owned(), link.key.to_owned()));
+            } else if !depends_on.contains(&link.key) {
+                depends_on.push(link.key.to_owned());
+            }
+        }
+
+        self.resource_items[resource_index].push(Item {
+            id: Some(record.key.to_owned()),
+            data: ItemData {
+                title: record.key.to_owned(),
+                start_date,
+                duration,
+                resource_index: Some(resource_index),
+                open: Some(self.options.config.is_open(&record.status)),
+            },
+            tooltip,
+            sprint: if record.sprint.is_empty() {
+                None
+            } else {
+                Some(record.sprint.to_owned())
+            },
+            risk: record.risk(),
+            progress: match (record.time_spent, record.original_estimate) {
+                (Some(spent), Some(estimate)) if estimate > 0 => {
+                    Some((spent as u64 * 100 / estimate as u64).min(100) as u8)
+                }
+                _ => None,
+            },
+            health: None,
+            color: former_assignee.as_ref().map(|_| REASSIGN_COLOR.to_owned()),
+            blocked: record.blocked.to_owned(),
+            depends_on,
+            stretch,
+            fields: self
+                .options
+                .include_fields
+                .iter()
+                .filter_map(|field| record.field(*field).map(|value| (field.name(), value)))
+                .collect(),
+            duration_source,
+            estimate: estimate.map(|(seconds, _)| seconds),
+            epic: Some(record.epic.to_owned()),
+            assignee: Some(assignee),
+            created: record.created,
+            summary: Some(record.summary.to_owned()),
+            due_date: record.due_date,
+            former_assignee,
+            fix_versions: record.fix_versions.to_owned(),
+            sla_remaining: record.sla_remaining,
+        });
+
+        if let (Some(spill), None) = (self.spill.as_mut(), self.spill_error.as_ref()) {
+            if let Err(err) = spill.hold(resource_index, &mut self.resource_items) {
+                self.spill_error = Some(err);
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> Result<Chart, Box<dyn Error>> {
+        if let Some(err) = self.spill_error.take() {
+            return Err(err);
+        }
+
+        // Sort by section and lane, with issues in no section or lane last,
+        // then by issue key. Sorting by SLA alone is stable, so lanes stay in
+        // the order their most urgent issue appears.
+        let mut pending = std::mem::take(&mut self.pending);
+        let sla = |record: &JiraRecord| {
+            (
+                record.sla_remaining.is_none(),
+                record.sla_remaining.unwrap_or_default(),
+            )
+        };
+
+        pending.sort_by(|a, b| {
+            let by_sla = if self.options.sla_order {
+                sla(a).cmp(&sla(b))
+            } else {
+                Ordering::Equal
+            };
+
+            if !self.options.stable_sort {
+                return by_sla;
+            }
+
+            let ((a_section, a_lane), (b_section, b_lane)) = (self.lane(a), self.lane(b));
+            let a_section = a_section.unwrap_or_default();
+            let b_section = b_section.unwrap_or_default();
+
+            (a_section.is_empty(), a_section, a_lane.is_empty(), a_lane)
+                .cmp(&(b_section.is_empty(), b_section, b_lane.is_empty(), b_lane))
+                .then(by_sla)
+                .then_with(|| compare_keys(&a.key, &b.key))
+        });
+        pending.iter().for_each(|record| self.add_item(record));
+
+        // Keep the resources of each section together, in the order the
+        // sections first appear
+        let mut sections: Vec<Option<String>> = vec![];
+
+        for (section, _) in self.resources.iter() {
+            if !sections.contains(section) {
+                sections.push(section.to_owned());
+            }
+        }
+
+        let mut order: Vec<usize> = (0..self.resources.len()).collect();
+
+        order.sort_by_key(|index| {
+            sections
+                .iter()
+                .position(|section| *section == self.resources[*index].0)
+        });
+
+        let mut new_index = vec![0; order.len()];
+
+        for (new, old) in order.iter().enumerate() {
+            new_index[*old] = new;
+        }
+
+        // Name the empty section or lane after what is missing, e.g. 'unassigned'
+        let text = self.options.text;
+        let name = |value: &str, group_by: GroupBy| {
+            if value.is_empty() {
+                text.get(group_by.empty_label()).to_owned()
+            } else {
+                value.to_owned()
+            }
+        };
+        let (section_by, lane_by) = match self.options.group_by {
+            [section, lane, ..] => (*section, *lane),
+            [lane] => (*lane, *lane),
+            [] => (GroupBy::Assignee, GroupBy::Assignee),
+        };
+        let mut resources = vec![];
+        let mut groups: Vec<Group> = vec![];
+
+        for (new, old) in order.iter().enumerate() {
+            let (ref section, ref lane) = self.resources[*old];
+
+            match section {
+                Some(section) => {
+                    let section = name(section, section_by);
+
+                    resources.push(format!("{}: {}", section, name(lane, lane_by)));
+
+                    match groups.last_mut() {
+                        Some(group) if group.name == section => group.resources.push(new),
+                        _ => groups.push(Group {
+                            name: section,
+                            color: None,
+                            resources: vec![new],
+                        }),
+                    }
+                }
+                None => resources.push(name(lane, lane_by)),
+            }
+        }
+
+        let mut resource_items = std::mem::take(&mut self.resource_items);
+        let mut items = vec![];
+
+        for old in order.iter() {
+            let mut lane_items = std::mem::take(&mut resource_items[*old]);
+
+            if let Some(ref mut spill) = self.spill {
+                lane_items = spill.take(*old, lane_items)?;
+            }
+
+            items.extend(lane_items.into_iter().map(|mut item| {
+                item.data.resource_index = item.data.resource_index.map(|i| new_index[i]);
+                item
+            }));
+        }
+
+        // Links to issues that aren't in the chart are left out, since
+        // there is nothing to draw them to
+        let ids: HashMap<String, usize> = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (item.id().to_owned(), index))
+            .collect();
+
+        for (before, after) in std::mem::take(&mut self.successors) {
+            if let Some(item) = ids.get(&after).map(|index| &mut items[*index]) {
+                if !item.depends_on.contains(&before) {
+                    item.depends_on.push(before);
+                }
+            }
+        }
+
+        for item in items.iter_mut() {
+            item.depends_on.retain(|id| ids.contains_key(id));
+        }
+
+        Ok(Chart {
+            resources,
+            items,
+            groups,
+            sprints: std::mem::take(&mut self.sprints),
+            ..Chart::new(&self.projects.join(", "))
+        })
+    }
+}
+
+fn midnight(date: NaiveDate) -> Option<NaiveDateTime> {
+    date.and_hms_opt(0, 0, 0)
+}
+
+/// Compare issue keys so that `PROJ-9` comes before `PROJ-10`
+fn compare_keys(a: &str, b: &str) -> Ordering {
+    let split = |key: &str| match key.rsplit_once('-') {
+        Some((project, number)) => match number.parse::<u64>() {
+            Ok(number) => (project.to_owned(), Some(number), key.to_owned()),
+            Err(_) => (key.to_owned(), None, key.to_owned()),
+        },
+        None => match key.parse::<u64>() {
+            Ok(number) => ("".to_owned(), Some(number), key.to_owned()),
+            Err(_) => (key.to_owned(), None, key.to_owned()),
+        },
+    };
+
+    split(a).cmp(&split(b))
+}
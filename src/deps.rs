@@ -0,0 +1,145 @@
+use crate::{chart::Chart, schedule};
+use chrono::{Duration, NaiveDate};
+use easy_error::{self, ResultExt};
+use std::{collections::HashMap, error::Error, fs::File, path::Path};
+
+/// An issue that can't start until another has finished, plus a lag in days
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    pub before: String,
+    pub after: String,
+    pub lag: i64,
+}
+
+/// Read a CSV file of `before,after[,lag]` rows. A header row is optional.
+pub fn load(path: &Path) -> Result<Vec<Dependency>, Box<dyn Error>> {
+    let file = File::open(path).context(format!(
+        "Unable to open dependencies file '{}'",
+        path.to_string_lossy()
+    ))?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(file);
+    let mut deps = vec![];
+
+    for (i, row) in reader.records().enumerate() {
+        let row = row?;
+        let field = |i: usize| row.get(i).unwrap_or_default();
+
+        if row.iter().all(|field| field.is_empty())
+            || (i == 0 && field(0).eq_ignore_ascii_case("before"))
+        {
+            continue;
+        }
+
+        let invalid = || {
+            format!(
+                "Row '{}' of dependencies file '{}' must be before,after[,lag]",
+                row.iter().collect::<Vec<&str>>().join(","),
+                path.to_string_lossy()
+            )
+        };
+
+        if row.len() > 3 || field(0).is_empty() || field(1).is_empty() {
+            return Err(From::from(invalid()));
+        }
+
+        deps.push(Dependency {
+            before: field(0).to_owned(),
+            after: field(1).to_owned(),
+            lag: match field(2) {
+                "" => 0,
+                lag => lag.parse().map_err(|_| invalid())?,
+            },
+        });
+    }
+
+    Ok(deps)
+}
+
+/// The dependencies of the items of a chart on each other, from issue links
+pub fn from_chart(chart: &Chart) -> Vec<Dependency> {
+    chart
+        .items
+        .iter()
+        .flat_map(|item| {
+            item.depends_on.iter().map(|before| Dependency {
+                before: before.to_owned(),
+                after: item.id().to_owned(),
+                lag: 0,
+            })
+        })
+        .collect()
+}
+
+/// Move the start of each item that depends on another to no earlier than
+/// the day after the other finishes, plus the lag. Items that follow a moved
+/// item in its lane move with it. Returns warnings for dependencies that
+/// can't be applied.
+pub fn apply(chart: &mut Chart, deps: &[Dependency]) -> Result<Vec<String>, Box<dyn Error>> {
+    let index: HashMap<String, usize> = chart
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| (item.id().to_owned(), i))
+        .collect();
+    let mut warnings = vec![];
+    let mut constraints = vec![];
+
+    for dep in deps.iter() {
+        match (index.get(&dep.before), index.get(&dep.after)) {
+            (Some(_), Some(after)) if chart.items[*after].has_fixed_dates() => {
+                warnings.push(format!(
+                    "Dependency of '{}' on '{}' ignored, '{}' has fixed start and end dates",
+                    dep.after, dep.before, dep.after
+                ))
+            }
+            (Some(before), Some(after)) => constraints.push((*before, *after, dep)),
+            _ => warnings.push(format!(
+                "Dependency of '{}' on '{}' ignored, the chart doesn't have both",
+                dep.after, dep.before
+            )),
+        }
+    }
+
+    // Each round settles at least one more link of the longest chain, so
+    // anything still moving after that many rounds is going round in a cycle
+    for round in 0..=constraints.len() {
+        let dates: Vec<(Option<NaiveDate>, Option<NaiveDate>)> = schedule::schedule(chart)
+            .iter()
+            .map(|item| (item.start, item.end))
+            .collect();
+        let mut moves: HashMap<usize, NaiveDate> = HashMap::new();
+
+        for (before, after, dep) in constraints.iter() {
+            match (dates[*before].1, dates[*after].0) {
+                (Some(end), Some(start)) => {
+                    let earliest = end + Duration::days(dep.lag);
+
+                    if start < earliest {
+                        let date = moves.entry(*after).or_insert(earliest);
+
+                        *date = (*date).max(earliest);
+                    }
+                }
+                _ if round == 0 => warnings.push(format!(
+                    "Dependency of '{}' on '{}' ignored, they don't both have dates",
+                    dep.after, dep.before
+                )),
+                _ => (),
+            }
+        }
+
+        if moves.is_empty() {
+            return Ok(warnings);
+        }
+
+        for (item, date) in moves {
+            chart.items[item].data.start_date = Some(date);
+        }
+    }
+
+    Err(From::from("The dependencies form a cycle"))
+}
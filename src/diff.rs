@@ -0,0 +1,175 @@
+use crate::{
+    chart::Chart,
+    schedule::{self, ScheduledItem},
+};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// One difference between two charts, for an item matched by its identifier
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(tag = "change", rename_all = "kebab-case")]
+pub enum Change {
+    Added {
+        title: String,
+        resource: Option<String>,
+        #[serde(rename = "startDate")]
+        start_date: Option<NaiveDate>,
+        duration: Option<i64>,
+    },
+    Removed {
+        title: String,
+    },
+    StartMoved {
+        title: String,
+        days: i64,
+        from: NaiveDate,
+        to: NaiveDate,
+    },
+    DurationChanged {
+        title: String,
+        from: Option<i64>,
+        to: Option<i64>,
+    },
+    ResourceChanged {
+        title: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+fn days(duration: Option<i64>) -> String {
+    match duration {
+        Some(1) => "1 day".to_owned(),
+        Some(days) => format!("{} days", days),
+        None => "a milestone".to_owned(),
+    }
+}
+
+fn name(resource: &Option<String>) -> &str {
+    resource.as_deref().unwrap_or("no resource")
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added {
+                title,
+                resource,
+                start_date,
+                duration,
+            } => {
+                write!(f, "+ {} added for {}", title, name(resource))?;
+
+                if let Some(date) = start_date {
+                    write!(f, " starting {}", date)?;
+                }
+
+                write!(f, ", {}", days(*duration))
+            }
+            Change::Removed { title } => write!(f, "- {} removed", title),
+            Change::StartMoved {
+                title,
+                days,
+                from,
+                to,
+            } => write!(
+                f,
+                "~ {} start moved {} by {} day(s), from {} to {}",
+                title,
+                if *days < 0 { "earlier" } else { "later" },
+                days.abs(),
+                from,
+                to
+            ),
+            Change::DurationChanged { title, from, to } => write!(
+                f,
+                "~ {} duration changed from {} to {}",
+                title,
+                days(*from),
+                days(*to)
+            ),
+            Change::ResourceChanged { title, from, to } => write!(
+                f,
+                "~ {} resource changed from {} to {}",
+                title,
+                name(from),
+                name(to)
+            ),
+        }
+    }
+}
+
+fn resource_name(chart: &Chart, item: &ScheduledItem) -> Option<String> {
+    item.resource
+        .and_then(|index| chart.resources.get(index))
+        .cloned()
+}
+
+/// Compare two charts item by item, matching items by their identifier.
+/// Changes are in the order of the new chart's items, followed by removed
+/// items in the order of the old chart.
+pub fn diff_charts(old: &Chart, new: &Chart) -> Vec<Change> {
+    let old_items = schedule::schedule(old);
+    let new_items = schedule::schedule(new);
+    let old_by_id: HashMap<&str, &ScheduledItem> = old_items
+        .iter()
+        .map(|item| (item.item.id(), item))
+        .collect();
+    let new_ids: HashSet<&str> = new_items.iter().map(|item| item.item.id()).collect();
+    let mut changes = vec![];
+
+    for new_item in new_items.iter() {
+        let title = new_item.item.data.title.to_owned();
+        let old_item = match old_by_id.get(new_item.item.id()) {
+            Some(old_item) => old_item,
+            None => {
+                changes.push(Change::Added {
+                    resource: resource_name(new, new_item),
+                    start_date: new_item.start,
+                    duration: new_item.item.data.duration,
+                    title,
+                });
+                continue;
+            }
+        };
+
+        if let (Some(from), Some(to)) = (old_item.start, new_item.start) {
+            if from != to {
+                changes.push(Change::StartMoved {
+                    title: title.to_owned(),
+                    days: (to - from).num_days(),
+                    from,
+                    to,
+                });
+            }
+        }
+
+        if old_item.item.data.duration != new_item.item.data.duration {
+            changes.push(Change::DurationChanged {
+                title: title.to_owned(),
+                from: old_item.item.data.duration,
+                to: new_item.item.data.duration,
+            });
+        }
+
+        let (from, to) = (resource_name(old, old_item), resource_name(new, new_item));
+
+        if from != to {
+            changes.push(Change::ResourceChanged { title, from, to });
+        }
+    }
+
+    for old_item in old_items.iter() {
+        if !new_ids.contains(old_item.item.id()) {
+            changes.push(Change::Removed {
+                title: old_item.item.data.title.to_owned(),
+            });
+        }
+    }
+
+    changes
+}
@@ -0,0 +1,338 @@
+use crate::{columns::Field, filter, record::JiraRecord};
+use serde_json::Value;
+use std::{cmp::Ordering, str::FromStr};
+
+/// A filter on the fields of issues, e.g.
+/// `status != "Closed" && (assignee == "alice" || labels ~ "team-*")`.
+///
+/// A comparison is a field, by the names used for `columns` in a config
+/// file, an operator and a quoted string or a number. `==` and `!=` compare
+/// ignoring case, `~` and `!~` match patterns where `*` matches anything,
+/// and `<`, `<=`, `>` and `>=` compare numbers as numbers and anything else,
+/// such as `yyyy-mm-dd` dates, as text. A field on its own is true when it
+/// has a value. Comparisons are combined with `&&`, `||`, `!` and
+/// parentheses, and `&&` binds tighter than `||`. A field with several
+/// values, such as `labels`, matches when any of them does.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expression(Node);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    Has(Field),
+    Compare(Field, Operator, Literal),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Matches,
+    NotMatches,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Literal {
+    Text(String),
+    Number(f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Literal(Literal),
+    Operator(Operator),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+/// Split an expression into tokens, each with the column it starts at
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let (token, length) = match (c, next) {
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Operator(Operator::Equal), 2),
+            ('!', Some('=')) => (Token::Operator(Operator::NotEqual), 2),
+            ('!', Some('~')) => (Token::Operator(Operator::NotMatches), 2),
+            ('<', Some('=')) => (Token::Operator(Operator::LessOrEqual), 2),
+            ('>', Some('=')) => (Token::Operator(Operator::GreaterOrEqual), 2),
+            ('~', _) => (Token::Operator(Operator::Matches), 1),
+            ('<', _) => (Token::Operator(Operator::Less), 1),
+            ('>', _) => (Token::Operator(Operator::Greater), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('"', _) => {
+                let mut text = String::new();
+                let mut end = i + 1;
+
+                loop {
+                    match chars.get(end) {
+                        None => return Err(format!("unclosed string at column {}", column)),
+                        Some('"') => break,
+                        Some('\\') if end + 1 < chars.len() => {
+                            text.push(chars[end + 1]);
+                            end += 2;
+                        }
+                        Some(c) => {
+                            text.push(*c);
+                            end += 1;
+                        }
+                    }
+                }
+
+                (Token::Literal(Literal::Text(text)), end + 1 - i)
+            }
+            (c, _) if c.is_ascii_digit() || c == '-' => {
+                let length = chars[i..]
+                    .iter()
+                    .skip(1)
+                    .position(|c| !(c.is_ascii_digit() || *c == '.'))
+                    .map_or(chars.len() - i, |position| position + 1);
+                let number: String = chars[i..i + length].iter().collect();
+
+                match number.parse() {
+                    Ok(number) => (Token::Literal(Literal::Number(number)), length),
+                    Err(_) => {
+                        return Err(format!("invalid number '{}' at column {}", number, column))
+                    }
+                }
+            }
+            (c, _) if c.is_alphabetic() => {
+                let length = chars[i..]
+                    .iter()
+                    .position(|c| !(c.is_alphanumeric() || *c == '_'))
+                    .unwrap_or(chars.len() - i);
+
+                (Token::Name(chars[i..i + length].iter().collect()), length)
+            }
+            (c, _) => return Err(format!("unexpected '{}' at column {}", c, column)),
+        };
+
+        tokens.push((column, token));
+        i += length;
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive descent parser over the tokens of an expression
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    /// The column after the end of the expression, for errors at the end
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(_, token)| token.clone());
+
+        self.position += 1;
+        token
+    }
+
+    /// Where the next token is, for errors
+    fn here(&self) -> String {
+        match self.tokens.get(self.position) {
+            Some((column, _)) => format!("at column {}", column),
+            None => format!("at column {}, the end", self.end),
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, String> {
+        let mut node = self.and()?;
+
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            node = Node::Or(Box::new(node), Box::new(self.and()?));
+        }
+
+        Ok(node)
+    }
+
+    fn and(&mut self) -> Result<Node, String> {
+        let mut node = self.not()?;
+
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            node = Node::And(Box::new(node), Box::new(self.not()?));
+        }
+
+        Ok(node)
+    }
+
+    fn not(&mut self) -> Result<Node, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+
+            return Ok(Node::Not(Box::new(self.not()?)));
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        let here = self.here();
+
+        match self.next() {
+            Some(Token::Open) => {
+                let node = self.or()?;
+                let here = self.here();
+
+                match self.next() {
+                    Some(Token::Close) => Ok(node),
+                    _ => Err(format!("expected ')' {}", here)),
+                }
+            }
+            Some(Token::Name(name)) => {
+                let field = Field::from_str(&name)?;
+                let operator = match self.peek() {
+                    Some(Token::Operator(operator)) => *operator,
+                    _ => return Ok(Node::Has(field)),
+                };
+
+                self.next();
+
+                let here = self.here();
+
+                match self.next() {
+                    Some(Token::Literal(literal)) => Ok(Node::Compare(field, operator, literal)),
+                    _ => Err(format!("expected a quoted string or a number {}", here)),
+                }
+            }
+            _ => Err(format!("expected a field or '(' {}", here)),
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+            end: s.chars().count() + 1,
+        };
+        let node = parser.or()?;
+
+        match parser.peek() {
+            None => Ok(Expression(node)),
+            Some(_) => Err(format!("unexpected {}", parser.here())),
+        }
+    }
+}
+
+impl Expression {
+    /// Whether an issue matches the expression
+    pub fn matches(&self, record: &JiraRecord) -> bool {
+        self.0.matches(record)
+    }
+}
+
+impl Node {
+    fn matches(&self, record: &JiraRecord) -> bool {
+        match self {
+            Node::And(a, b) => a.matches(record) && b.matches(record),
+            Node::Or(a, b) => a.matches(record) || b.matches(record),
+            Node::Not(node) => !node.matches(record),
+            Node::Has(field) => record.field(*field).is_some(),
+            Node::Compare(field, operator, literal) => {
+                let values = match record.field(*field) {
+                    Some(Value::Array(values)) => values,
+                    Some(value) => vec![value],
+                    None => vec![Value::from("")],
+                };
+                let any = |test: &dyn Fn(&Value) -> bool| values.iter().any(test);
+
+                match operator {
+                    Operator::Equal => {
+                        any(&|value| compare(value, literal) == Some(Ordering::Equal))
+                    }
+                    Operator::NotEqual => {
+                        !any(&|value| compare(value, literal) == Some(Ordering::Equal))
+                    }
+                    Operator::Matches => any(&|value| pattern_matches(value, literal)),
+                    Operator::NotMatches => !any(&|value| pattern_matches(value, literal)),
+                    Operator::Less => any(&|value| compare(value, literal) == Some(Ordering::Less)),
+                    Operator::LessOrEqual => any(&|value| {
+                        matches!(
+                            compare(value, literal),
+                            Some(Ordering::Less | Ordering::Equal)
+                        )
+                    }),
+                    Operator::Greater => {
+                        any(&|value| compare(value, literal) == Some(Ordering::Greater))
+                    }
+                    Operator::GreaterOrEqual => any(&|value| {
+                        matches!(
+                            compare(value, literal),
+                            Some(Ordering::Greater | Ordering::Equal)
+                        )
+                    }),
+                }
+            }
+        }
+    }
+}
+
+/// The text of a value, as it would be written in an export
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_owned(),
+        value => value.to_string(),
+    }
+}
+
+/// Compare a value of a field with a literal, as numbers when both are
+/// numbers and otherwise as text ignoring case. Empty values don't compare
+/// with numbers.
+fn compare(value: &Value, literal: &Literal) -> Option<Ordering> {
+    match literal {
+        Literal::Number(number) => value
+            .as_f64()
+            .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+            .and_then(|value| value.partial_cmp(number)),
+        Literal::Text(s) => Some(text(value).to_lowercase().cmp(&s.to_lowercase())),
+    }
+}
+
+fn pattern_matches(value: &Value, literal: &Literal) -> bool {
+    let pattern = match literal {
+        Literal::Text(s) => s.to_owned(),
+        Literal::Number(number) => number.to_string(),
+    };
+
+    filter::any_match(&[pattern], &text(value))
+}
@@ -0,0 +1,930 @@
+use crate::{
+    chart::{Period, Sprint},
+    columns::{DateFields, Field},
+    net::{self, NetworkOptions},
+    oauth::{self, OAuthOptions},
+    record::{IssueLink, JiraRecord},
+};
+use chrono::NaiveDate;
+use easy_error::{self, ResultExt};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+const ISSUE_FIELDS: &str =
+    "summary,status,assignee,timeoriginalestimate,timeestimate,timespent,created,resolutiondate,statuscategorychangedate,duedate,description,issuetype,parent,labels,components,fixVersions,issuelinks,project";
+const PAGE_SIZE: usize = 100;
+/// The names of the custom fields that Jira keeps some issue fields in, whose
+/// ids differ from site to site
+const CUSTOM_FIELDS: [(Field, &[&str]); 5] = [
+    (
+        Field::StoryPoints,
+        &["Story Points", "Story point estimate"],
+    ),
+    (Field::Sprint, &["Sprint"]),
+    (Field::Team, &["Team"]),
+    (Field::Risk, &["Risk"]),
+    (Field::SlaRemaining, &["Time to resolution"]),
+];
+
+/// How to authenticate with Jira
+pub enum Auth {
+    Anonymous,
+    /// Basic auth with a Jira Cloud account email and API token, or a Jira
+    /// Server/Data Center username and password
+    Basic {
+        user: String,
+        secret: String,
+    },
+    /// A Jira Server/Data Center personal access token
+    PersonalAccessToken(String),
+    /// Jira Cloud OAuth 2.0 three-legged auth
+    OAuth2(OAuthOptions),
+}
+
+/// Where and how to connect to Jira
+pub struct SiteOptions {
+    pub url: String,
+    pub auth: Auth,
+    pub network: NetworkOptions,
+    pub checkpoint: Option<Checkpoint>,
+    pub capture: Option<Capture>,
+    /// Custom fields with the start and end dates of issues
+    pub date_fields: Option<DateFields>,
+    /// The config file's columns for fields, whose names or `customfield_`
+    /// ids are tried before the usual names of the custom fields
+    pub columns: Vec<(Field, Vec<String>)>,
+    /// The most issues to fetch from each board, filter or search, after
+    /// which the rest are counted but left out
+    pub max_issues: Option<usize>,
+}
+
+/// Save API responses to a directory, or answer requests from responses
+/// saved earlier instead of calling Jira
+pub enum Capture {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl Capture {
+    /// Capture the responses of a named site in a directory of its own, so
+    /// they aren't mixed up with another site's
+    pub fn for_site(self, name: &str) -> Capture {
+        match self {
+            Capture::Record(dir) => Capture::Record(dir.join(name)),
+            Capture::Replay(dir) => Capture::Replay(dir.join(name)),
+        }
+    }
+
+    /// The file a response is saved in, named after the request path and
+    /// query so that the same request always uses the same file. The fields
+    /// and expansions are left out so recordings still replay when they
+    /// change.
+    fn response_path(dir: &Path, path: &str, query: &[(&str, String)]) -> PathBuf {
+        let mut name = path.to_owned();
+
+        for (name_part, value) in query
+            .iter()
+            .filter(|(name, _)| *name != "fields" && *name != "expand")
+        {
+            name.push_str(&format!("_{}_{}", name_part, value));
+        }
+
+        let name: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+
+        dir.join(format!("{}.json", name.trim_matches('_')))
+    }
+}
+
+/// A sprint and its planned dates
+#[derive(Debug, Clone)]
+pub struct SprintInfo {
+    pub name: String,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+}
+
+/// How far a fetch from one paged endpoint got
+struct Progress {
+    start_at: usize,
+    done: bool,
+    issues: Vec<Value>,
+}
+
+/// A record of the pages fetched so far, so that an interrupted fetch can be
+/// resumed. Each fetched page is appended to the file as a line of JSON.
+pub struct Checkpoint {
+    path: PathBuf,
+    file: File,
+    progress: HashMap<String, Progress>,
+}
+
+impl Checkpoint {
+    /// Open a checkpoint file, reading the pages already fetched when
+    /// resuming or starting afresh otherwise
+    pub fn open(path: &Path, resume: bool) -> Result<Checkpoint, Box<dyn Error>> {
+        let mut progress: HashMap<String, Progress> = HashMap::new();
+
+        if resume && path.exists() {
+            let content = fs::read_to_string(path).context(format!(
+                "Unable to read checkpoint file '{}'",
+                path.to_string_lossy()
+            ))?;
+
+            // A partially written last line means the fetch was interrupted
+            // while writing it, so ignore it and fetch that page again
+            for page in content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            {
+                let endpoint = page["path"].as_str().unwrap_or_default().to_owned();
+                let issues = page["issues"].as_array().cloned().unwrap_or_default();
+                let entry = progress.entry(endpoint).or_insert(Progress {
+                    start_at: 0,
+                    done: false,
+                    issues: vec![],
+                });
+
+                entry.start_at = page["startAt"].as_u64().unwrap_or(0) as usize + issues.len();
+                entry.done = page["done"].as_bool().unwrap_or(false);
+                entry.issues.extend(issues);
+            }
+        }
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(resume)
+            .write(true)
+            .truncate(!resume)
+            .open(path)
+            .context(format!(
+                "Unable to write checkpoint file '{}'",
+                path.to_string_lossy()
+            ))?;
+
+        Ok(Checkpoint {
+            path: path.to_owned(),
+            file,
+            progress,
+        })
+    }
+
+    fn take(&mut self, endpoint: &str) -> Option<Progress> {
+        self.progress.remove(endpoint)
+    }
+
+    fn record(
+        &mut self,
+        endpoint: &str,
+        start_at: usize,
+        issues: &[Value],
+        done: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let line = json!({
+            "path": endpoint,
+            "startAt": start_at,
+            "done": done,
+            "issues": issues,
+        });
+
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()?;
+
+        Ok(())
+    }
+
+    fn remove(&self) -> Result<(), Box<dyn Error>> {
+        fs::remove_file(&self.path).context(format!(
+            "Unable to remove checkpoint file '{}'",
+            self.path.to_string_lossy()
+        ))?;
+
+        Ok(())
+    }
+}
+
+/// The issues one board, filter or search has come across, for
+/// `max_issues`
+#[derive(Default)]
+struct Tally {
+    /// The issues in the pages fetched, including those over the limit
+    seen: usize,
+    /// The issues in the pages that weren't fetched once the limit was
+    /// reached
+    unfetched: usize,
+}
+
+pub struct JiraClient {
+    base_url: String,
+    agent: ureq::Agent,
+    authorization: Option<String>,
+    checkpoint: Option<Mutex<Checkpoint>>,
+    capture: Option<Capture>,
+    date_fields: Option<DateFields>,
+    columns: Vec<(Field, Vec<String>)>,
+    /// The ids of the custom fields, once they have been looked up
+    custom_fields: Mutex<Option<Vec<(Field, String)>>>,
+    max_issues: Option<usize>,
+    /// Warnings for the fetches that `max_issues` cut short, and for OAuth
+    /// tokens the keyring couldn't be used for
+    warnings: Mutex<Vec<String>>,
+}
+
+impl JiraClient {
+    /// Create a client for a site. `prompt` is called with a URL when the
+    /// user needs to authorize the tool in their browser.
+    pub fn new(site: SiteOptions, prompt: &dyn Fn(&str)) -> Result<JiraClient, Box<dyn Error>> {
+        let mut base_url = site.url.trim_end_matches('/').to_owned();
+        let mut warnings = vec![];
+        let authorization = match site.auth {
+            // Replayed responses need no credentials
+            _ if matches!(site.capture, Some(Capture::Replay(_))) => None,
+            Auth::Anonymous => None,
+            Auth::Basic {
+                ref user,
+                ref secret,
+            } => Some(format!(
+                "Basic {}",
+                base64::encode(format!("{}:{}", user, secret))
+            )),
+            Auth::PersonalAccessToken(ref token) => Some(format!("Bearer {}", token)),
+            Auth::OAuth2(ref options) => {
+                let session = oauth::authorize(&site.network, options, &base_url, prompt)?;
+
+                // OAuth requests go through the Atlassian API gateway
+                base_url = session.base_url;
+                warnings = session.warnings;
+                Some(format!("Bearer {}", session.access_token))
+            }
+        };
+
+        Ok(JiraClient {
+            agent: match site.capture {
+                Some(Capture::Replay(_)) => ureq::Agent::new(),
+                _ => net::agent(&site.network, &base_url)?,
+            },
+            base_url,
+            authorization,
+            checkpoint: site.checkpoint.map(Mutex::new),
+            capture: site.capture,
+            date_fields: site.date_fields,
+            columns: site.columns,
+            custom_fields: Mutex::new(None),
+            max_issues: site.max_issues,
+            warnings: Mutex::new(warnings),
+        })
+    }
+
+    fn get_json(&self, path: &str, query: &[(&str, String)]) -> Result<Value, Box<dyn Error>> {
+        match self.capture {
+            Some(Capture::Replay(ref dir)) => {
+                let file = Capture::response_path(dir, path, query);
+                let content = fs::read_to_string(&file).context(format!(
+                    "No recorded response for '{}' in '{}'",
+                    path,
+                    file.to_string_lossy()
+                ))?;
+
+                Ok(serde_json::from_str(&content)?)
+            }
+            Some(Capture::Record(ref dir)) => {
+                let value = self.request_json(path, query)?;
+                let file = Capture::response_path(dir, path, query);
+
+                fs::create_dir_all(dir).context(format!(
+                    "Unable to create record directory '{}'",
+                    dir.to_string_lossy()
+                ))?;
+                fs::write(&file, serde_json::to_string_pretty(&value)?).context(format!(
+                    "Unable to write recorded response '{}'",
+                    file.to_string_lossy()
+                ))?;
+
+                Ok(value)
+            }
+            None => self.request_json(path, query),
+        }
+    }
+
+    fn request_json(&self, path: &str, query: &[(&str, String)]) -> Result<Value, Box<dyn Error>> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut request = self.agent.get(&url).set("Accept", "application/json");
+
+        if let Some(ref authorization) = self.authorization {
+            request = request.set("Authorization", authorization);
+        }
+
+        for (name, value) in query.iter() {
+            request = request.query(name, value);
+        }
+
+        match request.call() {
+            Ok(response) => Ok(response.into_json()?),
+            Err(ureq::Error::Status(code, response)) => Err(From::from(format!(
+                "Jira request '{}' failed with status {}: {}",
+                url,
+                code,
+                response.into_string().unwrap_or_default()
+            ))),
+            Err(err) => Err(From::from(format!(
+                "Jira request '{}' failed: {}",
+                url, err
+            ))),
+        }
+    }
+
+    /// Get all the issues from a paged issue endpoint, passing each one to
+    /// `sink` as soon as its page arrives. `query` is added to the request
+    /// for each page, e.g. a JQL search.
+    fn get_issues(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        sprint: Option<&SprintInfo>,
+        tally: &mut Tally,
+        sink: &mut dyn FnMut(JiraRecord),
+    ) -> Result<(), Box<dyn Error>> {
+        let custom_fields = self.custom_fields()?;
+        let max_issues = self.max_issues;
+        let is_full = |tally: &Tally| max_issues.is_some_and(|max| tally.seen >= max);
+        let mut emit = |tally: &mut Tally, issue: &Value| {
+            tally.seen += 1;
+
+            if max_issues.is_some_and(|max| tally.seen > max) {
+                return;
+            }
+
+            let mut record = issue_to_record(issue, self.date_fields.as_ref(), &custom_fields);
+
+            if let Some(sprint) = sprint {
+                record.sprint = sprint.name.to_owned();
+                record.sprint_start = sprint.start_date;
+                record.sprint_end = sprint.end_date;
+            }
+
+            sink(record);
+        };
+        let mut start_at = 0;
+        let fields = std::iter::once(ISSUE_FIELDS)
+            .chain(
+                self.date_fields
+                    .iter()
+                    .flat_map(|date_fields| [date_fields.start.as_str(), date_fields.end.as_str()]),
+            )
+            .chain(custom_fields.iter().map(|(_, id)| id.as_str()))
+            .collect::<Vec<&str>>()
+            .join(",");
+        // Searches of the same endpoint are told apart in the checkpoint by
+        // their query
+        let endpoint = std::iter::once(path.to_owned())
+            .chain(
+                query
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, value)),
+            )
+            .collect::<Vec<String>>()
+            .join("?");
+
+        // Replay whatever an interrupted fetch already got
+        if let Some(ref checkpoint) = self.checkpoint {
+            let mut checkpoint = checkpoint.lock().unwrap();
+
+            if let Some(progress) = checkpoint.take(&endpoint) {
+                progress.issues.iter().for_each(|issue| emit(tally, issue));
+
+                if progress.done {
+                    return Ok(());
+                }
+
+                start_at = progress.start_at;
+            }
+        }
+
+        loop {
+            let page_query: Vec<(&str, String)> = query
+                .iter()
+                .cloned()
+                .chain([
+                    ("startAt", start_at.to_string()),
+                    ("maxResults", PAGE_SIZE.to_string()),
+                    ("fields", fields.to_owned()),
+                    ("expand", "changelog".to_owned()),
+                ])
+                .collect();
+            let page = self.get_json(path, &page_query)?;
+            let issues = page["issues"].as_array().cloned().unwrap_or_default();
+            let total = page["total"].as_u64().unwrap_or(0) as usize;
+            let done = issues.is_empty() || start_at + issues.len() >= total;
+
+            // A page fetched after the limit is reached only counts the
+            // issues left out
+            if is_full(tally) {
+                tally.unfetched += total.saturating_sub(start_at);
+                break;
+            }
+
+            if let Some(ref checkpoint) = self.checkpoint {
+                checkpoint
+                    .lock()
+                    .unwrap()
+                    .record(&endpoint, start_at, &issues, done)?;
+            }
+
+            start_at += issues.len();
+            issues.iter().for_each(|issue| emit(tally, issue));
+
+            if done {
+                break;
+            }
+
+            if is_full(tally) {
+                tally.unfetched += total.saturating_sub(start_at);
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The ids of the custom fields that story points, sprints, teams, risks
+    /// and SLAs are in, looked up by name in the site's fields the first
+    /// time they are needed. Fields the site doesn't have are left out.
+    fn custom_fields(&self) -> Result<Vec<(Field, String)>, Box<dyn Error>> {
+        let mut custom_fields = self.custom_fields.lock().unwrap();
+
+        if let Some(ref ids) = *custom_fields {
+            return Ok(ids.clone());
+        }
+
+        let site_fields = match self.get_json("/rest/api/2/field", &[]) {
+            Ok(site_fields) => site_fields,
+            // Responses recorded before custom fields were looked up still
+            // replay, without them
+            Err(_) if matches!(self.capture, Some(Capture::Replay(_))) => Value::Null,
+            Err(err) => return Err(err),
+        };
+        let ids = find_custom_fields(&site_fields, &self.columns);
+
+        *custom_fields = Some(ids.clone());
+
+        Ok(ids)
+    }
+
+    /// Keep a warning saying how many issues `max_issues` left out of a
+    /// fetch, e.g. `board 12`, if it left out any
+    fn report(&self, tally: Tally, fetch: &str) {
+        let Some(max) = self.max_issues else {
+            return;
+        };
+        let left_out = tally.seen.saturating_sub(max) + tally.unfetched;
+
+        if left_out > 0 {
+            self.warnings.lock().unwrap().push(format!(
+                "Only the first {} issues of {} were fetched because of --max-issues, leaving out {} more",
+                max, fetch, left_out
+            ));
+        }
+    }
+
+    /// The warnings for the fetches that `max_issues` cut short and the
+    /// OAuth tokens that couldn't be read or kept, which are only given once
+    pub fn take_warnings(&self) -> Vec<String> {
+        std::mem::take(&mut self.warnings.lock().unwrap())
+    }
+
+    fn get_active_sprints(&self, board_id: u64) -> Result<Vec<(u64, SprintInfo)>, Box<dyn Error>> {
+        let path = format!("/rest/agile/1.0/board/{}/sprint", board_id);
+        let mut sprints = vec![];
+        let mut start_at = 0;
+
+        loop {
+            let page = self.get_json(
+                &path,
+                &[
+                    ("state", "active".to_owned()),
+                    ("startAt", start_at.to_string()),
+                    ("maxResults", PAGE_SIZE.to_string()),
+                ],
+            )?;
+            let values = page["values"].as_array().cloned().unwrap_or_default();
+
+            start_at += values.len();
+
+            for value in values.iter() {
+                if let Some(id) = value["id"].as_u64() {
+                    sprints.push((
+                        id,
+                        SprintInfo {
+                            name: value["name"].as_str().unwrap_or_default().to_owned(),
+                            start_date: parse_api_date(&value["startDate"]),
+                            end_date: parse_api_date(&value["endDate"]),
+                        },
+                    ));
+                }
+            }
+
+            if values.is_empty() || page["isLast"].as_bool().unwrap_or(true) {
+                break;
+            }
+        }
+
+        sprints.sort_by_key(|(_, sprint)| sprint.start_date);
+
+        Ok(sprints)
+    }
+
+    /// Get the active sprints of a board and their issues, followed by the
+    /// board backlog in rank order. Issues are passed to `sink` as they
+    /// arrive and the sprints are returned.
+    pub fn fetch_board(
+        &self,
+        board_id: u64,
+        sink: &mut dyn FnMut(JiraRecord),
+    ) -> Result<Vec<SprintInfo>, Box<dyn Error>> {
+        let mut sprints = vec![];
+        let mut tally = Tally::default();
+
+        for (sprint_id, sprint) in self.get_active_sprints(board_id)? {
+            self.get_issues(
+                &format!(
+                    "/rest/agile/1.0/board/{}/sprint/{}/issue",
+                    board_id, sprint_id
+                ),
+                &[],
+                Some(&sprint),
+                &mut tally,
+                sink,
+            )?;
+            sprints.push(sprint);
+        }
+
+        self.get_issues(
+            &format!("/rest/agile/1.0/board/{}/backlog", board_id),
+            &[],
+            None,
+            &mut tally,
+            sink,
+        )?;
+        self.report(tally, &format!("board {}", board_id));
+
+        Ok(sprints)
+    }
+
+    /// Get the issues of a saved filter, in the filter's order, passing each
+    /// one to `sink` as it arrives. Returns the filter's name.
+    pub fn fetch_filter(
+        &self,
+        filter_id: u64,
+        sink: &mut dyn FnMut(JiraRecord),
+    ) -> Result<String, Box<dyn Error>> {
+        let filter = self.get_json(&format!("/rest/api/2/filter/{}", filter_id), &[])?;
+        let jql = filter["jql"]
+            .as_str()
+            .ok_or_else(|| format!("Jira filter {} has no JQL", filter_id))?;
+
+        self.search(jql, &format!("filter {}", filter_id), sink)?;
+
+        Ok(filter["name"].as_str().unwrap_or_default().to_owned())
+    }
+
+    /// Get the issues a JQL query finds, in the order it sorts them, passing
+    /// each one to `sink` as it arrives
+    pub fn fetch_jql(
+        &self,
+        jql: &str,
+        sink: &mut dyn FnMut(JiraRecord),
+    ) -> Result<(), Box<dyn Error>> {
+        self.search(jql, "the search", sink)
+    }
+
+    fn search(
+        &self,
+        jql: &str,
+        fetch: &str,
+        sink: &mut dyn FnMut(JiraRecord),
+    ) -> Result<(), Box<dyn Error>> {
+        let mut tally = Tally::default();
+
+        self.get_issues(
+            "/rest/api/2/search",
+            &[("jql", jql.to_owned())],
+            None,
+            &mut tally,
+            sink,
+        )?;
+        self.report(tally, fetch);
+
+        Ok(())
+    }
+
+    /// The URL of the site, which responses such as user names are cached
+    /// under
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The display name of a user, from their Jira Cloud account id or
+    /// their Jira Server username
+    pub fn display_name(&self, user: &str) -> Result<String, Box<dyn Error>> {
+        // Cloud account ids are hex, with a numeric prefix for older ones,
+        // e.g. 557058:f58131cb-b67d-43c7-b30d-6b58d40bd077
+        let by = if user.contains(':')
+            || (user.len() >= 24 && user.bytes().all(|b| b.is_ascii_hexdigit()))
+        {
+            "accountId"
+        } else {
+            "username"
+        };
+        let value = self.get_json("/rest/api/2/user", &[(by, user.to_owned())])?;
+
+        value["displayName"]
+            .as_str()
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_owned())
+            .ok_or_else(|| From::from(format!("Jira has no display name for user '{}'", user)))
+    }
+
+    /// Remove the checkpoint once everything has been fetched
+    pub fn finish(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(ref checkpoint) = self.checkpoint {
+            checkpoint.lock().unwrap().remove()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Jira API dates are ISO 8601 date times, but only the date is needed
+fn parse_api_date(value: &Value) -> Option<NaiveDate> {
+    value
+        .as_str()
+        .and_then(|s| s.get(0..10))
+        .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+}
+
+/// The id of each custom field that the site has, from its list of fields.
+/// A config file column is either a field's id, e.g. `customfield_10016`, or
+/// its name, with or without the `Custom field (...)` of CSV exports.
+fn find_custom_fields(
+    site_fields: &Value,
+    columns: &[(Field, Vec<String>)],
+) -> Vec<(Field, String)> {
+    let site_fields = site_fields
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    CUSTOM_FIELDS
+        .iter()
+        .filter_map(|(field, names)| {
+            columns
+                .iter()
+                .filter(|(f, _)| f == field)
+                .flat_map(|(_, names)| names.iter().map(String::as_str))
+                .chain(names.iter().copied())
+                .find_map(|name| {
+                    if name.starts_with("customfield_") {
+                        return Some(name.to_owned());
+                    }
+
+                    let name = name
+                        .strip_prefix("Custom field (")
+                        .and_then(|name| name.strip_suffix(')'))
+                        .unwrap_or(name);
+
+                    site_fields
+                        .iter()
+                        .find(|site_field| {
+                            site_field["name"]
+                                .as_str()
+                                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+                        })
+                        .and_then(|site_field| site_field["id"].as_str())
+                        .map(|id| id.to_owned())
+                })
+                .map(|id| (*field, id))
+        })
+        .collect()
+}
+
+fn issue_to_record(
+    issue: &Value,
+    date_fields: Option<&DateFields>,
+    custom_fields: &[(Field, String)],
+) -> JiraRecord {
+    let fields = &issue["fields"];
+    let text = |value: &Value| value.as_str().unwrap_or_default().to_owned();
+    let date = |name: Option<&String>| name.and_then(|name| parse_api_date(&fields[name.as_str()]));
+    // Estimates too big for the seconds of an export are cut down to the
+    // biggest, rather than wrapping around to a small one
+    let seconds = |value: &Value| {
+        value
+            .as_u64()
+            .map(|seconds| seconds.min(u32::MAX as u64) as u32)
+    };
+    let custom = |field: Field| {
+        custom_fields
+            .iter()
+            .find(|(f, _)| *f == field)
+            .map_or(&Value::Null, |(_, id)| &fields[id.as_str()])
+    };
+    // Teams are objects with a title and single choice fields, such as
+    // Risk, options with a value
+    let choice = |value: &Value| match value.as_str() {
+        Some(s) => s.to_owned(),
+        None => ["value", "title", "name"]
+            .into_iter()
+            .map(|name| text(&value[name]))
+            .find(|s| !s.is_empty())
+            .unwrap_or_default(),
+    };
+    // An issue carried over into later sprints is in the last one listed.
+    // Jira Server gives each sprint as a serialized object.
+    let mut sprints: Vec<Sprint> = custom(Field::Sprint)
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|sprint| match sprint.as_str() {
+            Some(s) => {
+                let (name, start_date, end_date) = JiraRecord::parse_sprint(s);
+
+                Sprint {
+                    name,
+                    start_date,
+                    end_date,
+                }
+            }
+            None => Sprint {
+                name: text(&sprint["name"]),
+                start_date: parse_api_date(&sprint["startDate"]),
+                end_date: parse_api_date(&sprint["endDate"]),
+            },
+        })
+        .collect();
+    let sprint = sprints.pop();
+
+    JiraRecord {
+        key: text(&issue["key"]),
+        summary: text(&fields["summary"]),
+        status: text(&fields["status"]["name"]),
+        // Users who hide their profile have no display name, only an
+        // account id
+        assignee: [
+            &fields["assignee"]["displayName"],
+            &fields["assignee"]["accountId"],
+        ]
+        .into_iter()
+        .map(text)
+        .find(|name| !name.is_empty())
+        .unwrap_or_default(),
+        assignee_inactive: fields["assignee"]["active"] == json!(false),
+        original_estimate: seconds(&fields["timeoriginalestimate"]),
+        remaining_estimate: seconds(&fields["timeestimate"]),
+        time_spent: seconds(&fields["timespent"]),
+        created: parse_api_date(&fields["created"]),
+        resolved: parse_api_date(&fields["resolutiondate"]),
+        status_changed: parse_api_date(&fields["statuscategorychangedate"]),
+        due_date: parse_api_date(&fields["duedate"]),
+        description: text(&fields["description"]),
+        issue_type: text(&fields["issuetype"]["name"]),
+        epic: text(&fields["parent"]["fields"]["summary"]),
+        project: text(&fields["project"]["name"]),
+        labels: fields["labels"]
+            .as_array()
+            .map(|labels| labels.iter().map(text).collect())
+            .unwrap_or_default(),
+        components: fields["components"]
+            .as_array()
+            .map(|components| {
+                components
+                    .iter()
+                    .map(|component| text(&component["name"]))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        fix_versions: fields["fixVersions"]
+            .as_array()
+            .map(|versions| {
+                versions
+                    .iter()
+                    .map(|version| text(&version["name"]))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        target_start: date(date_fields.map(|date_fields| &date_fields.start)),
+        target_end: date(date_fields.map(|date_fields| &date_fields.end)),
+        blocked: blocked_periods(&issue["changelog"]),
+        links: issue_links(&fields["issuelinks"]),
+        story_points: custom(Field::StoryPoints)
+            .as_f64()
+            .filter(|points| *points >= 0.0),
+        team: choice(custom(Field::Team)),
+        risk: choice(custom(Field::Risk)).to_lowercase(),
+        sprint: sprint
+            .as_ref()
+            .map(|sprint| sprint.name.to_owned())
+            .unwrap_or_default(),
+        sprint_start: sprint.as_ref().and_then(|sprint| sprint.start_date),
+        sprint_end: sprint.as_ref().and_then(|sprint| sprint.end_date),
+        earlier_sprints: sprints,
+        sla_remaining: sla_remaining(custom(Field::SlaRemaining)),
+        ..Default::default()
+    }
+}
+
+/// The time left on a Jira Service Management SLA in seconds, from the cycle
+/// that is running or else the last one to finish. It is negative once the
+/// SLA has been breached.
+fn sla_remaining(sla: &Value) -> Option<i64> {
+    let cycle = if sla["ongoingCycle"].is_object() {
+        &sla["ongoingCycle"]
+    } else {
+        sla["completedCycles"].as_array()?.last()?
+    };
+
+    cycle["remainingTime"]["millis"]
+        .as_i64()
+        .map(|millis| millis / 1000)
+}
+
+/// The links of an issue, each of which has the issue at its other end as
+/// either the outward or the inward issue
+fn issue_links(links: &Value) -> Vec<IssueLink> {
+    links
+        .as_array()
+        .map(|links| {
+            links
+                .iter()
+                .filter_map(|link| {
+                    let (outward, other) = if link["outwardIssue"].is_object() {
+                        (true, &link["outwardIssue"])
+                    } else {
+                        (false, &link["inwardIssue"])
+                    };
+
+                    Some(IssueLink {
+                        kind: link["type"]["name"].as_str()?.to_owned(),
+                        outward,
+                        key: other["key"].as_str()?.to_owned(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The periods an issue was flagged, or in a Blocked status, from its
+/// changelog. A period that hasn't ended has no end date.
+fn blocked_periods(changelog: &Value) -> Vec<Period> {
+    let mut histories: Vec<&Value> = changelog["histories"]
+        .as_array()
+        .map(|histories| histories.iter().collect())
+        .unwrap_or_default();
+    let (mut flagged, mut status_blocked) = (false, false);
+    let mut periods: Vec<Period> = vec![];
+
+    // Searches give the newest change first, but the order isn't promised
+    histories.sort_by_key(|history| history["created"].as_str().unwrap_or_default().to_owned());
+
+    for history in histories {
+        let date = match parse_api_date(&history["created"]) {
+            Some(date) => date,
+            None => continue,
+        };
+        let was_blocked = flagged || status_blocked;
+
+        for item in history["items"].as_array().into_iter().flatten() {
+            let to = item["toString"].as_str().unwrap_or_default();
+
+            match item["field"].as_str().unwrap_or_default() {
+                "Flagged" => flagged = !to.is_empty(),
+                "status" => status_blocked = to.eq_ignore_ascii_case("blocked"),
+                _ => (),
+            }
+        }
+
+        match (was_blocked, flagged || status_blocked) {
+            (false, true) => periods.push(Period {
+                start_date: date,
+                end_date: None,
+            }),
+            (true, false) => {
+                if let Some(period) = periods.last_mut() {
+                    period.end_date = Some(date);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    periods
+}
@@ -0,0 +1,75 @@
+use crate::{expression::Expression, ignore, record::JiraRecord};
+
+/// Which issues go on the chart, by their fields, so exports don't have to
+/// be filtered by hand first. Values are names or patterns where `*` matches
+/// any run of characters, matched ignoring case.
+#[derive(Default)]
+pub struct Filter {
+    /// Only issues in one of these statuses are kept, when there are any
+    pub include_statuses: Vec<String>,
+    /// Issues in these statuses are left out
+    pub exclude_statuses: Vec<String>,
+    /// Only issues assigned to one of these people are kept, as well as
+    /// unassigned issues with `unassigned`, when there are any
+    pub include_assignees: Vec<String>,
+    /// Issues assigned to these people are left out
+    pub exclude_assignees: Vec<String>,
+    /// Keep unassigned issues, and only those unless people are included
+    pub unassigned: bool,
+    /// Only issues of one of these types are kept, when there are any
+    pub include_types: Vec<String>,
+    /// Issues of these types are left out, e.g. `Sub-task`
+    pub exclude_types: Vec<String>,
+    /// Only issues with one of these labels are kept, when there are any
+    pub include_labels: Vec<String>,
+    /// Issues with any of these labels are left out
+    pub exclude_labels: Vec<String>,
+    /// Only issues in one of these components are kept, when there are any
+    pub include_components: Vec<String>,
+    /// Issues in any of these components are left out
+    pub exclude_components: Vec<String>,
+    /// Only issues that match this expression are kept
+    pub expression: Option<Expression>,
+}
+
+impl Filter {
+    /// Whether an issue goes on the chart
+    pub fn keeps(&self, record: &JiraRecord) -> bool {
+        let assigned = !record.assignee.is_empty();
+        let any = |patterns: &[String], values: &[String]| {
+            values.iter().any(|value| any_match(patterns, value))
+        };
+        let assignee_kept = if self.include_assignees.is_empty() && !self.unassigned {
+            true
+        } else if assigned {
+            any_match(&self.include_assignees, &record.assignee)
+        } else {
+            self.unassigned
+        };
+
+        (self.include_statuses.is_empty() || any_match(&self.include_statuses, &record.status))
+            && !any_match(&self.exclude_statuses, &record.status)
+            && assignee_kept
+            && !(assigned && any_match(&self.exclude_assignees, &record.assignee))
+            && (self.include_types.is_empty() || any_match(&self.include_types, &record.issue_type))
+            && !any_match(&self.exclude_types, &record.issue_type)
+            && (self.include_labels.is_empty() || any(&self.include_labels, &record.labels))
+            && !any(&self.exclude_labels, &record.labels)
+            && (self.include_components.is_empty()
+                || any(&self.include_components, &record.components))
+            && !any(&self.exclude_components, &record.components)
+            && self
+                .expression
+                .as_ref()
+                .is_none_or(|expression| expression.matches(record))
+    }
+}
+
+/// Whether a value matches any of some names or patterns, ignoring case
+pub fn any_match(patterns: &[String], value: &str) -> bool {
+    let value = value.to_lowercase();
+
+    patterns
+        .iter()
+        .any(|pattern| ignore::matches(pattern.to_lowercase().as_bytes(), value.as_bytes()))
+}
@@ -0,0 +1,115 @@
+use crate::{convert::JIRA_DAY_IN_SECONDS, record::JIRA_DATE_TIME_FORMAT};
+use chrono::{Duration, NaiveDate};
+use clap::ValueEnum;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    Rng, SeedableRng,
+};
+use std::{error::Error, io::Write};
+
+const STATUSES: [&str; 3] = ["Open", "In Progress", "Closed"];
+const ISSUE_TYPES: [&str; 3] = ["Story", "Task", "Bug"];
+const EPICS: [&str; 4] = ["Onboarding", "Billing", "Search", "Reporting"];
+/// Dates that look like Jira's but can't be read
+const BAD_DATES: [&str; 3] = ["31/Feb/23 9:00 AM", "2023-13-01", "next week"];
+
+/// How issues are shared out between assignees
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Spread {
+    /// Each assignee gets about the same number of issues
+    Even,
+    /// The first assignee gets the most issues, the second half as many and so
+    /// on, like most teams
+    Skewed,
+}
+
+/// What to put in a generated export
+pub struct FixtureOptions {
+    pub rows: usize,
+    pub assignees: usize,
+    pub spread: Spread,
+    /// The percentage of issues with no assignee
+    pub unassigned: u8,
+    /// The issues are created between these dates
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// The percentage of issues with a created date that can't be read
+    pub bad_dates: u8,
+    /// The percentage of issues with no original estimate
+    pub missing_estimates: u8,
+    /// The same seed gives the same export
+    pub seed: u64,
+}
+
+/// Write a made up Jira CSV export, for trying out and timing the tool
+/// without real data
+pub fn generate(writer: &mut dyn Write, options: &FixtureOptions) -> Result<(), Box<dyn Error>> {
+    if options.to < options.from {
+        return Err(From::from(format!(
+            "The fixture can't end on {} before it starts on {}",
+            options.to, options.from
+        )));
+    }
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let weights: Vec<f64> = (0..options.assignees)
+        .map(|index| match options.spread {
+            Spread::Even => 1.0,
+            Spread::Skewed => 1.0 / (index + 1) as f64,
+        })
+        .collect();
+    let assignee = WeightedIndex::new(&weights)?;
+    let days = (options.to - options.from).num_days();
+    let chance = |percent: u8| f64::from(percent) / 100.0;
+    let mut csv = csv::Writer::from_writer(writer);
+
+    csv.write_record([
+        "Issue key",
+        "Summary",
+        "Status",
+        "Assignee",
+        "Original Estimate",
+        "Created",
+        "Issue Type",
+        "Parent summary",
+    ])?;
+
+    for row in 1..=options.rows {
+        let assignee = if rng.gen_bool(chance(options.unassigned)) {
+            String::new()
+        } else {
+            format!("person{}", assignee.sample(&mut rng) + 1)
+        };
+        let estimate = if rng.gen_bool(chance(options.missing_estimates)) {
+            String::new()
+        } else {
+            (rng.gen_range(1..=10) * JIRA_DAY_IN_SECONDS as u32).to_string()
+        };
+        let created = if rng.gen_bool(chance(options.bad_dates)) {
+            BAD_DATES[rng.gen_range(0..BAD_DATES.len())].to_owned()
+        } else {
+            (options.from + Duration::days(rng.gen_range(0..=days)))
+                .and_hms_opt(rng.gen_range(8..18), rng.gen_range(0..60), 0)
+                .unwrap()
+                .format(JIRA_DATE_TIME_FORMAT)
+                .to_string()
+        };
+        let issue_type = ISSUE_TYPES[rng.gen_range(0..ISSUE_TYPES.len())];
+
+        csv.write_record([
+            format!("FIX-{}", row),
+            format!("{} {}", issue_type, row),
+            STATUSES[rng.gen_range(0..STATUSES.len())].to_owned(),
+            assignee,
+            estimate,
+            created,
+            issue_type.to_owned(),
+            EPICS[rng.gen_range(0..EPICS.len())].to_owned(),
+        ])?;
+    }
+
+    csv.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use crate::{chart::Chart, deps::Dependency, schedule};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+};
+
+/// Keep only the item of an issue and the items within `radius` hops of it,
+/// where a hop is a dependency either way or between a subtask and its
+/// parent. Items keep the dates and lanes they were scheduled with, lanes
+/// that are left empty are removed, and the rest keep their order.
+pub fn apply(
+    chart: &mut Chart,
+    dependencies: &[Dependency],
+    key: &str,
+    radius: u16,
+) -> Result<(), Box<dyn Error>> {
+    let index: HashMap<&str, usize> = chart
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| (item.id(), i))
+        .collect();
+    let focus = index
+        .get(key)
+        .copied()
+        .or_else(|| chart.items.iter().position(|item| item.data.title == key))
+        .ok_or_else(|| format!("Issue '{}' of --focus isn't on the chart", key))?;
+    let parents: HashMap<&str, usize> = chart
+        .items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| item.summary.as_deref().map(|summary| (summary, i)))
+        .chain(index.iter().map(|(key, i)| (*key, *i)))
+        .collect();
+    let mut neighbors: Vec<Vec<usize>> = vec![vec![]; chart.items.len()];
+    let mut link = |a: usize, b: usize| {
+        neighbors[a].push(b);
+        neighbors[b].push(a);
+    };
+
+    for dep in dependencies.iter() {
+        if let (Some(before), Some(after)) = (index.get(&*dep.before), index.get(&*dep.after)) {
+            link(*before, *after);
+        }
+    }
+
+    for (i, item) in chart.items.iter().enumerate() {
+        if let Some(parent) = item.epic.as_deref().and_then(|epic| parents.get(epic)) {
+            if *parent != i {
+                link(i, *parent);
+            }
+        }
+    }
+
+    let mut kept: HashSet<usize> = HashSet::from([focus]);
+    let mut frontier = vec![focus];
+
+    for _ in 0..radius {
+        frontier = frontier
+            .iter()
+            .flat_map(|i| neighbors[*i].iter().copied())
+            .filter(|i| kept.insert(*i))
+            .collect();
+    }
+
+    // An item that followed on from one that is removed starts where, and in
+    // the lane, it was scheduled
+    let scheduled: Vec<_> = schedule::schedule(chart)
+        .iter()
+        .map(|item| (item.start, item.resource))
+        .collect();
+
+    for (i, (start, resource)) in scheduled.into_iter().enumerate() {
+        if kept.contains(&i) && (i == 0 || !kept.contains(&(i - 1))) {
+            let data = &mut chart.items[i].data;
+
+            data.start_date = data.start_date.or(start);
+            data.resource_index = data.resource_index.or(resource);
+        }
+    }
+
+    let mut i = 0;
+
+    chart.items.retain(|_| {
+        i += 1;
+        kept.contains(&(i - 1))
+    });
+
+    let ids: HashSet<String> = chart
+        .items
+        .iter()
+        .map(|item| item.id().to_owned())
+        .collect();
+
+    for item in chart.items.iter_mut() {
+        item.depends_on.retain(|id| ids.contains(id));
+    }
+
+    let used: HashSet<usize> = chart
+        .items
+        .iter()
+        .filter_map(|item| item.data.resource_index)
+        .collect();
+    let mut lanes: HashMap<usize, usize> = HashMap::new();
+
+    chart.resources = std::mem::take(&mut chart.resources)
+        .into_iter()
+        .enumerate()
+        .filter(|(old, _)| used.contains(old))
+        .map(|(old, name)| {
+            lanes.insert(old, lanes.len());
+            name
+        })
+        .collect();
+
+    for item in chart.items.iter_mut() {
+        item.data.resource_index = item
+            .data
+            .resource_index
+            .and_then(|old| lanes.get(&old).copied());
+    }
+
+    for group in chart.groups.iter_mut() {
+        group.resources = group
+            .resources
+            .iter()
+            .filter_map(|old| lanes.get(old).copied())
+            .collect();
+    }
+
+    chart.groups.retain(|group| !group.resources.is_empty());
+
+    // Time off of only removed lanes goes, rather than becoming everyone's
+    chart.non_working.retain_mut(|days| {
+        if days.resources.is_empty() {
+            return true;
+        }
+
+        days.resources = days
+            .resources
+            .iter()
+            .filter_map(|old| lanes.get(old).copied())
+            .collect();
+        !days.resources.is_empty()
+    });
+
+    Ok(())
+}
@@ -0,0 +1,603 @@
+use crate::{
+    paginate,
+    report::{self, ReportFormat, ReportKind, SummaryTable},
+    JiraToGanttLog, JiraToGanttTool,
+};
+use clap::ValueEnum;
+use core::fmt::Arguments;
+use easy_error::{self, ResultExt};
+use serde_json::Value;
+use std::{
+    cell::RefCell,
+    env,
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+/// An export from the bundled suite, with what each backend should make of
+/// it. The exports and golden files are in `tests/golden`.
+struct Case {
+    name: &'static str,
+    input: &'static str,
+    /// The options the export is converted with
+    args: &'static [&'static str],
+    /// The kinds of report to write, e.g. `summary`
+    reports: &'static [&'static str],
+    chart: &'static str,
+    report_text: &'static str,
+    report_csv: &'static str,
+    report_markdown: &'static str,
+    summary_epics: &'static str,
+    summary_capacity: &'static str,
+    schedule: &'static str,
+    due_dates: &'static str,
+    workbook: &'static str,
+    pages: &'static str,
+}
+
+macro_rules! case {
+    ($name:literal, $args:expr, $reports:expr) => {
+        Case {
+            name: $name,
+            input: include_str!(concat!("../tests/golden/", $name, ".csv")),
+            args: $args,
+            reports: $reports,
+            chart: include_str!(concat!("../tests/golden/", $name, ".json5")),
+            report_text: include_str!(concat!("../tests/golden/", $name, ".report.txt")),
+            report_csv: include_str!(concat!("../tests/golden/", $name, ".report.csv")),
+            report_markdown: include_str!(concat!("../tests/golden/", $name, ".report.md")),
+            summary_epics: include_str!(concat!("../tests/golden/", $name, ".summary-epics.csv")),
+            summary_capacity: include_str!(concat!(
+                "../tests/golden/",
+                $name,
+                ".summary-capacity.csv"
+            )),
+            schedule: include_str!(concat!("../tests/golden/", $name, ".schedule.tsv")),
+            due_dates: include_str!(concat!("../tests/golden/", $name, ".due-dates.csv")),
+            workbook: include_str!(concat!("../tests/golden/", $name, ".workbook.tsv")),
+            pages: include_str!(concat!("../tests/golden/", $name, ".pages.tsv")),
+        }
+    };
+}
+
+const CASES: [Case; 3] = [
+    case!("jira", &["--source", "jira"], &["summary", "next-up"]),
+    case!(
+        "plan",
+        &[
+            "--source",
+            "plan",
+            "--group-by",
+            "epic,assignee",
+            "--milestones",
+            "fix-version"
+        ],
+        &["summary"]
+    ),
+    case!(
+        "generic",
+        &["--source", "generic", "--stable-sort", "--ignore-name-case"],
+        &["summary", "workload-by-week"]
+    ),
+];
+
+/// What a case is written as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Backend {
+    /// The chart data
+    Chart,
+    /// The chart drawn by `gantt_chart`
+    Svg,
+    /// The reports, in each of their formats
+    Report(ReportFormat),
+    /// The summary's other tables, which go in files of their own in CSV
+    SummaryTable(SummaryTable),
+    /// The schedule of `--print-schedule tsv`
+    Schedule,
+    /// The file of `--due-dates`
+    DueDates,
+    /// The HTML workbook of `--views`, by its title and the heading of each
+    /// view, as the drawings in it change with `gantt_chart`
+    Workbook,
+    /// The charts of `--max-resources-per-chart`, by the title, lanes and
+    /// items of each page
+    Pages,
+}
+
+/// The views each case's workbook has
+const VIEWS: &str = "assignee,epic,sprint";
+/// The most lanes on each page, so that all but the smallest case have
+/// more than one
+const RESOURCES_PER_PAGE: &str = "2";
+
+const BACKENDS: [Backend; 11] = [
+    Backend::Chart,
+    Backend::Svg,
+    Backend::Report(ReportFormat::Text),
+    Backend::Report(ReportFormat::Csv),
+    Backend::Report(ReportFormat::Markdown),
+    Backend::SummaryTable(SummaryTable::Epics),
+    Backend::SummaryTable(SummaryTable::Capacity),
+    Backend::Schedule,
+    Backend::DueDates,
+    Backend::Workbook,
+    Backend::Pages,
+];
+
+impl Backend {
+    /// The name of the golden file for a case. Drawings have none, as they
+    /// change with the version of `gantt_chart`, so they are checked against
+    /// the chart data instead.
+    fn golden_name(&self, case: &str) -> Option<String> {
+        let extension = match self {
+            Backend::Chart => "json5",
+            Backend::Svg => return None,
+            Backend::Report(ReportFormat::Text) => "report.txt",
+            Backend::Report(ReportFormat::Csv) => "report.csv",
+            Backend::Report(ReportFormat::Markdown) => "report.md",
+            Backend::SummaryTable(table) => {
+                return Some(format!("{}.summary-{}.csv", case, table.name()))
+            }
+            Backend::Schedule => "schedule.tsv",
+            Backend::DueDates => "due-dates.csv",
+            Backend::Workbook => "workbook.tsv",
+            Backend::Pages => "pages.tsv",
+        };
+
+        Some(format!("{}.{}", case, extension))
+    }
+
+    fn golden<'a>(&self, case: &'a Case) -> &'a str {
+        match self {
+            Backend::Chart | Backend::Svg => case.chart,
+            Backend::Report(ReportFormat::Text) => case.report_text,
+            Backend::Report(ReportFormat::Csv) => case.report_csv,
+            Backend::Report(ReportFormat::Markdown) => case.report_markdown,
+            Backend::SummaryTable(SummaryTable::Epics) => case.summary_epics,
+            Backend::SummaryTable(SummaryTable::Capacity) => case.summary_capacity,
+            Backend::Schedule => case.schedule,
+            Backend::DueDates => case.due_dates,
+            Backend::Workbook => case.workbook,
+            Backend::Pages => case.pages,
+        }
+    }
+}
+
+/// Keeps what the tool prints, which is where the schedule goes
+#[derive(Default)]
+struct CaptureLog(RefCell<String>);
+
+impl JiraToGanttLog for CaptureLog {
+    fn output(&self, args: Arguments) {
+        let mut output = self.0.borrow_mut();
+
+        output.push_str(&args.to_string());
+        output.push('\n');
+    }
+
+    fn warning(&self, _args: Arguments) {}
+    fn error(&self, _args: Arguments) {}
+}
+
+/// The outcome of one case through one backend, named after its golden file
+pub struct Check {
+    pub name: String,
+    /// The first difference from the golden file, if there is one
+    pub difference: Option<String>,
+}
+
+/// Run every case of the suite through every backend and compare what each
+/// writes with its golden file. With `bless` what they write is saved to
+/// that directory as the new golden files instead, to review and check in.
+pub fn run(bless: Option<&Path>) -> Result<Vec<Check>, Box<dyn Error>> {
+    let dir = env::temp_dir().join(format!("jira_to_gantt_self_test_{}", process::id()));
+
+    fs::create_dir_all(&dir).context(format!(
+        "Unable to create directory '{}'",
+        dir.to_string_lossy()
+    ))?;
+
+    let checks = run_in(&dir, bless);
+
+    fs::remove_dir_all(&dir).ok();
+    checks
+}
+
+fn run_in(dir: &Path, bless: Option<&Path>) -> Result<Vec<Check>, Box<dyn Error>> {
+    // An empty config, so the config file of the current directory isn't
+    // read
+    let config = dir.join("config.json5");
+    let mut checks = vec![];
+
+    fs::write(&config, "{}")?;
+
+    for case in CASES.iter() {
+        for backend in BACKENDS {
+            let name = backend
+                .golden_name(case.name)
+                .unwrap_or_else(|| format!("{}.svg", case.name));
+            let difference = match convert(case, backend, dir, &config) {
+                Ok(actual) => match bless {
+                    Some(bless) => {
+                        if let Some(ref name) = backend.golden_name(case.name) {
+                            let path = bless.join(name);
+
+                            fs::write(&path, &actual).context(format!(
+                                "Unable to write golden file '{}'",
+                                path.to_string_lossy()
+                            ))?;
+                        }
+
+                        None
+                    }
+                    None => compare(backend, backend.golden(case), &actual).err(),
+                },
+                Err(err) => Some(err.to_string()),
+            };
+
+            checks.push(Check { name, difference });
+        }
+    }
+
+    Ok(checks)
+}
+
+/// Convert the export of a case as a backend needs, returning what it wrote
+fn convert(
+    case: &Case,
+    backend: Backend,
+    dir: &Path,
+    config: &Path,
+) -> Result<String, Box<dyn Error>> {
+    let input = dir.join(format!("{}.csv", case.name));
+    let chart_file = dir.join(format!("{}.out", case.name));
+    let other_file = dir.join(format!("{}.other", case.name));
+    let path = |path: &PathBuf| path.clone().into_os_string();
+    let mut args: Vec<OsString> = vec!["jira-to-gantt".into(), path(&input)];
+
+    fs::write(&input, case.input)?;
+    fs::remove_file(&chart_file).ok();
+    fs::remove_file(&other_file).ok();
+
+    for page in pages_of(&chart_file) {
+        fs::remove_file(page).ok();
+    }
+
+    for kind in case
+        .reports
+        .iter()
+        .filter_map(|name| ReportKind::from_str(name, false).ok())
+    {
+        fs::remove_file(report::report_path(&other_file, kind)).ok();
+    }
+
+    for table in SummaryTable::ALL {
+        fs::remove_file(report::summary_table_path(&other_file, table)).ok();
+    }
+
+    args.extend(case.args.iter().map(OsString::from));
+    args.extend(["--config".into(), config.as_os_str().to_owned()]);
+
+    match backend {
+        Backend::Chart => (),
+        Backend::Svg => args.extend(["--format".into(), "svg".into()]),
+        Backend::Report(_) | Backend::SummaryTable(_) => {
+            let format = match backend {
+                Backend::Report(format) => format,
+                _ => ReportFormat::Csv,
+            };
+
+            for report in case.reports {
+                args.extend(["--report".into(), OsString::from(report)]);
+            }
+
+            args.extend([
+                "--report-format".into(),
+                format
+                    .to_possible_value()
+                    .map(|value| OsString::from(value.get_name()))
+                    .unwrap_or_default(),
+                "--report-file".into(),
+                path(&other_file),
+            ]);
+        }
+        Backend::Schedule => args.extend(["--print-schedule".into(), "tsv".into()]),
+        Backend::DueDates => args.extend(["--due-dates".into(), path(&other_file)]),
+        Backend::Workbook => args.extend(["--views".into(), VIEWS.into()]),
+        Backend::Pages => args.extend([
+            "--max-resources-per-chart".into(),
+            RESOURCES_PER_PAGE.into(),
+        ]),
+    }
+
+    args.extend(["-o".into(), path(&chart_file)]);
+
+    let log = CaptureLog::default();
+
+    JiraToGanttTool::new(&log).run(args)?;
+
+    let read = |path: &Path| {
+        fs::read_to_string(path)
+            .map_err(|_| format!("it didn't write '{}'", path.to_string_lossy()))
+    };
+
+    Ok(match backend {
+        Backend::Chart | Backend::Svg => read(&chart_file)?,
+        Backend::Schedule => log.0.into_inner(),
+        // Each of several CSV reports is in a file of its own, and they are
+        // checked one after another
+        Backend::Report(ReportFormat::Csv) if case.reports.len() > 1 => case
+            .reports
+            .iter()
+            .map(|name| {
+                let kind = ReportKind::from_str(name, false)?;
+
+                read(&report::report_path(&other_file, kind))
+            })
+            .collect::<Result<Vec<String>, String>>()?
+            .join("\n"),
+        Backend::Report(_) | Backend::DueDates => read(&other_file)?,
+        Backend::SummaryTable(table) => read(&report::summary_table_path(&other_file, table))?,
+        Backend::Workbook => workbook_outline(&read(&chart_file)?),
+        // A chart that fits on one page is written as it is
+        Backend::Pages => {
+            let mut pages = pages_of(&chart_file)
+                .iter()
+                .map(|page| read(page))
+                .collect::<Result<Vec<String>, String>>()?;
+
+            if pages.is_empty() {
+                pages.push(read(&chart_file)?);
+            }
+
+            pages_outline(&pages)?
+        }
+    })
+}
+
+/// The page files written for a chart, in page order
+fn pages_of(chart_file: &Path) -> Vec<PathBuf> {
+    (1..)
+        .map(|page| paginate::page_path(chart_file, page))
+        .take_while(|page| page.exists())
+        .collect()
+}
+
+/// The title of a workbook, then the heading of each view and whether it
+/// has a drawing, as rows of tab separated cells
+fn workbook_outline(html: &str) -> String {
+    let between = |text: &str, start: &str, end: &str| {
+        text.split_once(start)
+            .and_then(|(_, rest)| rest.split_once(end))
+            .map(|(inner, _)| inner.to_owned())
+            .unwrap_or_default()
+    };
+    let mut rows = vec![format!("title\t{}", between(html, "<title>", "</title>"))];
+
+    for section in html.split("<section>").skip(1) {
+        rows.push(format!(
+            "{}\t{}",
+            between(section, "<h1>", "</h1>"),
+            if section.contains("<svg") {
+                "drawn"
+            } else {
+                "empty"
+            }
+        ));
+    }
+
+    rows.join("\n")
+}
+
+/// The title, lanes and item titles of each page of a chart, as rows of
+/// tab separated cells
+fn pages_outline(pages: &[String]) -> Result<String, Box<dyn Error>> {
+    let mut rows = vec![];
+
+    for page in pages {
+        let chart: Value = json5::from_str(page)?;
+        let names = |values: &Value, name: Option<&str>| {
+            values
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|value| match name {
+                    Some(name) => value[name].as_str(),
+                    None => value.as_str(),
+                })
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        };
+
+        rows.push(format!(
+            "{}\t{}\t{}",
+            chart["title"].as_str().unwrap_or_default(),
+            names(&chart["resources"], None),
+            names(&chart["items"], Some("title"))
+        ));
+    }
+
+    Ok(rows.join("\n"))
+}
+
+/// Compare what a backend wrote with its golden file by what it means rather
+/// than byte by byte, so layout such as padding and key order doesn't
+/// matter. Returns the first difference.
+fn compare(backend: Backend, expected: &str, actual: &str) -> Result<(), String> {
+    match backend {
+        Backend::Chart => {
+            let expected: Value = json5::from_str(expected)
+                .map_err(|err| format!("the golden file is invalid: {}", err))?;
+            let actual: Value =
+                json5::from_str(actual).map_err(|err| format!("it isn't chart data: {}", err))?;
+
+            match difference("chart", &expected, &actual) {
+                Some(difference) => Err(difference),
+                None => Ok(()),
+            }
+        }
+        Backend::Svg => {
+            let chart: Value = json5::from_str(expected)
+                .map_err(|err| format!("the golden file is invalid: {}", err))?;
+
+            if !actual.trim_start().starts_with("<svg") {
+                return Err("it isn't an SVG drawing".to_owned());
+            }
+
+            // The text between tags, however the drawing is laid out
+            let texts: Vec<&str> = actual
+                .split('>')
+                .filter_map(|part| part.split('<').next())
+                .map(|text| text.trim())
+                .filter(|text| !text.is_empty())
+                .collect();
+            let titles = chart["items"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|item| item["title"].as_str());
+
+            for title in titles {
+                if !texts.contains(&title) {
+                    return Err(format!("item '{}' isn't drawn", title));
+                }
+            }
+
+            Ok(())
+        }
+        Backend::Report(format) => compare_rows(&rows(format, expected), &rows(format, actual)),
+        Backend::SummaryTable(_) => {
+            compare_rows(&delimited(expected, b','), &delimited(actual, b','))
+        }
+        Backend::Schedule | Backend::Workbook | Backend::Pages => {
+            compare_rows(&delimited(expected, b'\t'), &delimited(actual, b'\t'))
+        }
+        Backend::DueDates => compare_rows(&delimited(expected, b','), &delimited(actual, b',')),
+    }
+}
+
+/// The first difference between two JSON values, at a path such as
+/// `chart.items[2].duration`. A missing field is the same as `null`, and
+/// numbers are compared by value.
+fn difference(path: &str, expected: &Value, actual: &Value) -> Option<String> {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            let mut keys: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+
+            keys.sort();
+            keys.dedup();
+            keys.into_iter().find_map(|key| {
+                difference(
+                    &format!("{}.{}", path, key),
+                    expected.get(key).unwrap_or(&Value::Null),
+                    actual.get(key).unwrap_or(&Value::Null),
+                )
+            })
+        }
+        (Value::Array(expected), Value::Array(actual)) => expected
+            .iter()
+            .zip(actual.iter())
+            .enumerate()
+            .find_map(|(index, (expected, actual))| {
+                difference(&format!("{}[{}]", path, index), expected, actual)
+            })
+            .or_else(|| {
+                (expected.len() != actual.len()).then(|| {
+                    format!(
+                        "{} has {} entries, expected {}",
+                        path,
+                        actual.len(),
+                        expected.len()
+                    )
+                })
+            }),
+        (Value::Number(expected), Value::Number(actual))
+            if expected.as_f64() == actual.as_f64() =>
+        {
+            None
+        }
+        (expected, actual) if expected == actual => None,
+        (expected, actual) => Some(format!("{} is {}, expected {}", path, actual, expected)),
+    }
+}
+
+/// The cells of each line of a report, without the padding, separator rows
+/// and markup of its format
+fn rows(format: ReportFormat, report: &str) -> Vec<Vec<String>> {
+    match format {
+        ReportFormat::Text => report
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                // Columns are padded with at least two spaces
+                line.split("  ")
+                    .map(|cell| cell.trim())
+                    .filter(|cell| !cell.is_empty())
+                    .map(|cell| cell.to_owned())
+                    .collect()
+            })
+            .collect(),
+        ReportFormat::Csv => delimited(report, b','),
+        ReportFormat::Markdown => report
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| match line.strip_prefix('|') {
+                Some(cells) => cells
+                    .trim_end_matches('|')
+                    .split(" | ")
+                    .map(|cell| cell.trim().to_owned())
+                    .collect(),
+                None => vec![line.trim_start_matches('#').trim().to_owned()],
+            })
+            .filter(|cells: &Vec<String>| {
+                !cells
+                    .iter()
+                    .all(|cell| !cell.is_empty() && cell.chars().all(|c| c == '-' || c == ':'))
+            })
+            .collect(),
+    }
+}
+
+/// The cells of each row of delimited values, ignoring blank lines
+fn delimited(content: &str, delimiter: u8) -> Vec<Vec<String>> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(content.as_bytes())
+        .records()
+        .filter_map(|record| record.ok())
+        .map(|record| record.iter().map(|cell| cell.trim().to_owned()).collect())
+        .filter(|cells: &Vec<String>| cells.iter().any(|cell| !cell.is_empty()))
+        .collect()
+}
+
+fn compare_rows(expected: &[Vec<String>], actual: &[Vec<String>]) -> Result<(), String> {
+    let line = |cells: &[String]| cells.join(" | ");
+
+    for (index, (expected, actual)) in expected.iter().zip(actual.iter()).enumerate() {
+        if expected != actual {
+            return Err(format!(
+                "row {} is '{}', expected '{}'",
+                index + 1,
+                line(actual),
+                line(expected)
+            ));
+        }
+    }
+
+    if expected.len() != actual.len() {
+        return Err(format!(
+            "it has {} rows, expected {}",
+            actual.len(),
+            expected.len()
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::{chart::Chart, schedule};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// How far the share of an item's work that is done may be from the share
+/// of its time that has gone before it is ahead or behind, in percent
+const TOLERANCE: i64 = 10;
+
+/// Whether an open item's logged work is keeping up with the time it has had
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Health {
+    Ahead,
+    OnTrack,
+    Behind,
+}
+
+impl Health {
+    /// The translation key of the name of the health
+    pub fn name(&self) -> &'static str {
+        match self {
+            Health::Ahead => "health-ahead",
+            Health::OnTrack => "health-on-track",
+            Health::Behind => "health-behind",
+        }
+    }
+}
+
+/// The percentage of an item's days that have gone by a date
+pub fn elapsed(start: NaiveDate, duration: i64, today: NaiveDate) -> i64 {
+    ((today - start).num_days() * 100 / duration).clamp(0, 100)
+}
+
+/// Give each open item that has started by `today` a health, by comparing
+/// its progress with the share of its days that have gone. Items without a
+/// progress and milestones are left alone.
+pub fn apply(chart: &mut Chart, today: NaiveDate) {
+    let healths: Vec<Option<Health>> = schedule::schedule(chart)
+        .iter()
+        .map(|item| {
+            let (start, duration, progress) =
+                match (item.start, item.item.data.duration, item.item.progress) {
+                    (Some(start), Some(duration), Some(progress))
+                        if duration > 0 && start <= today && item.item.data.open != Some(false) =>
+                    {
+                        (start, duration, progress as i64)
+                    }
+                    _ => return None,
+                };
+            let elapsed = elapsed(start, duration, today);
+
+            Some(if progress > elapsed + TOLERANCE {
+                Health::Ahead
+            } else if progress < elapsed - TOLERANCE {
+                Health::Behind
+            } else {
+                Health::OnTrack
+            })
+        })
+        .collect();
+
+    for (item, health) in chart.items.iter_mut().zip(healths) {
+        item.health = health;
+    }
+}
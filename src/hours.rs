@@ -0,0 +1,126 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// What durations and starts are worked out in before they are rounded to
+/// the days of the chart
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    /// Each issue takes whole days and the next one starts the day after
+    Days,
+    /// Issues take working hours, so short issues can share a day
+    Hours,
+}
+
+/// The times of each weekday that count as working hours
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkingHours(Vec<(NaiveTime, NaiveTime)>);
+
+impl Default for WorkingHours {
+    fn default() -> Self {
+        WorkingHours(vec![(
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+        )])
+    }
+}
+
+impl FromStr for WorkingHours {
+    type Err = String;
+
+    /// Parse windows such as `09:00-12:00,13:00-17:00`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "'{}' must be times of day, e.g. 09:00-17:00 or 09:00-12:00,13:00-17:30",
+                s
+            )
+        };
+        let mut windows = vec![];
+
+        for window in s.split(',') {
+            let (from, to) = window.split_once('-').ok_or_else(invalid)?;
+            let from = NaiveTime::parse_from_str(from.trim(), "%H:%M").map_err(|_| invalid())?;
+            let to = NaiveTime::parse_from_str(to.trim(), "%H:%M").map_err(|_| invalid())?;
+
+            if from >= to || windows.last().is_some_and(|(_, end)| *end > from) {
+                return Err(invalid());
+            }
+
+            windows.push((from, to));
+        }
+
+        Ok(WorkingHours(windows))
+    }
+}
+
+impl fmt::Display for WorkingHours {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let windows: Vec<String> = self
+            .0
+            .iter()
+            .map(|(from, to)| format!("{}-{}", from.format("%H:%M"), to.format("%H:%M")))
+            .collect();
+
+        write!(f, "{}", windows.join(","))
+    }
+}
+
+impl WorkingHours {
+    /// The first working moment at or after `from`, on a day that
+    /// `is_working_day`
+    fn next_working(
+        &self,
+        from: NaiveDateTime,
+        is_working_day: &dyn Fn(NaiveDate) -> bool,
+    ) -> NaiveDateTime {
+        let mut date = from.date();
+
+        loop {
+            if is_working_day(date) {
+                for (start, end) in self.0.iter() {
+                    if from < date.and_time(*end) {
+                        return from.max(date.and_time(*start));
+                    }
+                }
+            }
+
+            date = date.succ_opt().unwrap_or(NaiveDate::MAX);
+        }
+    }
+
+    /// When work of `seconds` starting no earlier than `from` starts and
+    /// ends, working only on the days that `is_working_day`, e.g. those that
+    /// aren't weekends, holidays or a resource's time off
+    pub fn add(
+        &self,
+        from: NaiveDateTime,
+        seconds: i64,
+        is_working_day: &dyn Fn(NaiveDate) -> bool,
+    ) -> (NaiveDateTime, NaiveDateTime) {
+        let start = self.next_working(from, is_working_day);
+        let mut now = start;
+        let mut remaining = seconds;
+
+        while remaining > 0 {
+            let end = self
+                .0
+                .iter()
+                .map(|(_, end)| now.date().and_time(*end))
+                .find(|end| now < *end)
+                .unwrap();
+            let available = (end - now).num_seconds();
+
+            if remaining <= available {
+                return (start, now + Duration::seconds(remaining));
+            }
+
+            remaining -= available;
+            now = self.next_working(end, is_working_day);
+        }
+
+        (start, now)
+    }
+}
@@ -0,0 +1,329 @@
+use easy_error::{self, ResultExt};
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+/// The language used when no other is given, and for any string missing
+/// from a translation
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// The embedded translations of every generated string, by language
+const TABLE: &[(&str, &[(&str, &str)])] = &[
+    (
+        "en",
+        &[
+            ("unassigned", "unassigned"),
+            ("needs-reassignment", "Needs reassignment"),
+            ("no-issue-type", "no issue type"),
+            ("no-epic", "no epic"),
+            ("no-team", "no team"),
+            ("no-sprint", "no sprint"),
+            ("sprints", "sprints"),
+            ("sprint-end", "End of {}"),
+            ("releases", "releases"),
+            ("deadlines", "deadlines"),
+            ("due-issues", "{} issue(s) due"),
+            ("committed", "committed"),
+            ("status", "Status"),
+            ("assignee", "Assignee"),
+            ("estimate", "Estimate"),
+            ("risk", "Risk"),
+            ("days", "{} day(s)"),
+            ("report-summary", "Summary"),
+            ("report-issues", "Issues"),
+            ("report-total-days", "Total days"),
+            ("report-total", "Total"),
+            ("report-end-date", "End date"),
+            ("report-committed", "Committed"),
+            ("report-stretch", "Stretch"),
+            ("report-resource", "Resource"),
+            ("report-items", "Items"),
+            ("report-days", "Days"),
+            ("report-start", "Start"),
+            ("report-end", "End"),
+            ("report-epics", "Epics"),
+            ("report-epic", "Epic"),
+            ("report-earliest-finish", "Earliest finish"),
+            ("report-due", "Due"),
+            ("report-slip", "Slip"),
+            ("report-weekly-capacity", "Weekly capacity"),
+            ("report-week-of", "Week of"),
+            ("report-scheduled", "Scheduled"),
+            ("report-capacity", "Capacity"),
+            ("report-load", "Load"),
+            ("report-over", "OVER"),
+            ("report-next-up", "Next up"),
+            ("report-key", "Key"),
+            ("report-health", "Health"),
+            ("report-elapsed", "Elapsed"),
+            ("report-progress", "Progress"),
+            ("health-ahead", "ahead"),
+            ("health-on-track", "on track"),
+            ("health-behind", "behind"),
+            ("report-blocked", "Blocked"),
+            ("report-periods", "Periods"),
+            ("report-blocked-days", "Blocked days"),
+            ("report-reassign", "Needs reassignment"),
+            ("report-former-assignee", "Former assignee"),
+            ("report-workload-by-week", "Workload by week"),
+            ("report-sla", "Projected SLA breaches"),
+            ("report-sla-due", "SLA due"),
+            ("report-late", "Late"),
+            ("view-assignee", "By assignee"),
+            ("view-epic", "By epic"),
+            ("view-sprint", "By sprint"),
+            ("month-jan", "Jan"),
+            ("month-feb", "Feb"),
+            ("month-mar", "Mar"),
+            ("month-apr", "Apr"),
+            ("month-may", "May"),
+            ("month-jun", "Jun"),
+            ("month-jul", "Jul"),
+            ("month-aug", "Aug"),
+            ("month-sep", "Sep"),
+            ("month-oct", "Oct"),
+            ("month-nov", "Nov"),
+            ("month-dec", "Dec"),
+        ],
+    ),
+    (
+        "de",
+        &[
+            ("unassigned", "nicht zugewiesen"),
+            ("needs-reassignment", "Neu zuzuweisen"),
+            ("no-issue-type", "ohne Vorgangstyp"),
+            ("no-epic", "ohne Epic"),
+            ("no-team", "ohne Team"),
+            ("no-sprint", "ohne Sprint"),
+            ("sprints", "Sprints"),
+            ("sprint-end", "Ende von {}"),
+            ("releases", "Releases"),
+            ("deadlines", "Fristen"),
+            ("due-issues", "{} Vorgang/Vorgänge fällig"),
+            ("committed", "zugesagt"),
+            ("status", "Status"),
+            ("assignee", "Bearbeiter"),
+            ("estimate", "Schätzung"),
+            ("risk", "Risiko"),
+            ("days", "{} Tag(e)"),
+            ("report-summary", "Zusammenfassung"),
+            ("report-issues", "Vorgänge"),
+            ("report-total-days", "Tage gesamt"),
+            ("report-total", "Gesamt"),
+            ("report-end-date", "Enddatum"),
+            ("report-committed", "Zugesagt"),
+            ("report-stretch", "Optional"),
+            ("report-resource", "Ressource"),
+            ("report-items", "Einträge"),
+            ("report-days", "Tage"),
+            ("report-start", "Beginn"),
+            ("report-end", "Ende"),
+            ("report-epics", "Epics"),
+            ("report-epic", "Epic"),
+            ("report-earliest-finish", "Frühestes Ende"),
+            ("report-due", "Fällig"),
+            ("report-slip", "Verzug"),
+            ("report-weekly-capacity", "Wöchentliche Kapazität"),
+            ("report-week-of", "Woche ab"),
+            ("report-scheduled", "Geplant"),
+            ("report-capacity", "Kapazität"),
+            ("report-load", "Auslastung"),
+            ("report-over", "ÜBER"),
+            ("report-next-up", "Als Nächstes"),
+            ("report-key", "Schlüssel"),
+            ("report-health", "Zustand"),
+            ("report-elapsed", "Verstrichen"),
+            ("report-progress", "Fortschritt"),
+            ("health-ahead", "voraus"),
+            ("health-on-track", "im Plan"),
+            ("health-behind", "im Verzug"),
+            ("report-blocked", "Blockiert"),
+            ("report-periods", "Zeiträume"),
+            ("report-blocked-days", "Blockierte Tage"),
+            ("report-reassign", "Neu zuzuweisen"),
+            ("report-former-assignee", "Früherer Bearbeiter"),
+            ("report-workload-by-week", "Auslastung nach Woche"),
+            ("report-sla", "Voraussichtliche SLA-Verletzungen"),
+            ("report-sla-due", "SLA fällig"),
+            ("report-late", "Verspätung"),
+            ("view-assignee", "Nach Bearbeiter"),
+            ("view-epic", "Nach Epic"),
+            ("view-sprint", "Nach Sprint"),
+            ("month-jan", "Jan"),
+            ("month-feb", "Feb"),
+            ("month-mar", "Mär"),
+            ("month-apr", "Apr"),
+            ("month-may", "Mai"),
+            ("month-jun", "Jun"),
+            ("month-jul", "Jul"),
+            ("month-aug", "Aug"),
+            ("month-sep", "Sep"),
+            ("month-oct", "Okt"),
+            ("month-nov", "Nov"),
+            ("month-dec", "Dez"),
+        ],
+    ),
+    (
+        "es",
+        &[
+            ("unassigned", "sin asignar"),
+            ("no-issue-type", "sin tipo"),
+            ("no-epic", "sin épica"),
+            ("no-team", "sin equipo"),
+            ("no-sprint", "sin sprint"),
+            ("sprints", "sprints"),
+            ("sprint-end", "Fin de {}"),
+            ("releases", "versiones"),
+            ("deadlines", "plazos"),
+            ("due-issues", "{} incidencia(s) vence(n)"),
+            ("committed", "comprometido"),
+            ("status", "Estado"),
+            ("assignee", "Responsable"),
+            ("estimate", "Estimación"),
+            ("risk", "Riesgo"),
+            ("days", "{} día(s)"),
+            ("month-jan", "ene"),
+            ("month-feb", "feb"),
+            ("month-mar", "mar"),
+            ("month-apr", "abr"),
+            ("month-may", "may"),
+            ("month-jun", "jun"),
+            ("month-jul", "jul"),
+            ("month-aug", "ago"),
+            ("month-sep", "sep"),
+            ("month-oct", "oct"),
+            ("month-nov", "nov"),
+            ("month-dec", "dic"),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            ("unassigned", "non assigné"),
+            ("no-issue-type", "sans type"),
+            ("no-epic", "sans epic"),
+            ("no-team", "sans équipe"),
+            ("no-sprint", "sans sprint"),
+            ("sprints", "sprints"),
+            ("sprint-end", "Fin de {}"),
+            ("releases", "versions"),
+            ("deadlines", "échéances"),
+            ("due-issues", "{} ticket(s) dû(s)"),
+            ("committed", "engagé"),
+            ("status", "Statut"),
+            ("assignee", "Responsable"),
+            ("estimate", "Estimation"),
+            ("risk", "Risque"),
+            ("days", "{} jour(s)"),
+            ("month-jan", "janv."),
+            ("month-feb", "févr."),
+            ("month-mar", "mars"),
+            ("month-apr", "avr."),
+            ("month-may", "mai"),
+            ("month-jun", "juin"),
+            ("month-jul", "juil."),
+            ("month-aug", "août"),
+            ("month-sep", "sept."),
+            ("month-oct", "oct."),
+            ("month-nov", "nov."),
+            ("month-dec", "déc."),
+        ],
+    ),
+    (
+        "pt",
+        &[
+            ("unassigned", "não atribuído"),
+            ("no-issue-type", "sem tipo"),
+            ("no-epic", "sem épico"),
+            ("no-team", "sem equipe"),
+            ("no-sprint", "sem sprint"),
+            ("sprints", "sprints"),
+            ("sprint-end", "Fim de {}"),
+            ("releases", "versões"),
+            ("deadlines", "prazos"),
+            ("due-issues", "{} item(ns) com prazo"),
+            ("committed", "comprometido"),
+            ("status", "Status"),
+            ("assignee", "Responsável"),
+            ("estimate", "Estimativa"),
+            ("risk", "Risco"),
+            ("days", "{} dia(s)"),
+            ("month-jan", "jan"),
+            ("month-feb", "fev"),
+            ("month-mar", "mar"),
+            ("month-apr", "abr"),
+            ("month-may", "mai"),
+            ("month-jun", "jun"),
+            ("month-jul", "jul"),
+            ("month-aug", "ago"),
+            ("month-sep", "set"),
+            ("month-oct", "out"),
+            ("month-nov", "nov"),
+            ("month-dec", "dez"),
+        ],
+    ),
+];
+
+/// The generated strings for one language
+pub struct Translations {
+    strings: HashMap<String, String>,
+}
+
+impl Translations {
+    /// Load the embedded strings for a language, overridden by those in a
+    /// JSON5 translation file if one is given. A language that isn't
+    /// embedded is only allowed with a translation file.
+    pub fn load(lang: &str, file: Option<&Path>) -> Result<Translations, Box<dyn Error>> {
+        let embedded = |lang: &str| {
+            TABLE
+                .iter()
+                .find(|(l, _)| l.eq_ignore_ascii_case(lang))
+                .map(|(_, strings)| *strings)
+        };
+        let mut strings: HashMap<String, String> = HashMap::new();
+
+        for language in [DEFAULT_LANGUAGE, lang] {
+            match embedded(language) {
+                Some(table) => strings.extend(
+                    table
+                        .iter()
+                        .map(|(key, value)| (key.to_string(), value.to_string())),
+                ),
+                None if file.is_some() => (),
+                None => {
+                    return Err(From::from(format!(
+                        "Unknown language '{}', use one of {} or give a translation file",
+                        lang,
+                        TABLE.iter().map(|(l, _)| *l).collect::<Vec<_>>().join(", ")
+                    )))
+                }
+            }
+        }
+
+        if let Some(path) = file {
+            let content = fs::read_to_string(path).context(format!(
+                "Unable to read translation file '{}'",
+                path.to_string_lossy()
+            ))?;
+            let custom: HashMap<String, String> = json5::from_str(&content).map_err(|err| {
+                format!(
+                    "Invalid translation file '{}': {}",
+                    path.to_string_lossy(),
+                    err
+                )
+            })?;
+
+            strings.extend(custom);
+        }
+
+        Ok(Translations { strings })
+    }
+
+    /// Get a generated string, or the key itself if there is no translation
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings.get(key).map(|s| s.as_str()).unwrap_or(key)
+    }
+
+    /// Get a generated string with `{}` replaced by a value
+    pub fn format(&self, key: &str, value: impl ToString) -> String {
+        self.get(key).replace("{}", &value.to_string())
+    }
+}
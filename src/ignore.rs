@@ -0,0 +1,48 @@
+use easy_error::{self, ResultExt};
+use std::{error::Error, fs, path::Path};
+
+/// Issues to always leave out of charts, such as long running umbrella
+/// tickets, kept in a file of their own rather than in each export's filter
+pub struct IgnoreList {
+    /// Issue keys, or patterns where `*` matches any run of characters, e.g.
+    /// `OPS-*`, in upper case
+    patterns: Vec<String>,
+}
+
+impl IgnoreList {
+    /// Read a file with an issue key or pattern on each line. Blank lines and
+    /// lines starting with `#` are skipped.
+    pub fn load(path: &Path) -> Result<IgnoreList, Box<dyn Error>> {
+        let content = fs::read_to_string(path).context(format!(
+            "Unable to read ignore file '{}'",
+            path.to_string_lossy()
+        ))?;
+
+        Ok(IgnoreList {
+            patterns: content
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| line.to_uppercase())
+                .collect(),
+        })
+    }
+
+    /// Whether an issue is ignored. Keys are compared ignoring case.
+    pub fn ignores(&self, key: &str) -> bool {
+        let key = key.to_uppercase();
+
+        self.patterns
+            .iter()
+            .any(|pattern| matches(pattern.as_bytes(), key.as_bytes()))
+    }
+}
+
+/// Whether a key matches a pattern where `*` matches any run of characters
+pub fn matches(pattern: &[u8], key: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => key.is_empty(),
+        Some((b'*', rest)) => (0..=key.len()).any(|skip| matches(rest, &key[skip..])),
+        Some((c, rest)) => key.first() == Some(c) && matches(rest, &key[1..]),
+    }
+}
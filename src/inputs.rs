@@ -0,0 +1,77 @@
+use crate::ignore;
+use easy_error::{self, ResultExt};
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The extensions of the files in a directory that are read as exports
+const EXPORT_EXTENSIONS: [&str; 3] = ["csv", "tsv", "json"];
+
+/// Whether an input is a directory or a pattern rather than one file
+pub fn is_many(path: &Path) -> bool {
+    path.is_dir() || pattern(path).is_some()
+}
+
+/// The last part of a path, if it is a pattern where `*` matches any run of
+/// characters
+fn pattern(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| name.contains('*'))
+}
+
+/// The files that an input stands for, the exports in a directory or the
+/// files that match a pattern in the last part of its path, e.g.
+/// `exports/*.csv`, in order of their names so that merges come out the same
+/// each time. `None` for an input that is one file.
+pub fn expand(path: &Path) -> Result<Option<Vec<PathBuf>>, Box<dyn Error>> {
+    // A pattern without a directory is of files in the current one, and
+    // they are named as they would be on the command line
+    let (dir, pattern) = match pattern(path) {
+        Some(pattern) => (path.parent().unwrap_or(Path::new("")), Some(pattern)),
+        None if path.is_dir() => (path, None),
+        None => return Ok(None),
+    };
+    let listed = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let mut files = vec![];
+
+    for entry in fs::read_dir(listed).context(format!(
+        "Unable to read input directory '{}'",
+        listed.to_string_lossy()
+    ))? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        let file = dir.join(&name);
+        let wanted = match pattern {
+            Some(ref pattern) => ignore::matches(pattern.as_bytes(), name.as_bytes()),
+            None => file.extension().is_some_and(|extension| {
+                EXPORT_EXTENSIONS
+                    .iter()
+                    .any(|wanted| extension.eq_ignore_ascii_case(wanted))
+            }),
+        };
+
+        if wanted && file.is_file() {
+            files.push(file);
+        }
+    }
+
+    if files.is_empty() {
+        return Err(From::from(match pattern {
+            Some(_) => format!("No files match '{}'", path.to_string_lossy()),
+            None => format!(
+                "Input directory '{}' has no .csv, .tsv or .json exports",
+                path.to_string_lossy()
+            ),
+        }));
+    }
+
+    files.sort();
+
+    Ok(Some(files))
+}
@@ -0,0 +1,100 @@
+use crate::chart::Chart;
+use easy_error::{self, ResultExt};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs::File,
+    path::Path,
+};
+
+/// Give a key that is only a number, e.g. `123` or `#123` from GitHub or
+/// GitLab, a prefix so that it can't be confused with the same number from
+/// another tracker. Keys such as `PROJ-45` already say where they are from.
+pub fn normalize(key: &str, prefix: &str) -> String {
+    let number = key.strip_prefix('#').unwrap_or(key);
+
+    if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+        format!("{}#{}", prefix, number)
+    } else {
+        key.to_owned()
+    }
+}
+
+/// Keys of issues that are the same piece of work in different trackers,
+/// each mapped to the key to show it as
+pub struct KeyMap(HashMap<String, String>);
+
+impl KeyMap {
+    /// Read a CSV file of `key,canonical` rows. A header row is optional.
+    pub fn load(path: &Path) -> Result<KeyMap, Box<dyn Error>> {
+        let file = File::open(path).context(format!(
+            "Unable to open key map file '{}'",
+            path.to_string_lossy()
+        ))?;
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(file);
+        let mut map = HashMap::new();
+
+        for (i, row) in reader.records().enumerate() {
+            let row = row?;
+
+            if row.iter().all(|field| field.is_empty())
+                || (i == 0 && row[0].eq_ignore_ascii_case("key"))
+            {
+                continue;
+            }
+
+            match (row.get(0), row.get(1), row.len()) {
+                (Some(key), Some(canonical), 2) if !key.is_empty() && !canonical.is_empty() => {
+                    map.insert(key.to_owned(), canonical.to_owned());
+                }
+                _ => {
+                    return Err(From::from(format!(
+                        "Row '{}' of key map file '{}' must be key,canonical",
+                        row.iter().collect::<Vec<&str>>().join(","),
+                        path.to_string_lossy()
+                    )))
+                }
+            }
+        }
+
+        Ok(KeyMap(map))
+    }
+
+    /// Rename mapped items, and their identifiers, to their canonical key. Where that leaves more
+    /// than one item with the same key, the first is kept and the others are
+    /// removed. An item that followed a removed one in its lane starts on
+    /// the removed one's start date, if it had one.
+    pub fn reconcile(&self, chart: &mut Chart) {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut keep = vec![];
+
+        for item in chart.items.iter_mut() {
+            if let Some(canonical) = self.0.get(&item.data.title) {
+                item.data.title = canonical.to_owned();
+                item.id = Some(canonical.to_owned());
+            }
+
+            keep.push(seen.insert(item.data.title.to_owned()));
+        }
+
+        for (i, kept) in keep.iter().enumerate() {
+            let start_date = chart.items[i].data.start_date;
+
+            if !kept && start_date.is_some() {
+                if let Some(next) = chart.items.get_mut(i + 1) {
+                    if next.data.start_date.is_none() {
+                        next.data.start_date = start_date;
+                    }
+                }
+            }
+        }
+
+        let mut keep = keep.into_iter();
+
+        chart.items.retain(|_| keep.next().unwrap());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::record::JiraRecord;
+use clap::ValueEnum;
+use std::{cell::RefCell, str::FromStr};
+
+/// Where the resource of each issue comes from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResourceFrom {
+    /// The assignee, or the team of a plan
+    Assignee,
+    /// Labels starting with a prefix, e.g. `team-` for `team-alpha`
+    Label(String),
+}
+
+impl FromStr for ResourceFrom {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "assignee" => Ok(ResourceFrom::Assignee),
+            Some(("label", prefix)) if !prefix.is_empty() => {
+                Ok(ResourceFrom::Label(prefix.to_owned()))
+            }
+            _ => Err(format!(
+                "'{}' must be assignee, or label:<prefix> for labels such as team-alpha, e.g. label:team-",
+                s
+            )),
+        }
+    }
+}
+
+/// Which lane an issue with labels for more than one goes in
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    /// The first matching label of the issue
+    First,
+    /// The last matching label of the issue
+    Last,
+    /// The matching label that sorts first by name
+    Alphabetical,
+}
+
+/// Lanes named after labels with a prefix, instead of the assignee. The lane
+/// is the rest of the label, e.g. `alpha` for `team-alpha`, and issues
+/// without a matching label are unassigned.
+pub struct LabelLanes {
+    prefix: String,
+    tie_break: TieBreak,
+    conflicts: RefCell<Vec<String>>,
+}
+
+impl LabelLanes {
+    pub fn new(prefix: &str, tie_break: TieBreak) -> LabelLanes {
+        LabelLanes {
+            prefix: prefix.to_owned(),
+            tie_break,
+            conflicts: RefCell::new(vec![]),
+        }
+    }
+
+    /// The lanes of the labels of a record that start with the prefix
+    fn matching<'r>(&self, record: &'r JiraRecord) -> Vec<&'r str> {
+        let mut lanes: Vec<&str> = vec![];
+
+        for lane in record
+            .labels
+            .iter()
+            .filter_map(|label| label.strip_prefix(self.prefix.as_str()))
+            .filter(|lane| !lane.is_empty())
+        {
+            if !lanes.contains(&lane) {
+                lanes.push(lane);
+            }
+        }
+
+        lanes
+    }
+
+    /// The lane of a record
+    pub fn lane<'r>(&self, record: &'r JiraRecord) -> &'r str {
+        let lanes = self.matching(record);
+
+        match self.tie_break {
+            TieBreak::First => lanes.first().copied(),
+            TieBreak::Last => lanes.last().copied(),
+            TieBreak::Alphabetical => lanes.iter().min().copied(),
+        }
+        .unwrap_or_default()
+    }
+
+    /// Note a record with labels for more than one lane, to warn about later
+    pub fn check(&self, record: &JiraRecord) {
+        let lanes = self.matching(record);
+
+        if lanes.len() > 1 {
+            self.conflicts.borrow_mut().push(format!(
+                "Issue '{}' has labels for more than one lane ({}), putting it in '{}'",
+                record.key,
+                lanes.join(", "),
+                self.lane(record)
+            ));
+        }
+    }
+
+    /// The warnings for records with labels for more than one lane, which
+    /// are cleared
+    pub fn take_conflicts(&self) -> Vec<String> {
+        self.conflicts.take()
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    chart::{self, Chart, DurationSource, Group, Item},
+    i18n::Translations,
+    schedule,
+};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use gantt_chart::ItemData;
+use std::collections::BTreeMap;
+
+/// What each bar of the chart stands for
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    /// One bar for each issue
+    Issue,
+    /// One bar for each epic, covering all of its issues
+    Epic,
+}
+
+/// An epic and the span of its issues
+struct Epic {
+    /// The epic field of its issues, which is empty for issues with no epic
+    key: String,
+    name: String,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    /// When its first issue was created
+    created: Option<NaiveDate>,
+    open: bool,
+    /// The total of the estimates of its issues, if any have one
+    estimate: Option<u32>,
+    /// The work done, as the weight of each issue times its percent
+    /// complete, and the total weight of its issues, for the progress
+    done_weight: u64,
+    total_weight: u64,
+}
+
+/// Collapse a chart to one item for each epic, starting with its earliest
+/// issue and ending with its latest. Progress is the average percent
+/// complete of the epic's issues weighted by their estimates, so one large
+/// issue that is half done counts for more than several small ones that are
+/// finished. Issues without an estimate are weighted by their days on the
+/// chart, with days of `day_seconds`. Epics are in the order their first
+/// issue appears, each in a lane of its own.
+pub fn epics(chart: &Chart, text: &Translations, day_seconds: f64) -> Chart {
+    let mut epics: Vec<Epic> = vec![];
+
+    for item in schedule::schedule(chart).iter() {
+        let key = item.item.epic.as_deref().unwrap_or_default();
+        let name = if key.is_empty() {
+            text.get("no-epic")
+        } else {
+            key
+        };
+        let epic = match epics.iter().position(|epic| epic.name == name) {
+            Some(index) => &mut epics[index],
+            None => {
+                epics.push(Epic {
+                    key: key.to_owned(),
+                    name: name.to_owned(),
+                    start: None,
+                    created: None,
+                    end: None,
+                    open: false,
+                    estimate: None,
+                    done_weight: 0,
+                    total_weight: 0,
+                });
+                epics.last_mut().unwrap()
+            }
+        };
+        let days = item.item.data.duration.unwrap_or(0).max(0);
+        let open = item.item.data.open != Some(false);
+        let weight = match item.item.estimate {
+            Some(seconds) => seconds as u64,
+            None => (days as f64 * day_seconds) as u64,
+        };
+        // Closed issues are done whatever work was logged against them
+        let percent = if open {
+            item.item.progress.unwrap_or(0) as u64
+        } else {
+            100
+        };
+
+        epic.start = epic.start.into_iter().chain(item.start).min();
+        epic.created = epic.created.into_iter().chain(item.item.created).min();
+        epic.end = epic.end.into_iter().chain(item.end).max();
+        epic.open |= open;
+        epic.done_weight += weight * percent;
+        epic.total_weight += weight;
+
+        if let Some(seconds) = item.item.estimate {
+            epic.estimate = Some(epic.estimate.unwrap_or(0).saturating_add(seconds));
+        }
+    }
+
+    let mut items = vec![];
+
+    for (index, epic) in epics.iter().enumerate() {
+        let duration = match (epic.start, epic.end) {
+            (Some(start), Some(end)) if end > start => Some((end - start).num_days()),
+            _ => None,
+        };
+
+        items.push(Item {
+            id: Some(chart::synthetic_id("epic", &epic.key)),
+            data: ItemData {
+                title: epic.name.to_owned(),
+                start_date: epic.start,
+                duration,
+                resource_index: Some(index),
+                open: Some(epic.open),
+            },
+            tooltip: None,
+            sprint: None,
+            risk: None,
+            progress: Some(
+                epic.done_weight
+                    .checked_div(epic.total_weight)
+                    .unwrap_or(if epic.open { 0 } else { 100 }) as u8,
+            ),
+            health: None,
+            color: None,
+            blocked: vec![],
+            depends_on: vec![],
+            stretch: None,
+            fields: BTreeMap::new(),
+            duration_source: duration.map(|_| DurationSource::Issues),
+            estimate: epic.estimate,
+            epic: Some(epic.name.to_owned()),
+            assignee: None,
+            created: epic.created,
+            summary: None,
+            due_date: None,
+            former_assignee: None,
+            fix_versions: vec![],
+            sla_remaining: None,
+        });
+    }
+
+    Chart {
+        title: chart.title.to_owned(),
+        marked_date: chart.marked_date,
+        resources: epics.into_iter().map(|epic| epic.name).collect(),
+        items,
+        groups: vec![],
+        sprints: chart.sprints.clone(),
+        non_working: vec![],
+        units: chart.units.clone(),
+    }
+}
+
+/// Add a summary row at the top of each epic section of a chart grouped by
+/// epic and then something else, named after the epic and with the epic's
+/// item from `epics`, so the issues of each epic are nested beneath it.
+/// Every issue gets its start date, because the summary items come between
+/// the lanes.
+pub fn with_summaries(chart: &Chart, text: &Translations, day_seconds: f64) -> Chart {
+    let summaries = epics(chart, text, day_seconds);
+    let starts: Vec<Option<NaiveDate>> = schedule::schedule(chart)
+        .iter()
+        .map(|item| item.start)
+        .collect();
+    let mut resources = vec![];
+    let mut groups = vec![];
+    let mut items = vec![];
+
+    for group in chart.groups.iter() {
+        let summary_index = resources.len();
+
+        resources.push(group.name.to_owned());
+
+        if let Some(summary) = summaries
+            .items
+            .iter()
+            .find(|item| item.data.title == group.name)
+        {
+            let mut summary = summary.clone();
+
+            summary.data.resource_index = Some(summary_index);
+            items.push(summary);
+        }
+
+        for resource in group.resources.iter() {
+            let index = resources.len();
+
+            resources.push(chart.resources[*resource].to_owned());
+            items.extend(
+                chart
+                    .items
+                    .iter()
+                    .zip(starts.iter())
+                    .filter(|(item, _)| item.data.resource_index == Some(*resource))
+                    .map(|(item, start)| {
+                        let mut item = item.clone();
+
+                        item.data.start_date = *start;
+                        item.data.resource_index = Some(index);
+                        item
+                    }),
+            );
+        }
+
+        groups.push(Group {
+            name: group.name.to_owned(),
+            color: group.color.clone(),
+            resources: (summary_index..resources.len()).collect(),
+        });
+    }
+
+    Chart {
+        title: chart.title.to_owned(),
+        marked_date: chart.marked_date,
+        resources,
+        items,
+        groups,
+        sprints: chart.sprints.clone(),
+        non_working: vec![],
+        units: chart.units.clone(),
+    }
+}
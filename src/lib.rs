@@ -1,38 +1,773 @@
 /// Generate a Gantt chart
 use chrono::NaiveDate;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use core::fmt::Arguments;
 use csv::{self, ByteRecord, StringRecord};
 use easy_error::{self, ResultExt};
-use gantt_chart::{ChartData, ItemData};
-use serde::Deserialize;
 use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashSet},
     error::Error,
-    fs::File,
-    io::{self, Read, Write},
-    path::PathBuf,
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufRead, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
 };
 
+mod allocation;
+mod anonymize;
+mod availability;
+mod axis;
+mod bucket;
+mod chart;
+mod check;
+mod clipboard;
+mod color;
+mod columns;
+mod config;
+mod convert;
+mod deps;
+mod diff;
+mod expression;
+mod fetch;
+mod filter;
+mod fixture;
+mod focus;
+mod golden;
+mod health;
+mod hours;
+mod i18n;
+mod ignore;
+mod inputs;
+mod keys;
+mod labels;
+mod level;
 mod log_macros;
+mod milestones;
+mod names;
+mod net;
+mod number;
+mod oauth;
+mod overrides;
+mod paginate;
+mod portfolio;
+mod pto;
+mod record;
+mod rejects;
+mod render;
+mod report;
+mod rollup;
+mod schedule;
+mod spill;
+mod stamp;
+mod start;
+mod suggest;
+mod views;
 
-const JIRA_DAY_IN_SECONDS: f32 = 8.0 * 60.0 * 60.0;
+use allocation::Allocation;
+use availability::Availability;
+use axis::AxisRange;
+use bucket::Bucket;
+use chart::{Chart, Group, Sprint};
+use check::Threshold;
+use color::ColorBy;
+use columns::{ColumnIndex, ColumnMap, ColumnMapping, DateFields, Field, Source, SourceSite};
+use config::{Config, EstimateSource};
+use convert::{ChartBuilder, ConvertOptions};
+use expression::Expression;
+use fetch::{Auth, Capture, Checkpoint, JiraClient, SiteOptions, SprintInfo};
+use filter::Filter;
+use fixture::{FixtureOptions, Spread};
+use hours::{Granularity, WorkingHours};
+use i18n::Translations;
+use ignore::IgnoreList;
+use keys::KeyMap;
+use labels::{LabelLanes, ResourceFrom, TieBreak};
+use level::Level;
+use milestones::Milestones;
+use names::{Alias, NameResolver};
+use net::NetworkOptions;
+use number::NumberFormat;
+use oauth::OAuthOptions;
+use overrides::Overrides;
+use portfolio::{Fetch, PortfolioConfig};
+use pto::Pto;
+use record::GroupBy;
+use rejects::Rejects;
+use report::{ReportFormat, ReportKind, ScheduleFormat, SummaryTable};
+use rollup::Rollup;
+use spill::MemorySize;
+use stamp::Stamp;
+use start::StartFrom;
+use suggest::SuggestFormat;
+use views::View;
+
+pub use record::JiraRecord;
+pub use start::InferStart;
+
+const DEFAULT_CONCURRENCY: usize = 4;
+/// The most issues Jira puts in one CSV export
+const JIRA_EXPORT_LIMIT: usize = 1000;
+
+/// The issues and sprints fetched from a board
+type FetchedBoard = (Vec<JiraRecord>, Vec<SprintInfo>);
+/// Makes a client for a site in the config file, by name
+type SiteClient<'a> = dyn Fn(&str) -> Result<JiraClient, Box<dyn Error>> + 'a;
+
+/// How the chart file is laid out
+#[derive(Clone, Copy)]
+enum Layout {
+    /// Fields in the order they are declared, on one line
+    Default,
+    Pretty,
+    Compact,
+}
+
+/// What the chart is written as
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// The chart data, for gantt_chart or another renderer to draw
+    Json5,
+    /// The chart drawn by gantt_chart
+    Svg,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Compare two chart data files item by item
+    ChartDiff {
+        /// The earlier chart data file
+        #[clap(value_name = "OLD_FILE")]
+        old_file: PathBuf,
+
+        /// The later chart data file
+        #[clap(value_name = "NEW_FILE")]
+        new_file: PathBuf,
+
+        /// Write the changes as JSON instead of text
+        #[clap(long)]
+        json: bool,
+    },
+    /// Write a made up Jira CSV export, for testing and timing without real data
+    GenFixture {
+        /// The CSV file to write, or standard output
+        #[clap(value_name = "OUTPUT_FILE")]
+        output_file: Option<PathBuf>,
+
+        /// The number of issues
+        #[clap(long, value_name = "N", default_value_t = 100)]
+        rows: usize,
+
+        /// The number of people issues are assigned to
+        #[clap(long, value_name = "N", default_value_t = 5, value_parser = clap::value_parser!(u16).range(1..))]
+        assignees: u16,
+
+        /// How issues are shared out between the assignees
+        #[clap(long, value_enum, default_value_t = Spread::Even)]
+        spread: Spread,
+
+        /// The percentage of issues with no assignee
+        #[clap(long, value_name = "PERCENT", default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+        unassigned: u8,
+
+        /// The first date issues are created on, e.g. 2023-01-01
+        #[clap(long, value_name = "DATE", default_value = "2023-01-01")]
+        from: NaiveDate,
+
+        /// The last date issues are created on
+        #[clap(long, value_name = "DATE", default_value = "2023-12-31")]
+        to: NaiveDate,
+
+        /// The percentage of issues with a created date that can't be read
+        #[clap(long, value_name = "PERCENT", default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+        bad_dates: u8,
+
+        /// The percentage of issues with no original estimate
+        #[clap(long, value_name = "PERCENT", default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+        missing_estimates: u8,
+
+        /// The seed for the random numbers, so the same seed gives the same export
+        #[clap(long, value_name = "N", default_value_t = 1)]
+        seed: u64,
+    },
+    /// Suggest which open issues fit into each resource's next sprints, from a chart data file
+    SuggestSprints {
+        /// The chart data file, whose order of items is the order they are done in
+        #[clap(value_name = "CHART_FILE")]
+        chart_file: PathBuf,
+
+        /// The number of sprints to fill
+        #[clap(long, value_name = "N", default_value_t = 3, value_parser = clap::value_parser!(u16).range(1..))]
+        sprints: u16,
+
+        /// The days of work each resource gets through in a sprint, instead of the config file's sprintCapacity or the velocity of their closed issues
+        #[clap(long, value_name = "DAYS")]
+        capacity: Option<f64>,
+
+        /// Write a row for each issue, or a JQL query for each sprint to bulk edit its issues in Jira
+        #[clap(long, value_enum, default_value_t = SuggestFormat::Csv)]
+        format: SuggestFormat,
+    },
+    /// Check the stamp of a chart data file made with --stamp
+    Verify {
+        /// The chart data file
+        #[clap(value_name = "CHART_FILE")]
+        chart_file: PathBuf,
+
+        /// Also check the chart was made from this input file
+        #[clap(long, value_name = "FILE")]
+        input: Option<PathBuf>,
+
+        /// Also check the chart was made with these options, given after --
+        #[clap(last = true, value_name = "OPTIONS")]
+        options: Vec<OsString>,
+    },
+}
 
 #[derive(Parser)]
 #[clap(version, about, long_about = None)]
 struct Cli {
-    /// The JSON5 input file
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// The export to read, or a directory or pattern of exports to merge, e.g. 'exports/*.csv'
     #[clap(value_name = "INPUT_FILE")]
     input_file: Option<PathBuf>,
 
-    /// The SVG output file
+    /// Another export, directory or pattern of exports to merge into the chart, e.g. when a project has more issues than Jira exports at once (may be repeated)
+    #[clap(long, value_name = "INPUT_FILE", conflicts_with = "stamp")]
+    input: Vec<PathBuf>,
+
+    /// The output file, for the chart data or, with --format svg, the drawn chart
     #[clap(value_name = "OUTPUT_FILE")]
     output_file: Option<PathBuf>,
+
+    /// Read the export from the clipboard, e.g. rows copied from a browser or spreadsheet
+    #[clap(long, conflicts_with_all = ["input_file", "input", "portfolio", "board", "filter_id", "jql"])]
+    clipboard: bool,
+
+    /// Copy the chart to the clipboard instead of writing it to a file
+    #[clap(long, conflicts_with_all = ["output_file", "output"])]
+    to_clipboard: bool,
+
+    /// The output file, for when there is no input file, e.g. with --board
+    #[clap(
+        short,
+        long,
+        value_name = "OUTPUT_FILE",
+        conflicts_with = "output_file"
+    )]
+    output: Option<PathBuf>,
+
+    /// The title of the chart, instead of the one from a portfolio, saved filter, the config file or the Project name column
+    #[clap(long, value_name = "TITLE")]
+    title: Option<String>,
+
+    /// Make issues depend on those that block them, from the issue links of the export or API, with the link types that count, e.g. Blocks,Depends (Blocks if none are given)
+    #[clap(
+        long,
+        value_name = "TYPES",
+        num_args = 0..,
+        value_delimiter = ',',
+        default_missing_value = "Blocks"
+    )]
+    link_deps: Option<Vec<String>>,
+
+    /// Cut any item that takes more than this many days down to it, with a warning, so one mistyped estimate doesn't make the time axis unreadable
+    #[clap(long, value_name = "DAYS", value_parser = clap::value_parser!(u32).range(1..))]
+    max_duration: Option<u32>,
+
+    /// Chart closed issues from their Created date to their Resolved date, so a chart of past work shows what actually happened rather than what was estimated
+    #[clap(long)]
+    actuals: bool,
+
+    /// The label of issues that are stretch scope rather than committed, e.g. stretch, for --group-by commitment and the summary report
+    #[clap(long, value_name = "LABEL")]
+    stretch_label: Option<String>,
+
+    /// A TOML or JSON5 file of column names, date formats, statuses, default durations and the title, defaulting to jira_to_gantt.toml or jira_to_gantt.json5 if there is one
+    #[clap(long, value_name = "CONFIG_FILE")]
+    config: Option<PathBuf>,
+
+    /// Where durations come from, in the order they are tried for each issue, e.g. remaining-estimate,original-estimate,story-points,default, instead of the config file's estimateOrder or original-estimate,default
+    #[clap(long, value_enum, value_name = "SOURCES", value_delimiter = ',')]
+    estimate_order: Vec<EstimateSource>,
+
+    /// Timebox spikes, the issues of type Spike or with a spike label, or those of the config file's spikes, to this many days whatever their estimates, instead of the config file's spikeDuration
+    #[clap(long, value_name = "DAYS", value_parser = clap::value_parser!(u32).range(1..))]
+    spike_duration: Option<u32>,
+
+    /// The statuses of closed issues, or patterns where * matches anything, e.g. Done,Resolved,*Closed*, instead of the config file's closedStatuses or Closed
+    #[clap(long, value_name = "STATUSES", value_delimiter = ',')]
+    closed_status: Vec<String>,
+
+    /// Show someone given as an account id, username or email by another name, e.g. alice@corp.com="Alice W.", merging their lanes (may be repeated), as well as the config file's aliases
+    #[clap(long, value_name = "NAME=DISPLAY_NAME")]
+    alias: Vec<Alias>,
+
+    /// The hours in a working day, for turning estimates in seconds into days, e.g. 7.5, instead of the config file's hoursPerDay or Jira's usual 8
+    #[clap(long, value_name = "HOURS")]
+    hours_per_day: Option<f64>,
+
+    /// The chrono format of the dates and date times in the export, e.g. "%d/%m/%Y %H:%M", tried before the config file's and the usual formats (may be repeated)
+    #[clap(long, value_name = "FORMAT")]
+    date_format: Vec<String>,
+
+    /// Show the display names of assignees that are Jira account ids or usernames, looked up with the user API and cached
+    #[clap(long)]
+    resolve_names: bool,
+
+    /// Read a field from a column with another name, e.g. "estimate=Σ Original Estimate", in preference to the config file (may be repeated)
+    #[clap(long, value_name = "FIELD=COLUMN")]
+    map: Vec<ColumnMapping>,
+
+    /// Write the chart data as JSON5, or draw the chart and write it as SVG
+    #[clap(long, value_enum, default_value_t = Format::Json5)]
+    format: Format,
+
+    /// Draw the chart and write it as SVG, the same as --format svg
+    #[clap(long, alias = "render", conflicts_with_all = ["format", "views"])]
+    svg: bool,
+
+    /// Write the chart indented and with its keys sorted, for reviewing in diffs
+    #[clap(long, conflicts_with = "compact")]
+    pretty: bool,
+
+    /// Write the chart on one line with its keys sorted
+    #[clap(long)]
+    compact: bool,
+
+    /// Add a stamp to the chart data with the tool's version, hashes of the options and input file, and the time, to check with `verify`
+    #[clap(long, conflicts_with_all = ["svg", "views"])]
+    stamp: bool,
+
+    /// Run the bundled exports through every kind of output and check each against its golden file, e.g. after an upgrade
+    #[clap(long)]
+    self_test: bool,
+
+    /// With --self-test, write what each kind of output gives to this directory as new golden files instead of checking them
+    #[clap(long, value_name = "DIR", requires = "self_test")]
+    bless: Option<PathBuf>,
+
+    /// Add a tooltip with the issue details to each item
+    #[clap(long)]
+    tooltips: bool,
+
+    /// Add these fields of each issue to its item as they were read, e.g. key,status,labels
+    #[clap(long, value_name = "FIELDS", value_delimiter = ',')]
+    include_fields: Vec<Field>,
+
+    /// What the lanes of the chart are. Give two, e.g. epic,assignee, for sections with lanes inside.
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "assignee",
+        num_args = 1..=2
+    )]
+    group_by: Vec<GroupBy>,
+
+    /// Where the resource of each issue comes from, the assignee or labels with a prefix, e.g. label:team-
+    #[clap(long, value_name = "FROM", default_value = "assignee")]
+    resource_from: ResourceFrom,
+
+    /// Which lane an issue with labels for more than one resource goes in
+    #[clap(long, value_enum, default_value_t = TieBreak::First)]
+    label_tie_break: TieBreak,
+
+    /// What each bar of the chart stands for, e.g. epic for a roadmap with one bar per epic
+    #[clap(long, value_enum, default_value_t = Level::Issue)]
+    level: Level,
+
+    /// Add a summary row for each epic with its issues nested beneath it, for --group-by epic,<lanes>
+    #[clap(long, conflicts_with_all = ["level", "rollup_by"])]
+    epic_summaries: bool,
+
+    /// Roll people's lanes up into a lane for each of their teams from the config file, or for each team at the top with org, with bars for when the team is busy titled with its utilization
+    #[clap(long, value_enum, value_name = "LEVEL", conflicts_with = "level")]
+    rollup_by: Option<Rollup>,
+
+    /// Snap items to quarters, or to planning increments with pi:<start>:<weeks>, for a coarse roadmap
+    #[clap(long, value_name = "BUCKET")]
+    bucket: Option<Bucket>,
+
+    /// Split a chart with more lanes than this into numbered chart files with the same time axis
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    max_resources_per_chart: Option<u16>,
+
+    /// Mark today and say whether each open item is ahead of or behind its dates, from the work logged against its estimate
+    #[clap(long)]
+    health: bool,
+
+    /// Mark a date on the chart with a line, e.g. 2024-06-01, which is also the date --health is worked out for
+    #[clap(long, value_name = "DATE", conflicts_with = "mark_today")]
+    marked_date: Option<NaiveDate>,
+
+    /// Mark today's date on the chart with a line
+    #[clap(long)]
+    mark_today: bool,
+
+    /// Color items, e.g. by age to make the longest open issues stand out
+    #[clap(long, value_enum, value_name = "BY")]
+    color_by: Option<ColorBy>,
+
+    /// Make the time axis cover at least <first>:<last>, so charts made separately line up side by side
+    #[clap(long, value_name = "RANGE")]
+    align_axis: Option<AxisRange>,
+
+    /// Sort lanes by name and items by issue key, so the chart doesn't depend on the export order
+    #[clap(long)]
+    stable_sort: bool,
+
+    /// When work on issues without planned dates starts
+    #[clap(long, value_enum, value_name = "FROM", default_value_t = StartFrom::Created)]
+    start_from: StartFrom,
+
+    /// Put each lane's issues in the order their SLAs run out, from the Time to resolution column of Jira Service Management
+    #[clap(long)]
+    sla_order: bool,
+
+    /// Write a report about the chart (may be repeated)
+    #[clap(long, value_enum, value_name = "KIND")]
+    report: Vec<ReportKind>,
+
+    /// Print the start, end and duration of each issue. Without an output file the chart isn't written.
+    #[clap(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        default_missing_value = "table"
+    )]
+    print_schedule: Option<ScheduleFormat>,
+
+    /// How the tables of reports are written
+    #[clap(long, value_enum, default_value_t = ReportFormat::Text)]
+    report_format: ReportFormat,
+
+    /// The file to write reports to, instead of standard output
+    #[clap(long, value_name = "REPORT_FILE")]
+    report_file: Option<PathBuf>,
+
+    /// A JSON5 file giving the days per week each resource can work, for reports
+    #[clap(long, value_name = "AVAILABILITY_FILE")]
+    availability: Option<PathBuf>,
+
+    /// The percentage of their time resources spend on this work, e.g. 50, or alice=50 for one resource (may be repeated)
+    #[clap(long, value_name = "PERCENT")]
+    allocation: Vec<Allocation>,
+
+    /// Work out durations and starts in days, or in working hours so short issues can share a day
+    #[clap(long, value_enum, default_value_t = Granularity::Days)]
+    granularity: Granularity,
+
+    /// The working hours of each weekday for --granularity hours
+    #[clap(long, value_name = "HH:MM-HH:MM[,...]", default_value = "09:00-17:00")]
+    working_hours: WorkingHours,
+
+    /// A CSV file of before,after[,lag] rows giving issues that can't start until others finish
+    #[clap(long, value_name = "DEPS_FILE")]
+    deps: Option<PathBuf>,
+
+    /// A JSON5 file of changes to make to issues by key, e.g. { "PROJ-12": { resource: "bob", start: "2024-03-04", duration: 3, title: "Spike" } }
+    #[clap(long, value_name = "OVERRIDES_FILE")]
+    overrides: Option<PathBuf>,
+
+    /// Chart only this issue and the issues near it by dependencies, subtasks and parents, e.g. PROJ-123
+    #[clap(long, value_name = "KEY")]
+    focus: Option<String>,
+
+    /// How many dependency or subtask hops from the --focus issue to include
+    #[clap(long, value_name = "HOPS", default_value_t = 1, requires = "focus")]
+    radius: u16,
+
+    /// Time off from an iCalendar file, as RESOURCE=FILE.ics, or a directory of calendars named after their resources (may be repeated)
+    #[clap(long, value_name = "CALENDAR")]
+    pto: Vec<String>,
+
+    /// Holidays that nobody works, from an iCalendar file (may be repeated)
+    #[clap(long, value_name = "CALENDAR_FILE")]
+    holidays: Vec<PathBuf>,
+
+    /// Write an HTML workbook with the chart drawn grouped each of these ways, e.g. assignee,epic,sprint
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "VIEWS",
+        conflicts_with_all = ["to_clipboard", "max_resources_per_chart"]
+    )]
+    views: Vec<View>,
+
+    /// Replace people's names with pseudonyms that are the same on every run with the same secret salt
+    #[clap(
+        long,
+        value_name = "SALT",
+        env = "JIRA_TO_GANTT_ANON_SALT",
+        hide_env_values = true
+    )]
+    anon_salt: Option<String>,
+
+    /// Replace people's names with Resource 1 to Resource N, e.g. to share a chart with a client
+    #[clap(long, conflicts_with = "anon_salt")]
+    anonymize: bool,
+
+    /// Replace issue keys with Issue 1 to Issue N
+    #[clap(long)]
+    anonymize_keys: bool,
+
+    /// Count only weekdays as work, so items start on weekdays and stretch over the weekends they span
+    #[clap(long)]
+    working_days: bool,
+
+    /// Give every item its start date, rather than only the first item of each lane
+    #[clap(long)]
+    explicit_starts: bool,
+
+    /// Add the weekends, holidays and time off to the chart, for renderers to shade
+    #[clap(long)]
+    shade_nonworking: bool,
+
+    /// Add a lane with a milestone at the start and end of each sprint
+    #[clap(long)]
+    sprint_milestones: bool,
+
+    /// Add a lane of milestones for each of these, e.g. fix-version,due-date
+    #[clap(long, value_enum, value_name = "KINDS", value_delimiter = ',')]
+    milestones: Vec<Milestones>,
+
+    /// The number of open issues due on the same day that makes a due-date milestone
+    #[clap(long, value_name = "N", default_value_t = 3, value_parser = clap::value_parser!(u16).range(1..))]
+    due_cluster: u16,
+
+    /// Fail if a condition holds for the chart, e.g. "total-days > 400" (may be repeated)
+    #[clap(long, value_name = "CONDITION")]
+    fail_if: Vec<Threshold>,
+
+    /// Write the last scheduled day of each open issue to a CSV file of issue keys and due dates, for Jira's CSV import to update the issues from
+    #[clap(long, value_name = "CSV_FILE")]
+    due_dates: Option<PathBuf>,
+
+    /// Fail once the chart is written if there were more than this many warnings, e.g. 0 for a nightly job that should catch every data problem
+    #[clap(long, value_name = "N")]
+    max_warnings: Option<usize>,
+
+    /// Write rows that are skipped or can't be read to a CSV file, with the reason appended, instead of stopping
+    #[clap(long, value_name = "REJECTS_FILE")]
+    rejects: Option<PathBuf>,
+
+    /// Custom fields with the start and end dates of issues, as export columns or Jira field ids, e.g. customfield_10015,customfield_10016
+    #[clap(long, value_name = "START_FIELD,END_FIELD")]
+    date_fields: Option<DateFields>,
+
+    /// A file of issue keys or patterns such as OPS-*, one per line, to always leave out of the chart
+    #[clap(long, value_name = "IGNORE_FILE")]
+    ignore: Option<PathBuf>,
+
+    /// Only chart issues in these statuses, or patterns where * matches anything, e.g. "In Progress,To Do"
+    #[clap(long, value_name = "STATUSES", value_delimiter = ',')]
+    include_status: Vec<String>,
+
+    /// Leave out issues in these statuses, or patterns where * matches anything, e.g. Closed,Cancelled
+    #[clap(long, value_name = "STATUSES", value_delimiter = ',')]
+    exclude_status: Vec<String>,
+
+    /// Only chart issues assigned to these people, or patterns where * matches anything, e.g. "Ana Lima,Bo*"
+    #[clap(long, value_name = "NAMES", value_delimiter = ',')]
+    assignee: Vec<String>,
+
+    /// Leave out issues assigned to these people, or patterns where * matches anything
+    #[clap(long, value_name = "NAMES", value_delimiter = ',')]
+    exclude_assignee: Vec<String>,
+
+    /// Only chart unassigned issues, as well as those of --assignee if it is given
+    #[clap(long)]
+    unassigned: bool,
+
+    /// Only chart issues of these types, or patterns where * matches anything, e.g. Story,Bug
+    #[clap(long, value_name = "TYPES", value_delimiter = ',')]
+    include_type: Vec<String>,
+
+    /// Leave out issues of these types, or patterns where * matches anything, e.g. Sub-task,Epic
+    #[clap(long, value_name = "TYPES", value_delimiter = ',')]
+    exclude_type: Vec<String>,
+
+    /// Only chart issues with one of these labels, or patterns where * matches anything, e.g. backend
+    #[clap(long, value_name = "LABELS", value_delimiter = ',')]
+    label: Vec<String>,
+
+    /// Leave out issues with any of these labels, or patterns where * matches anything, e.g. wontfix
+    #[clap(long, value_name = "LABELS", value_delimiter = ',')]
+    exclude_label: Vec<String>,
+
+    /// Only chart issues in one of these components, or patterns where * matches anything, e.g. API
+    #[clap(long, value_name = "COMPONENTS", value_delimiter = ',')]
+    component: Vec<String>,
+
+    /// Leave out issues in any of these components, or patterns where * matches anything
+    #[clap(long, value_name = "COMPONENTS", value_delimiter = ',')]
+    exclude_component: Vec<String>,
+
+    /// Only chart issues that match an expression of their fields, e.g. 'status != "Closed" && assignee == "alice"'
+    #[clap(long, value_name = "EXPRESSION")]
+    filter: Option<Expression>,
+
+    /// Put assignees whose names differ only in capitalization in one lane, named as they first appear
+    #[clap(long)]
+    ignore_name_case: bool,
+
+    /// Keep the issues read from a large export to roughly this much memory, e.g. 256M, by writing them to temporary files until the chart is put together
+    #[clap(long, value_name = "SIZE", conflicts_with_all = ["stable_sort", "sla_order"])]
+    max_memory: Option<MemorySize>,
+
+    /// Convert only the first N issues of each export, for quick iteration on large exports
+    #[clap(long, value_name = "N")]
+    head: Option<usize>,
+
+    /// How numbers are written in the export, e.g. comma for 28.800 or 1,5
+    #[clap(long, value_enum, default_value_t = NumberFormat::Auto)]
+    number_format: NumberFormat,
+
+    /// The language of generated labels, e.g. de, es, fr or pt
+    #[clap(long, value_name = "LANG", default_value = i18n::DEFAULT_LANGUAGE)]
+    lang: String,
+
+    /// A JSON5 file of translations for generated labels, overriding those for --lang
+    #[clap(long, value_name = "TRANSLATION_FILE")]
+    translations: Option<PathBuf>,
+
+    /// The tool that produced the export, detected from the header row if not given, e.g. plan, or jira:SITE to fetch from a site in the config file
+    #[clap(long, value_name = "SOURCE[:SITE]")]
+    source: Option<SourceSite>,
+
+    /// A JSON5 portfolio file listing several sources to combine into one chart
+    #[clap(long, value_name = "PORTFOLIO_FILE", conflicts_with_all = ["input_file", "input"])]
+    portfolio: Option<PathBuf>,
+
+    /// Fetch the active sprints and backlog of a Jira board instead of reading an export
+    #[clap(long, value_name = "BOARD_ID", conflicts_with_all = ["input_file", "input", "portfolio"])]
+    board: Option<u64>,
+
+    /// Fetch the issues of a saved Jira filter, in the filter's order, instead of reading an export
+    #[clap(long, value_name = "FILTER_ID", conflicts_with_all = ["input_file", "input", "portfolio", "board"])]
+    filter_id: Option<u64>,
+
+    /// Fetch the issues a JQL query finds, e.g. "project = PROJ AND sprint in openSprints()", instead of reading an export
+    #[clap(long, value_name = "JQL", conflicts_with_all = ["input_file", "input", "portfolio", "board", "filter_id"])]
+    jql: Option<String>,
+
+    /// The base URL of the Jira site, e.g. https://example.atlassian.net
+    #[clap(long, value_name = "URL", env = "JIRA_URL")]
+    jira_url: Option<String>,
+
+    /// The Jira user to authenticate as
+    #[clap(long, value_name = "USER", env = "JIRA_USER")]
+    jira_user: Option<String>,
+
+    /// The Jira API token to authenticate with
+    #[clap(
+        long,
+        value_name = "TOKEN",
+        env = "JIRA_API_TOKEN",
+        hide_env_values = true
+    )]
+    jira_token: Option<String>,
+
+    /// The Jira Server/Data Center password to authenticate with
+    #[clap(
+        long,
+        value_name = "PASSWORD",
+        env = "JIRA_PASSWORD",
+        hide_env_values = true,
+        conflicts_with = "jira_token"
+    )]
+    jira_password: Option<String>,
+
+    /// A Jira Server/Data Center personal access token to authenticate with
+    #[clap(
+        long,
+        value_name = "TOKEN",
+        env = "JIRA_PAT",
+        hide_env_values = true,
+        conflicts_with_all = ["jira_token", "jira_password"]
+    )]
+    jira_pat: Option<String>,
+
+    /// Authenticate with Jira Cloud using OAuth 2.0 instead of an API token
+    #[clap(
+        long,
+        requires = "oauth_client_id",
+        conflicts_with_all = ["jira_token", "jira_password", "jira_pat"]
+    )]
+    oauth: bool,
+
+    /// The client ID of the OAuth 2.0 app
+    #[clap(long, value_name = "ID", env = "JIRA_OAUTH_CLIENT_ID")]
+    oauth_client_id: Option<String>,
+
+    /// The client secret of the OAuth 2.0 app
+    #[clap(
+        long,
+        value_name = "SECRET",
+        env = "JIRA_OAUTH_CLIENT_SECRET",
+        hide_env_values = true
+    )]
+    oauth_client_secret: Option<String>,
+
+    /// The local port to listen on for the OAuth 2.0 redirect
+    #[clap(long, value_name = "PORT", default_value_t = 8080)]
+    oauth_port: u16,
+
+    /// A PEM file of extra certificate authorities to trust, e.g. a corporate CA
+    #[clap(long, value_name = "PEM_FILE")]
+    ca_bundle: Option<PathBuf>,
+
+    /// Don't verify TLS certificates, e.g. for a self-signed certificate
+    #[clap(long, conflicts_with = "ca_bundle")]
+    insecure: bool,
+
+    /// The proxy for network requests, overriding HTTPS_PROXY and HTTP_PROXY
+    #[clap(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Record fetched pages in a checkpoint file so an interrupted fetch can be resumed
+    #[clap(long, value_name = "CHECKPOINT_FILE")]
+    checkpoint: Option<PathBuf>,
+
+    /// Resume an interrupted fetch from the checkpoint file
+    #[clap(long, requires = "checkpoint")]
+    resume: bool,
+
+    /// Save every Jira API response to a directory
+    #[clap(long, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Answer Jira API requests from responses saved with --record instead of calling Jira
+    #[clap(long, value_name = "DIR")]
+    replay: Option<PathBuf>,
+
+    /// How many portfolio sources to fetch from Jira at the same time [default: 4]
+    #[clap(long, value_name = "N")]
+    concurrency: Option<usize>,
+
+    /// Fetch at most this many issues from each board, filter or search, with a warning saying how many were left out
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(usize))]
+    max_issues: Option<usize>,
 }
 
 impl Cli {
+    fn output_path(&self) -> Option<&PathBuf> {
+        self.output.as_ref().or(self.output_file.as_ref())
+    }
+
     fn get_output(&self) -> Result<Box<dyn Write>, Box<dyn Error>> {
-        match self.output_file {
-            Some(ref path) => File::create(path)
+        match self.output_path() {
+            Some(path) => File::create(path)
                 .context(format!(
                     "Unable to create file '{}'",
                     path.to_string_lossy()
@@ -43,13 +778,191 @@ impl Cli {
         }
     }
 
-    fn get_input(&self) -> Result<Box<dyn Read>, Box<dyn Error>> {
-        match self.input_file {
-            Some(ref path) => File::open(path)
-                .context(format!("Unable to open file '{}'", path.to_string_lossy()))
-                .map(|f| Box::new(f) as Box<dyn Read>)
-                .map_err(|e| Box::new(e) as Box<dyn Error>),
-            None => Ok(Box::new(io::stdin())),
+    fn has_output_file(&self) -> bool {
+        self.output.is_some() || self.output_file.is_some() || self.to_clipboard
+    }
+
+    fn get_format(&self) -> Format {
+        if self.svg {
+            Format::Svg
+        } else {
+            self.format
+        }
+    }
+
+    fn get_layout(&self) -> Layout {
+        if self.pretty {
+            Layout::Pretty
+        } else if self.compact {
+            Layout::Compact
+        } else {
+            Layout::Default
+        }
+    }
+
+    fn get_auth(&self) -> Result<Auth, Box<dyn Error>> {
+        if self.oauth {
+            return Ok(Auth::OAuth2(OAuthOptions {
+                client_id: self.oauth_client_id.to_owned().unwrap_or_default(),
+                client_secret: self.oauth_client_secret.to_owned().unwrap_or_default(),
+                port: self.oauth_port,
+            }));
+        }
+
+        if let Some(ref token) = self.jira_pat {
+            return Ok(Auth::PersonalAccessToken(token.to_owned()));
+        }
+
+        match (
+            &self.jira_user,
+            self.jira_token.as_ref().or(self.jira_password.as_ref()),
+        ) {
+            (Some(user), Some(secret)) => Ok(Auth::Basic {
+                user: user.to_owned(),
+                secret: secret.to_owned(),
+            }),
+            (None, None) => Ok(Auth::Anonymous),
+            _ => Err(From::from(
+                "Both a Jira user and an API token or password are required to authenticate",
+            )),
+        }
+    }
+
+    fn get_network(&self) -> NetworkOptions {
+        NetworkOptions {
+            ca_bundle: self.ca_bundle.to_owned(),
+            insecure: self.insecure,
+            proxy: self.proxy.to_owned(),
+        }
+    }
+
+    fn get_capture(&self) -> Option<Capture> {
+        match (&self.record, &self.replay) {
+            (Some(dir), _) => Some(Capture::Record(dir.to_owned())),
+            (_, Some(dir)) => Some(Capture::Replay(dir.to_owned())),
+            _ => None,
+        }
+    }
+
+    fn get_client(
+        &self,
+        config: &Config,
+        prompt: &dyn Fn(&str),
+    ) -> Result<Option<JiraClient>, Box<dyn Error>> {
+        // Replaying doesn't talk to Jira, so the URL isn't needed
+        let url = match self.replay {
+            Some(_) => Some(self.jira_url.to_owned().unwrap_or_default()),
+            None => self.jira_url.to_owned(),
+        };
+
+        match url {
+            Some(url) => Ok(Some(JiraClient::new(
+                SiteOptions {
+                    url,
+                    auth: self.get_auth()?,
+                    network: self.get_network(),
+                    checkpoint: match self.checkpoint {
+                        Some(ref path) => Some(Checkpoint::open(path, self.resume)?),
+                        None => None,
+                    },
+                    capture: self.get_capture(),
+                    date_fields: self.date_fields.clone(),
+                    columns: config.columns.clone(),
+                    max_issues: self.max_issues,
+                },
+                prompt,
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    /// A client for a site in the config file, with its own URL and
+    /// credentials. Its responses are recorded in a directory and its
+    /// progress is checkpointed in a file named after it.
+    fn get_site_client(
+        &self,
+        config: &Config,
+        name: &str,
+        prompt: &dyn Fn(&str),
+    ) -> Result<JiraClient, Box<dyn Error>> {
+        let site = config.site(name)?;
+
+        JiraClient::new(
+            SiteOptions {
+                url: site.url.to_owned(),
+                auth: site.auth(name)?,
+                network: self.get_network(),
+                checkpoint: match self.checkpoint {
+                    Some(ref path) => {
+                        let mut file_name = path.file_name().unwrap_or_default().to_owned();
+
+                        file_name.push(format!(".{}", name));
+                        Some(Checkpoint::open(
+                            &path.with_file_name(file_name),
+                            self.resume,
+                        )?)
+                    }
+                    None => None,
+                },
+                capture: self.get_capture().map(|capture| capture.for_site(name)),
+                date_fields: self.date_fields.clone(),
+                columns: config.columns.clone(),
+                max_issues: self.max_issues,
+            },
+            prompt,
+        )
+    }
+
+    /// The exports to read, from the input file and those of `--input`, or
+    /// else the clipboard or standard input
+    fn get_inputs(&self) -> Result<Vec<Input>, Box<dyn Error>> {
+        if self.clipboard {
+            return Ok(vec![Input::new(io::Cursor::new(clipboard::read()?))]);
+        }
+
+        let paths: Vec<&PathBuf> = self.input_file.iter().chain(self.input.iter()).collect();
+
+        if paths.is_empty() {
+            return Ok(vec![Input::new(io::stdin())]);
+        }
+
+        let mut inputs = vec![];
+
+        for path in paths {
+            let open = |path: &Path| {
+                File::open(path)
+                    .context(format!("Unable to open file '{}'", path.to_string_lossy()))
+            };
+
+            match inputs::expand(path)? {
+                Some(files) => {
+                    for file in files {
+                        inputs.push(Input {
+                            reader: Box::new(open(&file)?),
+                            found: Some(file),
+                        });
+                    }
+                }
+                None => inputs.push(Input::new(open(path)?)),
+            }
+        }
+
+        Ok(inputs)
+    }
+}
+
+/// An export to read. Those found in a directory or by a pattern have their
+/// path, and are skipped with a warning if they can't be read.
+struct Input {
+    reader: Box<dyn Read>,
+    found: Option<PathBuf>,
+}
+
+impl Input {
+    fn new(reader: impl Read + 'static) -> Input {
+        Input {
+            reader: Box::new(reader),
+            found: None,
         }
     }
 }
@@ -58,120 +971,1428 @@ pub trait JiraToGanttLog {
     fn output(self: &Self, args: Arguments);
     fn warning(self: &Self, args: Arguments);
     fn error(self: &Self, args: Arguments);
+    /// A decision the tool made that is worth knowing about but isn't a
+    /// problem, so it doesn't count towards --max-warnings
+    fn note(self: &Self, args: Arguments) {
+        self.warning(args);
+    }
 }
 
-pub struct JiraToGanttTool<'a> {
+/// Drops every message, for reading an export only to check that it can be
+struct QuietLog;
+
+impl JiraToGanttLog for QuietLog {
+    fn output(&self, _args: Arguments) {}
+    fn warning(&self, _args: Arguments) {}
+    fn error(&self, _args: Arguments) {}
+    fn note(&self, _args: Arguments) {}
+}
+
+/// Passes messages on to another log, counting the warnings
+struct CountingLog<'a> {
     log: &'a dyn JiraToGanttLog,
+    warnings: Cell<usize>,
+}
+
+impl JiraToGanttLog for CountingLog<'_> {
+    fn output(&self, args: Arguments) {
+        self.log.output(args);
+    }
+    fn warning(&self, args: Arguments) {
+        self.warnings.set(self.warnings.get() + 1);
+        self.log.warning(args);
+    }
+    fn error(&self, args: Arguments) {
+        self.log.error(args);
+    }
+    fn note(&self, args: Arguments) {
+        self.log.note(args);
+    }
 }
 
-#[derive(Deserialize, Debug)]
-struct JiraRecord {
-    #[serde(rename = "Issue key")]
-    key: String,
-    #[serde(rename = "Status")]
-    #[allow(dead_code)]
-    status: String,
-    #[serde(rename = "Assignee")]
-    assignee: String,
-    #[serde(rename = "Original Estimate", default)]
-    original_estimate: Option<u32>,
-    #[serde(rename = "Created")]
-    created: String,
+pub struct JiraToGanttTool<'a> {
+    log: &'a dyn JiraToGanttLog,
+    /// How to infer when work starts, instead of --start-from
+    start: Option<&'a dyn InferStart>,
 }
 
 impl<'a> JiraToGanttTool<'a> {
-    pub fn new(log: &'a dyn JiraToGanttLog) -> JiraToGanttTool {
-        JiraToGanttTool { log }
+    pub fn new(log: &'a dyn JiraToGanttLog) -> JiraToGanttTool<'a> {
+        JiraToGanttTool { log, start: None }
+    }
+
+    /// Infer when work on issues without planned dates starts in a way of
+    /// one's own, instead of one of those of --start-from
+    pub fn with_start(self, start: &'a dyn InferStart) -> JiraToGanttTool<'a> {
+        JiraToGanttTool {
+            start: Some(start),
+            ..self
+        }
     }
 
     pub fn run(
         self: &mut Self,
-        args: impl IntoIterator<Item = std::ffi::OsString>,
+        args: impl IntoIterator<Item = OsString>,
     ) -> Result<(), Box<dyn Error>> {
-        let cli = match Cli::try_parse_from(args) {
+        let args: Vec<OsString> = args.into_iter().collect();
+        let cli = match Cli::try_parse_from(&args) {
             Ok(m) => m,
             Err(err) => {
                 output!(self.log, "{}", err.to_string());
                 return Ok(());
             }
         };
+        let max_warnings = cli.max_warnings;
+        let log = CountingLog {
+            log: self.log,
+            warnings: Cell::new(0),
+        };
 
-        let chart_data = self.read_jira_csv_file(cli.get_input()?)?;
+        JiraToGanttTool {
+            log: &log,
+            start: self.start,
+        }
+        .run_cli(cli, &args[1..])?;
 
-        Self::write_chart_data_file(cli.get_output()?, &chart_data)?;
+        let warnings = log.warnings.get();
 
-        Ok(())
+        // A single warning is easy enough to see without a count
+        if warnings > 1 {
+            warning!(self.log, "{} warnings in all", warnings);
+        }
+
+        match max_warnings {
+            Some(max) if warnings > max => Err(From::from(format!(
+                "{} warning(s), more than the --max-warnings of {}",
+                warnings, max
+            ))),
+            _ => Ok(()),
+        }
     }
 
-    fn write_chart_data_file(
-        mut writer: Box<dyn Write>,
-        chart_data: &ChartData,
-    ) -> Result<(), Box<dyn Error>> {
-        write!(writer, "{}", json5::to_string(&chart_data)?)?;
+    fn run_cli(&mut self, cli: Cli, args: &[OsString]) -> Result<(), Box<dyn Error>> {
+        if let Some(Command::ChartDiff {
+            ref old_file,
+            ref new_file,
+            json,
+        }) = cli.command
+        {
+            return self.chart_diff(old_file, new_file, json);
+        }
 
-        Ok(())
-    }
+        if let Some(Command::Verify {
+            ref chart_file,
+            ref input,
+            ref options,
+        }) = cli.command
+        {
+            return self.verify(chart_file, input.as_deref(), options);
+        }
 
-    fn read_jira_csv_file(&self, reader: Box<dyn Read>) -> Result<ChartData, Box<dyn Error>> {
-        let mut reader = csv::Reader::from_reader(reader);
-        let mut resources: Vec<String> = vec![];
-        let mut resource_items: Vec<Vec<ItemData>> = vec![];
-        let headers = reader.headers().cloned().ok();
+        if cli.self_test {
+            return self.self_test(cli.bless.as_deref());
+        }
 
-        for byte_record in reader.byte_records() {
-            let byte_record: ByteRecord = byte_record?;
-            let string_record: StringRecord = StringRecord::from_byte_record_lossy(byte_record);
-            let record: JiraRecord = string_record.deserialize(headers.as_ref())?;
+        if let Some(Command::SuggestSprints {
+            ref chart_file,
+            sprints,
+            capacity,
+            format,
+        }) = cli.command
+        {
+            return self.suggest_sprints(
+                chart_file,
+                &Config::load(cli.config.as_deref())?,
+                usize::from(sprints),
+                capacity,
+                format,
+            );
+        }
 
-            if record.key.is_empty() {
-                continue;
-            }
+        if let Some(Command::GenFixture {
+            ref output_file,
+            rows,
+            assignees,
+            spread,
+            unassigned,
+            from,
+            to,
+            bad_dates,
+            missing_estimates,
+            seed,
+        }) = cli.command
+        {
+            let options = FixtureOptions {
+                rows,
+                assignees: usize::from(assignees),
+                spread,
+                unassigned,
+                from,
+                to,
+                bad_dates,
+                missing_estimates,
+                seed,
+            };
 
-            let mut start_date = Some(NaiveDate::parse_from_str(
-                &record.created,
-                "%-d/%b/%y %I:%M %p",
-            )?);
-            let resource_index;
+            return match output_file {
+                Some(path) => fixture::generate(
+                    &mut File::create(path).context(format!(
+                        "Unable to create file '{}'",
+                        path.to_string_lossy()
+                    ))?,
+                    &options,
+                ),
+                None => fixture::generate(&mut io::stdout(), &options),
+            };
+        }
 
-            // Update resources and get the index into the array
-            if let Some(index) = resources.iter().position(|s| *s == record.assignee) {
-                resource_index = index;
-                start_date = None;
-            } else {
-                resource_index = resources.len();
-                resources.push(record.assignee.to_owned());
-                resource_items.push(vec![]);
-            }
+        if cli.group_by.len() > 2 {
+            return Err(From::from(
+                "--group-by takes at most two levels, e.g. epic,assignee",
+            ));
+        }
 
-            let mut duration: Option<i64> = None;
+        if cli.epic_summaries && !matches!(cli.group_by[..], [GroupBy::Epic, _]) {
+            return Err(From::from(
+                "--epic-summaries needs epics with lanes beneath them, e.g. --group-by epic,assignee",
+            ));
+        }
 
-            if let Some(seconds) = record.original_estimate {
-                duration = Some((((seconds + 1) as f32) / JIRA_DAY_IN_SECONDS).ceil() as i64);
-            }
+        if cli.stamp && cli.get_format() == Format::Svg {
+            return Err(From::from("--stamp is only for chart data, not SVG"));
+        }
 
-            resource_items[resource_index].push(ItemData {
-                title: record.key.to_owned(),
-                start_date,
-                duration,
-                resource_index: Some(resource_index),
-                open: Some(record.status != "Closed"),
-            });
+        if cli.group_by.contains(&GroupBy::Commitment) && cli.stretch_label.is_none() {
+            return Err(From::from(
+                "--group-by commitment needs the label of stretch issues, use --stretch-label",
+            ));
         }
 
-        // Turn empty resource into 'unassigned'
-        if let Some(index) = resources.iter().position(|s| s.is_empty()) {
-            resources[index] = "unassigned".to_owned();
+        // Without an output file --print-schedule is a dry run, so the
+        // chart isn't written
+        let write_chart = cli.has_output_file() || cli.print_schedule.is_none();
+
+        if !cli.report.is_empty()
+            && cli.report_file.is_none()
+            && !cli.has_output_file()
+            && write_chart
+        {
+            return Err(From::from(
+                "Reports and the chart can't both go to standard output, use -o or --report-file",
+            ));
         }
 
-        Ok(ChartData {
-            title: "".to_owned(),
-            resources,
-            marked_date: None,
-            items: resource_items
-                .iter()
-                .flatten()
-                .cloned()
-                .collect::<Vec<ItemData>>(),
-        })
+        // The summary's epics and weekly capacity are tables of their own
+        if (cli.report.len() > 1 || cli.report.contains(&ReportKind::Summary))
+            && cli.report_format == ReportFormat::Csv
+            && cli.report_file.is_none()
+        {
+            return Err(From::from(
+                "--report-format csv writes each report and table to a file of its own, use --report-file",
+            ));
+        }
+
+        if !cli.views.is_empty() && cli.get_format() == Format::Svg {
+            return Err(From::from(
+                "--views writes an HTML workbook of drawn charts, so it can't be used with --format svg",
+            ));
+        }
+
+        if !cli.views.is_empty() && cli.output_path().is_none() {
+            return Err(From::from(
+                "--views writes an HTML workbook, so it needs an output file, use -o",
+            ));
+        }
+
+        if cli.max_resources_per_chart.is_some() && write_chart && cli.output_path().is_none() {
+            return Err(From::from(
+                "--max-resources-per-chart writes a file for each page, so it needs an output file, use -o",
+            ));
+        }
+
+        let text = Translations::load(&cli.lang, cli.translations.as_deref())?;
+        let availability = match cli.availability {
+            Some(ref path) => Availability::load(path)?,
+            None => Availability::default(),
+        };
+        let mut dependencies = match cli.deps {
+            Some(ref path) => deps::load(path)?,
+            None => vec![],
+        };
+        let rejects = match cli.rejects {
+            Some(ref path) => Some(Rejects::create(path)?),
+            None => None,
+        };
+        let ignore = match cli.ignore {
+            Some(ref path) => Some(IgnoreList::load(path)?),
+            None => None,
+        };
+        let filter = Filter {
+            include_statuses: cli.include_status.clone(),
+            exclude_statuses: cli.exclude_status.clone(),
+            include_assignees: cli.assignee.clone(),
+            exclude_assignees: cli.exclude_assignee.clone(),
+            unassigned: cli.unassigned,
+            include_types: cli.include_type.clone(),
+            exclude_types: cli.exclude_type.clone(),
+            include_labels: cli.label.clone(),
+            exclude_labels: cli.exclude_label.clone(),
+            include_components: cli.component.clone(),
+            exclude_components: cli.exclude_component.clone(),
+            expression: cli.filter.clone(),
+        };
+        let mut config = Config::load(cli.config.as_deref())?;
+
+        config.map_columns(&cli.map);
+
+        if !cli.estimate_order.is_empty() {
+            config.estimate_order = cli.estimate_order.clone();
+        }
+
+        if cli.spike_duration.is_some() {
+            config.spike_duration = cli.spike_duration;
+        }
+
+        if !cli.closed_status.is_empty() {
+            config.closed_statuses = cli.closed_status.clone();
+        }
+
+        for alias in cli.alias.iter() {
+            config
+                .aliases
+                .retain(|name, _| name.to_lowercase() != alias.name.to_lowercase());
+            config
+                .aliases
+                .insert(alias.name.to_owned(), alias.display_name.to_owned());
+        }
+
+        if let Some(hours) = cli.hours_per_day {
+            config::check_hours_per_day(hours)
+                .map_err(|err| format!("Invalid --hours-per-day: {}", err))?;
+            config.hours_per_day = Some(hours);
+        }
+
+        config
+            .date_formats
+            .splice(0..0, cli.date_format.iter().cloned());
+
+        let pto = Pto::load(
+            &cli.pto,
+            &cli.holidays,
+            &config,
+            cli.working_days || config.working_days,
+        )?;
+
+        let warnings = RefCell::new(vec![]);
+        let label_lanes = match cli.resource_from {
+            ResourceFrom::Label(ref prefix) => Some(LabelLanes::new(prefix, cli.label_tie_break)),
+            ResourceFrom::Assignee => None,
+        };
+        let prompt = |url: &str| {
+            note!(
+                self.log,
+                "Open this URL in a browser to authorize access to Jira:\n{}",
+                url
+            )
+        };
+        let site_client = |site: &str| cli.get_site_client(&config, site, &prompt);
+        let client = match cli
+            .source
+            .as_ref()
+            .and_then(|source| source.site.as_deref())
+        {
+            Some(site) => Some(site_client(site)?),
+            None => cli.get_client(&config, &prompt)?,
+        };
+        let names = if cli.resolve_names {
+            Some(NameResolver::new(Self::require_client(client.as_ref())?))
+        } else {
+            None
+        };
+        let options = ConvertOptions {
+            source: cli.source.as_ref().map(|source| source.source),
+            tooltips: cli.tooltips,
+            numbers: cli.number_format,
+            head: cli.head,
+            stable_sort: cli.stable_sort,
+            sla_order: cli.sla_order,
+            group_by: &cli.group_by,
+            text: &text,
+            allocations: &cli.allocation,
+            granularity: cli.granularity,
+            working_hours: &cli.working_hours,
+            pto: &pto,
+            rejects: rejects.as_ref(),
+            label_lanes: label_lanes.as_ref(),
+            config: &config,
+            date_fields: cli.date_fields.as_ref(),
+            link_types: cli.link_deps.as_deref().unwrap_or_default(),
+            include_fields: &cli.include_fields,
+            max_duration: cli.max_duration.map(i64::from),
+            actuals: cli.actuals,
+            stretch_label: cli.stretch_label.as_deref(),
+            names: names.as_ref(),
+            ignore: ignore.as_ref(),
+            filter: &filter,
+            ignore_name_case: cli.ignore_name_case,
+            max_memory: cli.max_memory,
+            warnings: &warnings,
+            start: self.start.unwrap_or(&cli.start_from),
+            today: chrono::Local::now().date_naive(),
+        };
+        let mut chart = if let Some(ref path) = cli.portfolio {
+            self.read_portfolio(
+                path,
+                client.as_ref(),
+                &site_client,
+                options,
+                cli.concurrency,
+            )?
+        } else if let Some(board_id) = cli.board {
+            self.fetch_board_chart(Self::require_client(client.as_ref())?, board_id, options)?
+        } else if let Some(filter_id) = cli.filter_id {
+            let mut builder = ChartBuilder::new(options);
+            let name =
+                Self::require_client(client.as_ref())?.fetch_filter(filter_id, &mut |record| {
+                    builder.add(&record);
+                })?;
+            let mut chart = builder.finish()?;
+
+            chart.title = name;
+            chart
+        } else if let Some(ref jql) = cli.jql {
+            let mut builder = ChartBuilder::new(options);
+
+            Self::require_client(client.as_ref())?.fetch_jql(jql, &mut |record| {
+                builder.add(&record);
+            })?;
+            builder.finish()?
+        } else {
+            self.read_chart(cli.get_inputs()?, options)?
+        };
+
+        if let Some(ref label_lanes) = label_lanes {
+            label_lanes
+                .take_conflicts()
+                .iter()
+                .for_each(|w| warning!(self.log, "{}", w));
+        }
+
+        warnings
+            .take()
+            .iter()
+            .for_each(|w| warning!(self.log, "{}", w));
+
+        if let Some(ref client) = client {
+            client
+                .take_warnings()
+                .iter()
+                .for_each(|w| warning!(self.log, "{}", w));
+        }
+
+        if let Some(ref names) = names {
+            names
+                .take_warnings()
+                .iter()
+                .for_each(|w| warning!(self.log, "{}", w));
+            names.save()?;
+        }
+
+        // A portfolio or a saved filter names its own chart, and otherwise
+        // the projects of its issues do unless the config file names it
+        let named = (cli.portfolio.is_some() || cli.filter_id.is_some()) && !chart.title.is_empty();
+
+        if let Some(title) = cli
+            .title
+            .as_ref()
+            .or(config.title.as_ref().filter(|_| !named))
+        {
+            chart.title = title.to_owned();
+        }
+
+        if let Some(ref path) = cli.overrides {
+            Overrides::load(path)?
+                .apply(&mut chart)
+                .iter()
+                .for_each(|w| warning!(self.log, "{}", w));
+        }
+
+        dependencies.extend(deps::from_chart(&chart));
+        Self::schedule(&mut chart, &dependencies, &pto, cli.granularity)?
+            .iter()
+            .for_each(|w| warning!(self.log, "{}", w));
+
+        if let Some(ref key) = cli.focus {
+            focus::apply(&mut chart, &dependencies, key, cli.radius)?;
+        }
+
+        chart.units = Some(pto.units(
+            cli.granularity,
+            &cli.working_hours,
+            config.day_seconds() / 3600.0,
+        ));
+
+        if cli.explicit_starts {
+            schedule::fill_starts(&mut chart);
+        }
+
+        if cli.level == Level::Epic {
+            chart = level::epics(&chart, &text, config.day_seconds());
+        }
+
+        if cli.epic_summaries {
+            chart = level::with_summaries(&chart, &text, config.day_seconds());
+        }
+
+        if let Some(rollup) = cli.rollup_by {
+            if config.teams.is_empty() {
+                return Err(From::from(
+                    "--rollup-by needs the teams people are in, from the config file",
+                ));
+            }
+
+            chart = rollup::apply(&chart, &config, rollup, &text);
+        }
+
+        if let Some(ref salt) = cli.anon_salt {
+            anonymize::apply(&mut chart, salt);
+        } else if cli.anonymize {
+            anonymize::number(&mut chart);
+        }
+
+        if cli.anonymize_keys {
+            anonymize::number_keys(&mut chart);
+        }
+
+        let today = chrono::Local::now().date_naive();
+
+        if cli.mark_today {
+            chart.marked_date = Some(today);
+        } else if cli.marked_date.is_some() {
+            chart.marked_date = cli.marked_date;
+        }
+
+        if cli.health || cli.report.contains(&ReportKind::Health) {
+            let marked = *chart.marked_date.get_or_insert(today);
+
+            health::apply(&mut chart, marked);
+        }
+
+        if let Some(by) = cli.color_by {
+            color::apply(&mut chart, by, today);
+        }
+
+        if let Some(bucket) = cli.bucket {
+            bucket.apply(&mut chart);
+        }
+
+        // The milestones that widen the axis or mark sprints and releases and
+        // the shading are only for drawing, so they aren't in the schedule or
+        // reports
+        let drawn = (cli.align_axis.is_some()
+            || cli.shade_nonworking
+            || cli.sprint_milestones
+            || !cli.milestones.is_empty())
+        .then(|| {
+            let mut drawn = chart.clone();
+
+            for kind in cli.milestones.iter() {
+                match kind {
+                    Milestones::FixVersion => milestones::fix_versions(&mut drawn, &text),
+                    Milestones::DueDate => {
+                        milestones::due_dates(&mut drawn, usize::from(cli.due_cluster), &text)
+                    }
+                }
+            }
+
+            if cli.sprint_milestones {
+                axis::sprint_boundaries(&mut drawn, &text);
+            }
+
+            if let Some(range) = cli.align_axis {
+                axis::align(&mut drawn, range);
+            }
+
+            if cli.shade_nonworking {
+                pto.shade(&mut drawn);
+            }
+
+            drawn
+        });
+        let chart_file = drawn.as_ref().unwrap_or(&chart);
+        let stamp = if cli.stamp {
+            let input = match cli.input_file {
+                Some(ref path) if inputs::is_many(path) => {
+                    return Err(From::from(
+                        "--stamp needs one input file, not a directory or pattern",
+                    ))
+                }
+                Some(ref path) if !cli.clipboard => Some(stamp::file_hash(path)?),
+                _ => None,
+            };
+
+            Some(Stamp::new(stamp::options_hash(args), input))
+        } else {
+            None
+        };
+
+        if cli.to_clipboard {
+            let mut content = vec![];
+
+            Self::write_chart_file(
+                &mut content,
+                chart_file,
+                &text,
+                cli.get_format(),
+                cli.get_layout(),
+                stamp.as_ref(),
+            )?;
+            clipboard::write(&String::from_utf8(content)?)?;
+        } else if !cli.views.is_empty() {
+            write!(
+                cli.get_output()?,
+                "{}",
+                views::workbook(chart_file, &cli.views, &text)?
+            )?;
+        } else if write_chart {
+            match (cli.max_resources_per_chart, cli.output_path()) {
+                (Some(max), Some(path)) if chart.resources.len() > max as usize => {
+                    for (i, page) in paginate::pages(chart_file, max as usize).iter().enumerate() {
+                        let page_path = paginate::page_path(path, i + 1);
+                        let mut file = File::create(&page_path).context(format!(
+                            "Unable to create file '{}'",
+                            page_path.to_string_lossy()
+                        ))?;
+
+                        Self::write_chart_file(
+                            &mut file,
+                            page,
+                            &text,
+                            cli.get_format(),
+                            cli.get_layout(),
+                            stamp.as_ref(),
+                        )?;
+                    }
+                }
+                _ => Self::write_chart_file(
+                    &mut cli.get_output()?,
+                    chart_file,
+                    &text,
+                    cli.get_format(),
+                    cli.get_layout(),
+                    stamp.as_ref(),
+                )?,
+            }
+        }
+
+        if let Some(format) = cli.print_schedule {
+            output!(self.log, "{}", report::schedule(&chart, format));
+        }
+
+        if let Some(ref path) = cli.due_dates {
+            std::fs::write(path, report::due_dates(&chart)?).context(format!(
+                "Unable to write due dates file '{}'",
+                path.to_string_lossy()
+            ))?;
+        }
+
+        if let (Some(rejects), Some(path)) = (rejects, cli.rejects.as_ref()) {
+            rejects.finish()?;
+
+            if rejects.count() > 0 {
+                warning!(
+                    self.log,
+                    "{} row(s) were rejected, see '{}'",
+                    rejects.count(),
+                    path.to_string_lossy()
+                );
+            }
+        }
+
+        if let Some(client) = client {
+            client.finish()?;
+        }
+
+        if !cli.report.is_empty() {
+            let format = cli.report_format;
+            let reports = cli
+                .report
+                .iter()
+                .map(|kind| match kind {
+                    ReportKind::Summary => {
+                        report::summary(&chart, &availability, &pto, &text, format)
+                    }
+                    ReportKind::NextUp => report::next_up(&chart, &dependencies, &text, format),
+                    ReportKind::Health => report::health(&chart, &text, format),
+                    ReportKind::Blocked => report::blocked(&chart, today, &text, format),
+                    ReportKind::Reassign => report::reassign(&chart, &text, format),
+                    ReportKind::WorkloadByWeek => {
+                        report::workload_by_week(&chart, &pto, &text, format)
+                    }
+                    ReportKind::Sla => report::sla(&chart, today, &text, format),
+                })
+                .collect::<Result<Vec<String>, _>>()?;
+
+            match cli.report_file {
+                // A CSV file holds one table, so each report goes in a file
+                // of its own
+                Some(ref path) if format == ReportFormat::Csv && reports.len() > 1 => {
+                    for (kind, report) in cli.report.iter().zip(reports) {
+                        let path = report::report_path(path, *kind);
+
+                        std::fs::write(&path, report + "\n").context(format!(
+                            "Unable to write report file '{}'",
+                            path.to_string_lossy()
+                        ))?;
+                    }
+                }
+                Some(ref path) => std::fs::write(path, reports.join("\n\n") + "\n").context(
+                    format!("Unable to write report file '{}'", path.to_string_lossy()),
+                )?,
+                None => output!(self.log, "{}", reports.join("\n\n")),
+            }
+
+            if let Some(ref path) = cli.report_file {
+                if format == ReportFormat::Csv && cli.report.contains(&ReportKind::Summary) {
+                    for table in SummaryTable::ALL {
+                        let path = report::summary_table_path(path, table);
+
+                        std::fs::write(
+                            &path,
+                            report::summary_table(&chart, &availability, &pto, &text, table)?
+                                + "\n",
+                        )
+                        .context(format!(
+                            "Unable to write report file '{}'",
+                            path.to_string_lossy()
+                        ))?;
+                    }
+                }
+            }
+        }
+
+        let mut failures = 0;
+
+        for threshold in cli.fail_if.iter() {
+            if let Some(failure) = threshold.check(&chart)? {
+                error!(self.log, "{}", failure);
+                failures += 1;
+            }
+        }
+
+        if failures > 0 {
+            return Err(From::from(format!(
+                "{} --fail-if condition(s) failed",
+                failures
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Move and stretch items for dependencies and time off. Either can move
+    /// items that the other has already placed, so alternate until neither
+    /// changes anything.
+    fn schedule(
+        chart: &mut Chart,
+        dependencies: &[deps::Dependency],
+        pto: &Pto,
+        granularity: Granularity,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        // Working hours already go around the days off, so only the days
+        // worked count as the work of each item
+        let work: Vec<Option<i64>> = match granularity {
+            Granularity::Days => chart.items.iter().map(|item| item.data.duration).collect(),
+            Granularity::Hours => pto.worked_days(chart),
+        };
+        let warnings = deps::apply(chart, dependencies)?;
+
+        for _ in 0..=chart.items.len() {
+            if !pto.apply(chart, &work) {
+                return Ok(warnings);
+            }
+
+            deps::apply(chart, dependencies)?;
+        }
+
+        Err(From::from("Dependencies and time off can't all be met"))
+    }
+
+    fn chart_diff(
+        &self,
+        old_file: &Path,
+        new_file: &Path,
+        json: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let changes = diff::diff_charts(&Chart::load(old_file)?, &Chart::load(new_file)?);
+
+        if json {
+            output!(self.log, "{}", serde_json::to_string_pretty(&changes)?);
+        } else if changes.is_empty() {
+            output!(self.log, "No changes");
+        } else {
+            changes
+                .iter()
+                .for_each(|change| output!(self.log, "{}", change));
+        }
+
+        Ok(())
+    }
+
+    fn self_test(&self, bless: Option<&Path>) -> Result<(), Box<dyn Error>> {
+        let checks = golden::run(bless)?;
+        let failed: Vec<&golden::Check> = checks
+            .iter()
+            .filter(|check| check.difference.is_some())
+            .collect();
+
+        for check in failed.iter() {
+            error!(
+                self.log,
+                "{}: {}",
+                check.name,
+                check.difference.as_deref().unwrap_or_default()
+            );
+        }
+
+        if !failed.is_empty() {
+            return Err(From::from(format!(
+                "{} of {} self-test checks failed",
+                failed.len(),
+                checks.len()
+            )));
+        }
+
+        match bless {
+            Some(dir) => output!(
+                self.log,
+                "Wrote the golden files of {} checks to '{}'",
+                checks.len(),
+                dir.to_string_lossy()
+            ),
+            None => output!(self.log, "All {} self-test checks passed", checks.len()),
+        }
+
+        Ok(())
+    }
+
+    fn verify(
+        &self,
+        chart_file: &Path,
+        input: Option<&Path>,
+        options: &[OsString],
+    ) -> Result<(), Box<dyn Error>> {
+        let content = fs::read_to_string(chart_file).context(format!(
+            "Unable to read chart file '{}'",
+            chart_file.to_string_lossy()
+        ))?;
+        let stamp = stamp::verify(&content, input, (!options.is_empty()).then_some(options))
+            .map_err(|err| {
+                format!(
+                    "Chart file '{}' fails verification: {}",
+                    chart_file.to_string_lossy(),
+                    err
+                )
+            })?;
+
+        output!(self.log, "Made by {} at {}", stamp.tool, stamp.generated);
+        output!(self.log, "Options {}", stamp.options);
+
+        if let Some(ref input) = stamp.input {
+            output!(self.log, "Input {}", input);
+        }
+
+        output!(self.log, "Chart {} is unchanged", stamp.chart);
+
+        Ok(())
+    }
+
+    fn suggest_sprints(
+        &self,
+        chart_file: &Path,
+        config: &Config,
+        sprints: usize,
+        capacity: Option<f64>,
+        format: SuggestFormat,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(days) = capacity.filter(|days| !days.is_finite() || *days <= 0.0) {
+            return Err(From::from(format!(
+                "The sprint capacity must be more than zero days, not {}",
+                days
+            )));
+        }
+
+        let chart = Chart::load(chart_file)?;
+        let today = chrono::Local::now().date_naive();
+        let (suggestions, unknown) = suggest::suggest(&chart, config, sprints, capacity, today);
+
+        for resource in unknown.iter() {
+            warning!(
+                self.log,
+                "No sprint capacity for '{}', so its issues aren't suggested (give one with --capacity or sprintCapacity in the config file)",
+                resource
+            );
+        }
+
+        let mut buffer = vec![];
+
+        suggest::write(&mut buffer, &suggestions, format)?;
+        output!(self.log, "{}", String::from_utf8(buffer)?.trim_end());
+
+        Ok(())
+    }
+
+    fn write_chart_file(
+        writer: &mut dyn Write,
+        chart: &Chart,
+        text: &Translations,
+        format: Format,
+        layout: Layout,
+        stamp: Option<&Stamp>,
+    ) -> Result<(), Box<dyn Error>> {
+        if format == Format::Svg {
+            write!(writer, "{}", render::svg(chart, text)?)?;
+
+            return Ok(());
+        }
+
+        let stamped = match stamp {
+            Some(stamp) => Some(stamp::apply(chart, stamp.clone())?),
+            None => None,
+        };
+
+        // Written from the chart itself, the default keeps its field order
+        match (layout, stamped) {
+            (Layout::Default, Some(stamped)) => write!(writer, "{}", json5::to_string(&stamped)?)?,
+            (Layout::Default, None) => write!(writer, "{}", json5::to_string(&chart)?)?,
+            (_, Some(stamped)) => {
+                Self::write_chart_value(writer, &serde_json::to_value(&stamped)?, layout)?
+            }
+            (_, None) => Self::write_chart_value(writer, &serde_json::to_value(chart)?, layout)?,
+        }
+
+        Ok(())
+    }
+
+    fn write_chart_value(
+        writer: &mut dyn Write,
+        value: &serde_json::Value,
+        layout: Layout,
+    ) -> Result<(), Box<dyn Error>> {
+        // The keys of JSON objects here are sorted, and JSON is also valid JSON5
+        match layout {
+            Layout::Default => write!(writer, "{}", json5::to_string(value)?)?,
+            Layout::Pretty => writeln!(writer, "{}", serde_json::to_string_pretty(value)?)?,
+            Layout::Compact => write!(writer, "{}", serde_json::to_string(value)?)?,
+        }
+
+        Ok(())
+    }
+
+    fn require_client(client: Option<&JiraClient>) -> Result<&JiraClient, Box<dyn Error>> {
+        client.ok_or_else(|| From::from("A Jira URL is required, use --jira-url or set JIRA_URL"))
+    }
+
+    fn read_portfolio(
+        &self,
+        path: &Path,
+        client: Option<&JiraClient>,
+        site_client: &SiteClient,
+        options: ConvertOptions,
+        concurrency: Option<usize>,
+    ) -> Result<Chart, Box<dyn Error>> {
+        let config = PortfolioConfig::load(path)?;
+        let key_map = match config.key_map {
+            Some(ref path) => Some(KeyMap::load(path)?),
+            None => None,
+        };
+        let mut chart = Chart::new(&config.title);
+
+        for source in config.sources.iter() {
+            let kinds = [
+                source.file.is_some(),
+                source.board.is_some(),
+                source.jql.is_some(),
+                source.filter.is_some(),
+            ];
+
+            if kinds.iter().filter(|kind| **kind).count() != 1 {
+                return Err(From::from(format!(
+                    "Portfolio source '{}' must have exactly one of 'file', 'board', 'jql' or 'filter'",
+                    source.name
+                )));
+            }
+        }
+
+        // Sources on a named site are fetched with a client for the site,
+        // shared by the site's sources
+        let mut site_clients: BTreeMap<&str, JiraClient> = BTreeMap::new();
+
+        for source in config.sources.iter().filter(|s| s.fetch().is_some()) {
+            if let Some(site) = source.site() {
+                if !site_clients.contains_key(site) {
+                    site_clients.insert(site, site_client(site)?);
+                }
+            }
+        }
+
+        let mut fetches: Vec<(&JiraClient, Fetch)> = vec![];
+
+        for source in config.sources.iter() {
+            if let Some(fetch) = source.fetch() {
+                let client = match source.site() {
+                    Some(site) => &site_clients[site],
+                    None => Self::require_client(client)?,
+                };
+
+                fetches.push((client, fetch));
+            }
+        }
+
+        let mut boards = if fetches.is_empty() {
+            vec![]
+        } else {
+            Self::fetch_boards(
+                &fetches,
+                concurrency
+                    .or(config.concurrency)
+                    .unwrap_or(DEFAULT_CONCURRENCY),
+            )?
+        }
+        .into_iter();
+
+        // The clients of named sites aren't needed again, so their
+        // checkpoints go as soon as their sources are fetched
+        for client in site_clients.values() {
+            client
+                .take_warnings()
+                .iter()
+                .for_each(|w| warning!(self.log, "{}", w));
+            client.finish()?;
+        }
+
+        // Sources are merged in the order they are listed, however long each
+        // one took to fetch, so that the chart is the same from run to run
+        for source in config.sources.iter() {
+            let source_options = ConvertOptions {
+                source: source
+                    .source
+                    .as_ref()
+                    .map(|source| source.source)
+                    .or(options.source),
+                ..options
+            };
+            let mut source_chart = if let Some(ref file) = source.file {
+                let reader = File::open(file)
+                    .context(format!("Unable to open file '{}'", file.to_string_lossy()))?;
+
+                self.read_chart(vec![Input::new(reader)], source_options)?
+            } else {
+                // There is one fetched board for each source fetched from
+                // Jira, with no sprints for a filter or search
+                let (records, sprints) = boards.next().unwrap();
+                let mut builder = ChartBuilder::new(source_options);
+
+                records.iter().for_each(|record| {
+                    builder.add(record);
+                });
+                Self::board_chart(builder, sprints)?
+            };
+
+            for item in source_chart.items.iter_mut() {
+                item.data.title = keys::normalize(&item.data.title, source.key_prefix());
+                item.id = item
+                    .id
+                    .as_deref()
+                    .map(|id| keys::normalize(id, source.key_prefix()));
+                item.depends_on = item
+                    .depends_on
+                    .iter()
+                    .map(|id| keys::normalize(id, source.key_prefix()))
+                    .collect();
+            }
+
+            let resources = chart.append(source_chart, source.prefix());
+
+            chart.groups.push(Group {
+                name: source.name.to_owned(),
+                color: source.color.to_owned(),
+                resources,
+            });
+        }
+
+        if let Some(key_map) = key_map {
+            key_map.reconcile(&mut chart);
+        }
+
+        Ok(chart)
+    }
+
+    /// Fetch the boards, filters and searches of several sources, each with
+    /// the client for its site, with up to `concurrency` fetches at a time.
+    /// Results are returned in the same order as `fetches`.
+    fn fetch_boards(
+        fetches: &[(&JiraClient, Fetch)],
+        concurrency: usize,
+    ) -> Result<Vec<FetchedBoard>, Box<dyn Error>> {
+        let next = AtomicUsize::new(0);
+        let results: Vec<Mutex<Option<Result<_, String>>>> =
+            fetches.iter().map(|_| Mutex::new(None)).collect();
+
+        thread::scope(|scope| {
+            for _ in 0..concurrency.clamp(1, fetches.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+
+                    if index >= fetches.len() {
+                        break;
+                    }
+
+                    let (client, fetch) = fetches[index];
+                    let mut records = vec![];
+                    let mut sink = |record| records.push(record);
+                    let sprints = match fetch {
+                        Fetch::Board(board_id) => client.fetch_board(board_id, &mut sink),
+                        Fetch::Filter(filter_id) => {
+                            client.fetch_filter(filter_id, &mut sink).map(|_| vec![])
+                        }
+                        Fetch::Jql(jql) => client.fetch_jql(jql, &mut sink).map(|()| vec![]),
+                    };
+                    let result = sprints
+                        .map(|sprints| (records, sprints))
+                        .map_err(|err| err.to_string());
+
+                    *results[index].lock().unwrap() = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_iter()
+            .map(|result| result.into_inner().unwrap().unwrap().map_err(From::from))
+            .collect()
+    }
+
+    fn fetch_board_chart(
+        &self,
+        client: &JiraClient,
+        board_id: u64,
+        options: ConvertOptions,
+    ) -> Result<Chart, Box<dyn Error>> {
+        let mut builder = ChartBuilder::new(options);
+        let sprints = client.fetch_board(board_id, &mut |record| {
+            builder.add(&record);
+        })?;
+
+        Self::board_chart(builder, sprints)
+    }
+
+    fn board_chart(
+        builder: ChartBuilder,
+        sprints: Vec<SprintInfo>,
+    ) -> Result<Chart, Box<dyn Error>> {
+        let mut chart = builder.finish()?;
+
+        chart.sprints = sprints
+            .into_iter()
+            .map(|sprint| Sprint {
+                name: sprint.name,
+                start_date: sprint.start_date,
+                end_date: sprint.end_date,
+            })
+            .collect();
+
+        Ok(chart)
+    }
+
+    /// The given source, or the one detected from the header row
+    fn source_for(&self, source: Option<Source>, headers: &StringRecord) -> Source {
+        source.unwrap_or_else(|| {
+            let source = Source::detect(headers);
+
+            note!(
+                self.log,
+                "No --source given, detected a {} export from the header row",
+                source.name()
+            );
+            source
+        })
+    }
+
+    /// Read the rows of a CSV export, adding each issue to `builder` as soon
+    /// as it is read, so that the rows aren't all held at once
+    fn read_records(
+        &self,
+        reader: Box<dyn Read>,
+        delimiter: u8,
+        options: ConvertOptions,
+        add: &mut dyn FnMut(&JiraRecord) -> bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .from_reader(reader);
+        let headers = reader.headers()?.clone();
+        let source = self.source_for(options.source, &headers);
+        let (index, warnings) = ColumnMap::for_source(source)
+            .with_columns(&options.config.columns)
+            .with_date_fields(options.date_fields)
+            .index(&headers)?;
+
+        warnings.iter().for_each(|w| warning!(self.log, "{}", w));
+
+        let mut count = 0;
+        let mut rows = 0;
+        // Whether every row was read, rather than stopping at --head
+        let mut whole = true;
+
+        for byte_record in reader.byte_records() {
+            if options.head.is_some_and(|head| count >= head) {
+                whole = false;
+                break;
+            }
+
+            let byte_record: ByteRecord = byte_record?;
+
+            rows += 1;
+            let string_record: StringRecord = StringRecord::from_byte_record_lossy(byte_record);
+
+            if let Some(record) = Self::read_row(&index, &headers, &string_record, options)? {
+                if add(&record) {
+                    count += 1;
+                }
+            }
+        }
+
+        if source == Source::Jira && whole && rows == JIRA_EXPORT_LIMIT {
+            warning!(
+                self.log,
+                "The export has exactly {} issues, the most Jira exports at once, so it is probably missing some. Export the issues in smaller batches, e.g. by created date, or fetch them with --jql",
+                JIRA_EXPORT_LIMIT
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Read a JSON array of issue objects keyed by column name, as exported
+    /// from a plan. The objects are turned into rows so they can be read
+    /// exactly like a CSV export.
+    fn read_json_records(
+        &self,
+        mut reader: Box<dyn Read>,
+        options: ConvertOptions,
+        add: &mut dyn FnMut(&JiraRecord) -> bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut content = String::new();
+
+        reader.read_to_string(&mut content)?;
+
+        let value: serde_json::Value = serde_json::from_str(&content)?;
+        let issues = match value {
+            serde_json::Value::Array(issues) => issues,
+            serde_json::Value::Object(mut object) => match object.remove("issues") {
+                Some(serde_json::Value::Array(issues)) => issues,
+                _ => return Err(From::from("JSON export must contain an 'issues' array")),
+            },
+            _ => return Err(From::from("JSON export must be an array of issues")),
+        };
+        let mut headers: Vec<String> = vec![];
+
+        for issue in issues.iter() {
+            if let serde_json::Value::Object(object) = issue {
+                for name in object.keys() {
+                    if !headers.contains(name) {
+                        headers.push(name.to_owned());
+                    }
+                }
+            }
+        }
+
+        fn to_text(value: &serde_json::Value) -> String {
+            match value {
+                serde_json::Value::Null => "".to_owned(),
+                serde_json::Value::String(s) => s.to_owned(),
+                serde_json::Value::Array(values) => values
+                    .iter()
+                    .map(to_text)
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                other => other.to_string(),
+            }
+        }
+
+        let headers = StringRecord::from(headers);
+        let (index, warnings) = ColumnMap::for_source(options.source.unwrap_or(Source::Plan))
+            .with_columns(&options.config.columns)
+            .with_date_fields(options.date_fields)
+            .index(&headers)?;
+
+        warnings.iter().for_each(|w| warning!(self.log, "{}", w));
+
+        let mut count = 0;
+
+        for issue in issues.iter() {
+            if options.head.is_some_and(|head| count >= head) {
+                break;
+            }
+
+            let row = StringRecord::from(
+                headers
+                    .iter()
+                    .map(|name| issue.get(name).map(to_text).unwrap_or_default())
+                    .collect::<Vec<String>>(),
+            );
+
+            if let Some(record) = Self::read_row(&index, &headers, &row, options)? {
+                if add(&record) {
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read one row of an export, or `None` if it has no issue key. With
+    /// rejects, rows that are skipped or can't be read are written there
+    /// instead.
+    fn read_row(
+        index: &ColumnIndex,
+        headers: &StringRecord,
+        row: &StringRecord,
+        options: ConvertOptions,
+    ) -> Result<Option<JiraRecord>, Box<dyn Error>> {
+        let result = JiraRecord::from_row(index, row, options.numbers, options.config);
+        let reason = match result {
+            Ok(ref record) if record.key.is_empty() => "No issue key".to_owned(),
+            Ok(record) => return Ok(Some(record)),
+            Err(err) if options.rejects.is_some() => err.to_string(),
+            Err(err) => return Err(err),
+        };
+
+        if let Some(rejects) = options.rejects {
+            rejects.reject(headers, row, &reason)?;
+        }
+
+        Ok(None)
+    }
+
+    /// Read one or more exports into one chart. When there are several, an
+    /// issue that is in more than one is only read from the first.
+    fn read_chart(
+        &self,
+        inputs: Vec<Input>,
+        options: ConvertOptions,
+    ) -> Result<Chart, Box<dyn Error>> {
+        let merging = inputs.len() > 1;
+        let mut builder = ChartBuilder::new(options);
+        let mut earlier: HashSet<String> = HashSet::new();
+        let mut duplicates = 0;
+
+        for input in inputs {
+            let mut keys = HashSet::new();
+            let mut add = |record: &JiraRecord| {
+                if !merging {
+                    builder.add(record)
+                } else if earlier.contains(&record.key) {
+                    duplicates += 1;
+                    false
+                } else {
+                    keys.insert(record.key.to_owned());
+                    builder.add(record)
+                }
+            };
+
+            match input.found {
+                None => self.read_export(input.reader, options, &mut add)?,
+                // Checked in a first pass that adds nothing, so a file that
+                // can't be read isn't half in the chart, then read again as
+                // it is added
+                Some(ref path) => {
+                    let discard = Rejects::discard();
+                    let check = ConvertOptions {
+                        head: None,
+                        rejects: options.rejects.map(|_| &discard),
+                        ..options
+                    };
+                    let quiet = JiraToGanttTool {
+                        log: &QuietLog,
+                        start: self.start,
+                    };
+
+                    match quiet.read_export(input.reader, check, &mut |_| true) {
+                        Ok(()) => {
+                            let file = File::open(path).context(format!(
+                                "Unable to open file '{}'",
+                                path.to_string_lossy()
+                            ))?;
+
+                            self.read_export(Box::new(file), options, &mut add)?
+                        }
+                        Err(err) => warning!(
+                            self.log,
+                            "Skipped '{}', which couldn't be read: {}",
+                            path.to_string_lossy(),
+                            err
+                        ),
+                    }
+                }
+            }
+
+            earlier.extend(keys);
+        }
+
+        if duplicates > 0 {
+            warning!(
+                self.log,
+                "{} issue(s) are in more than one input file, so only the first file with each is used",
+                duplicates
+            );
+        }
+
+        builder.finish()
+    }
+
+    fn read_export(
+        &self,
+        reader: Box<dyn Read>,
+        options: ConvertOptions,
+        add: &mut dyn FnMut(&JiraRecord) -> bool,
+    ) -> Result<(), Box<dyn Error>> {
+        // Plans can also be exported as JSON, so sniff the first character.
+        // Only the lines up to the first that isn't blank are read ahead, so
+        // the rest is converted as it is read rather than held all at once.
+        let mut reader = io::BufReader::new(reader);
+        let mut start: Vec<u8> = vec![];
+
+        while start.iter().all(|b| b.is_ascii_whitespace()) {
+            if reader.read_until(b'\n', &mut start)? == 0 {
+                break;
+            }
+        }
+
+        // JSON exports only come from plans
+        let is_json = matches!(options.source, None | Some(Source::Plan))
+            && matches!(
+                start.iter().find(|b| !b.is_ascii_whitespace()),
+                Some(b'[') | Some(b'{')
+            );
+        // Rows copied from a spreadsheet or a web page are tab separated
+        let header_row = start
+            .split(|b| *b == b'\n')
+            .find(|line| !line.iter().all(|b| b.is_ascii_whitespace()))
+            .unwrap_or_default();
+        let delimiter = if header_row.contains(&b'\t') && !header_row.contains(&b',') {
+            b'\t'
+        } else {
+            b','
+        };
+        let reader = Box::new(io::Cursor::new(start).chain(reader));
+
+        if is_json {
+            self.read_json_records(reader, options, add)
+        } else {
+            self.read_records(reader, delimiter, options, add)
+        }
     }
 }
@@ -25,4 +25,14 @@ macro_rules! error {
   ($log: expr, $fmt: expr, $($args: tt)+) => {
     $log.error(format_args!($fmt, $($args)+))
   };
+}
+
+#[macro_export]
+macro_rules! note {
+  ($log: expr, $fmt: expr) => {
+    $log.note(format_args!($fmt))
+  };
+  ($log: expr, $fmt: expr, $($args: tt)+) => {
+    $log.note(format_args!($fmt, $($args)+))
+  };
 }
\ No newline at end of file
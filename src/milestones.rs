@@ -0,0 +1,102 @@
+use crate::{
+    axis,
+    chart::{self, Chart},
+    i18n::Translations,
+    schedule,
+};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+
+/// What to add milestones for
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Milestones {
+    /// Each release of the Fix Version/s column, on the day its last issue
+    /// is scheduled to finish
+    FixVersion,
+    /// Each day that several open issues are due on, which may be a deadline
+    /// that isn't in the release plan
+    DueDate,
+}
+
+/// Add a lane with a milestone for each release, titled with its name, on
+/// the last day of the last of its issues to finish. Each milestone depends
+/// on the issues of its release, so renderers can draw arrows from them.
+/// Milestones are in the order of their dates.
+pub fn fix_versions(chart: &mut Chart, text: &Translations) {
+    let mut releases: Vec<(String, NaiveDate, Vec<String>)> = vec![];
+
+    for item in schedule::schedule(chart).iter() {
+        let last_day = match item.last_day() {
+            Some(last_day) => last_day,
+            None => continue,
+        };
+
+        for version in item.item.fix_versions.iter() {
+            match releases.iter_mut().find(|(name, _, _)| name == version) {
+                Some((_, date, issues)) => {
+                    *date = (*date).max(last_day);
+                    issues.push(item.item.id().to_owned());
+                }
+                None => releases.push((
+                    version.to_owned(),
+                    last_day,
+                    vec![item.item.id().to_owned()],
+                )),
+            }
+        }
+    }
+
+    if releases.is_empty() {
+        return;
+    }
+
+    releases.sort_by_key(|(_, date, _)| *date);
+
+    let lane = chart.resources.len();
+
+    chart.resources.push(text.get("releases").to_owned());
+
+    for (name, date, issues) in releases {
+        let mut item = axis::milestone(&chart::synthetic_id("release", &name), date, lane);
+
+        item.data.title = name;
+        item.depends_on = issues;
+        chart.items.push(item);
+    }
+}
+
+/// Add a lane with a milestone on each day that at least `min_issues` open
+/// issues are due, titled with the number of issues. Each milestone depends
+/// on the issues due that day.
+pub fn due_dates(chart: &mut Chart, min_issues: usize, text: &Translations) {
+    let mut days: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+
+    for item in chart.items.iter() {
+        if let (Some(due_date), true) = (item.due_date, item.data.open != Some(false)) {
+            days.entry(due_date).or_default().push(item.id().to_owned());
+        }
+    }
+
+    days.retain(|_, issues| issues.len() >= min_issues);
+
+    if days.is_empty() {
+        return;
+    }
+
+    let lane = chart.resources.len();
+
+    chart.resources.push(text.get("deadlines").to_owned());
+
+    for (date, issues) in days {
+        let mut item = axis::milestone(
+            &chart::synthetic_id("deadline", &date.to_string()),
+            date,
+            lane,
+        );
+
+        item.data.title = text.format("due-issues", issues.len());
+        item.depends_on = issues;
+        chart.items.push(item);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::fetch::JiraClient;
+use easy_error::{self, ResultExt};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    env,
+    error::Error,
+    fs,
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// Display names by site and then by account id or username
+type NameCache = HashMap<String, HashMap<String, String>>;
+
+/// Turns the account ids and usernames of assignees into their display
+/// names with Jira's user API. Names are cached in the user's config
+/// directory, so each person is only looked up once.
+pub struct NameResolver<'a> {
+    client: &'a JiraClient,
+    cache: RefCell<NameCache>,
+    /// Users that couldn't be looked up, so each is only tried and warned
+    /// about once
+    failed: RefCell<HashSet<String>>,
+    warnings: RefCell<Vec<String>>,
+}
+
+/// The per-user directory for the tool's own files
+fn user_config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("jira_to_gantt"))
+}
+
+fn cache_path() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("names.json"))
+}
+
+impl<'a> NameResolver<'a> {
+    pub fn new(client: &'a JiraClient) -> NameResolver<'a> {
+        NameResolver {
+            client,
+            cache: RefCell::new(
+                cache_path()
+                    .and_then(|path| fs::read_to_string(path).ok())
+                    .and_then(|content| serde_json::from_str(&content).ok())
+                    .unwrap_or_default(),
+            ),
+            failed: RefCell::new(HashSet::new()),
+            warnings: RefCell::new(vec![]),
+        }
+    }
+
+    /// The display name of an assignee, if it is an account id or username
+    /// that Jira knows. Names with spaces are taken to be display names
+    /// already.
+    pub fn resolve(&self, user: &str) -> Option<String> {
+        if user.is_empty() || user.contains(char::is_whitespace) {
+            return None;
+        }
+
+        let site = self.client.base_url();
+
+        if let Some(name) = self
+            .cache
+            .borrow()
+            .get(site)
+            .and_then(|names| names.get(user))
+        {
+            return Some(name.to_owned());
+        }
+
+        if self.failed.borrow().contains(user) {
+            return None;
+        }
+
+        match self.client.display_name(user) {
+            Ok(name) => {
+                self.cache
+                    .borrow_mut()
+                    .entry(site.to_owned())
+                    .or_default()
+                    .insert(user.to_owned(), name.to_owned());
+
+                Some(name)
+            }
+            Err(err) => {
+                self.failed.borrow_mut().insert(user.to_owned());
+                self.warnings.borrow_mut().push(format!(
+                    "Unable to look up the name of '{}', so its lane keeps that name: {}",
+                    user, err
+                ));
+
+                None
+            }
+        }
+    }
+
+    /// Write the names looked up to the cache
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = cache_path()
+            .ok_or_else(|| "Unable to find a user directory for the name cache".to_owned())?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(&path, serde_json::to_string_pretty(&*self.cache.borrow())?).context(format!(
+            "Unable to write name cache '{}'",
+            path.to_string_lossy()
+        ))?;
+
+        Ok(())
+    }
+
+    /// The users that couldn't be looked up, to warn about once the chart
+    /// is built
+    pub fn take_warnings(&self) -> Vec<String> {
+        self.warnings.take()
+    }
+}
+
+/// A name to show for someone instead of how they are given in the export,
+/// given as `name=display name`, e.g. `alice@corp.com=Alice W.`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alias {
+    pub name: String,
+    pub display_name: String,
+}
+
+impl FromStr for Alias {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unquote = |s: &str| s.trim().trim_matches('"').trim().to_owned();
+        let invalid = || {
+            format!(
+                "'{}' must be a name and the name to show, e.g. alice@corp.com=\"Alice W.\"",
+                s
+            )
+        };
+        let (name, display_name) = s.split_once('=').ok_or_else(invalid)?;
+        let (name, display_name) = (unquote(name), unquote(display_name));
+
+        if name.is_empty() || display_name.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Alias { name, display_name })
+    }
+}
@@ -0,0 +1,205 @@
+use rustls::{
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{self, ring, CryptoProvider},
+    pki_types::{pem::PemObject, CertificateDer, ServerName, UnixTime},
+    ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use std::{env, error::Error, path::PathBuf, sync::Arc};
+
+/// Transport settings shared by everything that talks to the network
+#[derive(Default, Clone)]
+pub struct NetworkOptions {
+    /// A PEM file of extra certificate authorities to trust
+    pub ca_bundle: Option<PathBuf>,
+    /// Skip verification of server certificates
+    pub insecure: bool,
+    /// A proxy URL to use instead of the `HTTPS_PROXY` or `HTTP_PROXY`
+    /// environment variables
+    pub proxy: Option<String>,
+}
+
+/// Accepts any server certificate, for self-signed certificates when the
+/// user has explicitly asked for it. Handshake signatures are still checked.
+#[derive(Debug)]
+struct NoVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn tls_config(options: &NetworkOptions) -> Result<Option<Arc<ClientConfig>>, Box<dyn Error>> {
+    if !options.insecure && options.ca_bundle.is_none() {
+        return Ok(None);
+    }
+
+    let provider = Arc::new(ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+
+    if options.insecure {
+        return Ok(Some(Arc::new(
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(NoVerification(provider)))
+                .with_no_client_auth(),
+        )));
+    }
+
+    let mut roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+    if let Some(ref path) = options.ca_bundle {
+        let certificates = CertificateDer::pem_file_iter(path)
+            .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+            .map_err(|err| {
+                format!(
+                    "Unable to read CA bundle '{}': {}",
+                    path.to_string_lossy(),
+                    err
+                )
+            })?;
+
+        if certificates.is_empty() {
+            return Err(From::from(format!(
+                "No certificates found in CA bundle '{}'",
+                path.to_string_lossy()
+            )));
+        }
+
+        for certificate in certificates.into_iter() {
+            roots.add(certificate).map_err(|err| {
+                format!(
+                    "Unable to use certificate in '{}': {}",
+                    path.to_string_lossy(),
+                    err
+                )
+            })?;
+        }
+    }
+
+    Ok(Some(Arc::new(
+        builder.with_root_certificates(roots).with_no_client_auth(),
+    )))
+}
+
+fn env_var(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .find_map(|name| env::var(name).ok())
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// Check a host against a `NO_PROXY` style list. Entries match the host
+/// itself and any of its subdomains, and `*` matches every host.
+fn is_no_proxy(host: &str, port: Option<u16>, no_proxy: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+
+            let (entry_host, entry_port) = match entry.rsplit_once(':') {
+                Some((h, p)) if !h.contains(':') || h.ends_with(']') => (h, p.parse::<u16>().ok()),
+                _ => (entry, None),
+            };
+
+            if entry_port.is_some() && entry_port != port {
+                return false;
+            }
+
+            let entry_host = entry_host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .trim_start_matches('.');
+
+            host.eq_ignore_ascii_case(entry_host)
+                || host
+                    .to_ascii_lowercase()
+                    .ends_with(&format!(".{}", entry_host.to_ascii_lowercase()))
+        })
+}
+
+/// Choose the proxy for a URL, if any
+fn proxy_for(options: &NetworkOptions, url: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let url = url::Url::parse(url)?;
+
+    if let (Some(host), Some(no_proxy)) = (url.host_str(), env_var(&["NO_PROXY", "no_proxy"])) {
+        if is_no_proxy(host, url.port_or_known_default(), &no_proxy) {
+            return Ok(None);
+        }
+    }
+
+    if options.proxy.is_some() {
+        return Ok(options.proxy.clone());
+    }
+
+    Ok(match url.scheme() {
+        "https" => env_var(&["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]),
+        _ => env_var(&["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"]),
+    })
+}
+
+/// Build an HTTP agent with the given transport settings for requests to
+/// the given URL
+pub fn agent(options: &NetworkOptions, url: &str) -> Result<ureq::Agent, Box<dyn Error>> {
+    // The proxy is chosen here rather than by ureq so that NO_PROXY is honored
+    let mut builder = ureq::AgentBuilder::new().try_proxy_from_env(false);
+
+    if let Some(config) = tls_config(options)? {
+        builder = builder.tls_config(config);
+    }
+
+    if let Some(proxy) = proxy_for(options, url)? {
+        builder = builder.proxy(
+            ureq::Proxy::new(&proxy)
+                .map_err(|err| format!("Invalid proxy '{}': {}", proxy, err))?,
+        );
+    }
+
+    Ok(builder.build())
+}
@@ -0,0 +1,90 @@
+use clap::ValueEnum;
+
+/// How numbers in an export are written
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberFormat {
+    /// Guess the separators from each number
+    Auto,
+    /// A decimal point and comma thousands separators, e.g. 28,800.5
+    Point,
+    /// A decimal comma and point thousands separators, e.g. 28.800,5
+    Comma,
+}
+
+/// Parse a number that may have thousands separators or a decimal comma.
+/// Spaces and apostrophes are always thousands separators.
+pub fn parse_number(s: &str, format: NumberFormat) -> Option<f64> {
+    let s: String = s
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '\'' | '’'))
+        .collect();
+    let decimal = match format {
+        NumberFormat::Point => Some('.'),
+        NumberFormat::Comma => Some(','),
+        NumberFormat::Auto => guess_decimal_separator(&s),
+    };
+    let normalized: String = s
+        .chars()
+        .filter_map(|c| match c {
+            '.' | ',' if Some(c) == decimal => Some('.'),
+            '.' | ',' => None,
+            c => Some(c),
+        })
+        .collect();
+
+    normalized.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+/// With both separators the last one is the decimal separator. With only
+/// one that appears once and is followed by exactly three digits, it is a
+/// thousands separator, because estimates are whole numbers of seconds.
+fn guess_decimal_separator(s: &str) -> Option<char> {
+    let last = s.rfind(['.', ','])?;
+    let separator = s[last..].chars().next()?;
+    let other = if separator == '.' { ',' } else { '.' };
+
+    if s.contains(other) {
+        return Some(separator);
+    }
+
+    if s.matches(separator).count() > 1 || s.len() - last - 1 == 3 {
+        None
+    } else {
+        Some(separator)
+    }
+}
+
+/// Parse a duration such as `1w 2d 4h 30m` into seconds, with a working day
+/// of `day_seconds` and a working week of five days
+pub fn parse_duration(s: &str, day_seconds: f64) -> Option<f64> {
+    let mut seconds = 0.0;
+    let mut rest = s.trim();
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+            .unwrap_or(rest.len());
+        let amount = parse_number(&rest[..digits], NumberFormat::Auto)?;
+        let unit_end = rest[digits..]
+            .find(|c: char| !c.is_alphabetic())
+            .map(|i| i + digits)
+            .unwrap_or(rest.len());
+
+        seconds += amount
+            * match rest[digits..unit_end].to_lowercase().as_str() {
+                "w" => 5.0 * day_seconds,
+                "d" => day_seconds,
+                "h" => 3600.0,
+                "m" => 60.0,
+                "s" => 1.0,
+                _ => return None,
+            };
+        rest = rest[unit_end..].trim_start();
+    }
+
+    Some(seconds)
+}
@@ -0,0 +1,340 @@
+use crate::net::{self, NetworkOptions};
+use easy_error::{self, ResultExt};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const AUTH_URL: &str = "https://auth.atlassian.com";
+const API_URL: &str = "https://api.atlassian.com";
+const SCOPES: &str = "read:jira-work read:jira-user offline_access";
+// Refresh tokens that expire within this many seconds
+const EXPIRY_MARGIN: u64 = 60;
+/// The keyring service that tokens are kept under, one entry per client and
+/// site
+const KEYRING_SERVICE: &str = "jira_to_gantt";
+
+/// Settings for Atlassian OAuth 2.0 three-legged authentication
+pub struct OAuthOptions {
+    pub client_id: String,
+    pub client_secret: String,
+    pub port: u16,
+}
+
+/// A token as stored in the keyring
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_at: u64,
+    cloud_id: String,
+}
+
+/// An access token and the API base URL it is valid for
+pub struct OAuthSession {
+    pub access_token: String,
+    pub base_url: String,
+    /// Warnings for tokens that couldn't be read from or kept in the keyring
+    pub warnings: Vec<String>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn keyring_entry(key: &str) -> Result<keyring::Entry, Box<dyn Error>> {
+    keyring::Entry::new(KEYRING_SERVICE, key).map_err(|err| {
+        From::from(format!(
+            "Unable to use the keyring for OAuth tokens: {}",
+            err
+        ))
+    })
+}
+
+/// The token in the keyring, or `None` if there is none or it can't be
+/// parsed, e.g. after a change to its format
+fn read_token(key: &str) -> Result<Option<CachedToken>, Box<dyn Error>> {
+    match keyring_entry(key)?.get_password() {
+        Ok(content) => Ok(serde_json::from_str(&content).ok()),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(From::from(format!(
+            "Unable to read OAuth tokens from the keyring: {}",
+            err
+        ))),
+    }
+}
+
+fn write_token(key: &str, token: &CachedToken) -> Result<(), Box<dyn Error>> {
+    keyring_entry(key)?
+        .set_password(&serde_json::to_string(token)?)
+        .map_err(|err| format!("Unable to save OAuth tokens in the keyring: {}", err))?;
+
+    Ok(())
+}
+
+fn forget_token(key: &str) {
+    if let Ok(entry) = keyring_entry(key) {
+        let _ = entry.delete_password();
+    }
+}
+
+/// Ask for a token. The inner error is the status and body of a request
+/// that was turned down, e.g. for a refresh token that has been revoked or
+/// has expired.
+fn send_token(
+    network: &NetworkOptions,
+    body: Value,
+) -> Result<Result<Value, (u16, String)>, Box<dyn Error>> {
+    let url = format!("{}/oauth/token", AUTH_URL);
+
+    match net::agent(network, &url)?.post(&url).send_json(body) {
+        Ok(response) => Ok(Ok(response.into_json()?)),
+        Err(ureq::Error::Status(code, response)) => {
+            Ok(Err((code, response.into_string().unwrap_or_default())))
+        }
+        Err(err) => Err(From::from(format!("OAuth token request failed: {}", err))),
+    }
+}
+
+fn post_token(network: &NetworkOptions, body: Value) -> Result<Value, Box<dyn Error>> {
+    send_token(network, body)?.map_err(|(code, body)| {
+        From::from(format!(
+            "OAuth token request failed with status {}: {}",
+            code, body
+        ))
+    })
+}
+
+/// Wait for the browser to be redirected back with the authorization code
+fn wait_for_code(listener: &TcpListener, state: &str) -> Result<String, Box<dyn Error>> {
+    loop {
+        let (mut stream, _) = listener.accept()?;
+        let mut request_line = String::new();
+
+        BufReader::new(&stream).read_line(&mut request_line)?;
+
+        let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+        let url = url::Url::parse(&format!("http://localhost{}", target))?;
+
+        if url.path() != "/callback" {
+            stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")?;
+            continue;
+        }
+
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let body = "Authorization complete, you can close this window.";
+
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )?;
+
+        if params.get("state").map(|s| s.as_str()) != Some(state) {
+            return Err(From::from("OAuth redirect has an unexpected state"));
+        }
+
+        if let Some(error) = params.get("error") {
+            return Err(From::from(format!("OAuth authorization failed: {}", error)));
+        }
+
+        return params
+            .get("code")
+            .cloned()
+            .ok_or_else(|| From::from("OAuth redirect did not contain a code"));
+    }
+}
+
+/// Find the cloud ID of the site the token gives access to
+fn get_cloud_id(
+    network: &NetworkOptions,
+    access_token: &str,
+    site_url: &str,
+) -> Result<String, Box<dyn Error>> {
+    let url = format!("{}/oauth/token/accessible-resources", API_URL);
+    let resources: Value = net::agent(network, &url)?
+        .get(&url)
+        .set("Authorization", &format!("Bearer {}", access_token))
+        .call()?
+        .into_json()?;
+    let site_url = site_url.trim_end_matches('/');
+
+    resources
+        .as_array()
+        .and_then(|resources| {
+            resources
+                .iter()
+                .find(|r| r["url"].as_str().map(|u| u.trim_end_matches('/')) == Some(site_url))
+        })
+        .and_then(|r| r["id"].as_str())
+        .map(|id| id.to_owned())
+        .ok_or_else(|| {
+            From::from(format!(
+                "The OAuth token does not give access to '{}'",
+                site_url
+            ))
+        })
+}
+
+fn token_from_response(
+    network: &NetworkOptions,
+    response: &Value,
+    refresh_token: Option<String>,
+    cloud_id: Option<String>,
+    site_url: &str,
+) -> Result<CachedToken, Box<dyn Error>> {
+    let access_token = response["access_token"]
+        .as_str()
+        .ok_or("OAuth token response did not contain an access token")?
+        .to_owned();
+    let cloud_id = match cloud_id {
+        Some(cloud_id) => cloud_id,
+        None => get_cloud_id(network, &access_token, site_url)?,
+    };
+
+    Ok(CachedToken {
+        expires_at: now() + response["expires_in"].as_u64().unwrap_or(3600),
+        // Atlassian rotates refresh tokens, so prefer the new one
+        refresh_token: response["refresh_token"]
+            .as_str()
+            .map(|s| s.to_owned())
+            .or(refresh_token),
+        access_token,
+        cloud_id,
+    })
+}
+
+/// Ask the user to authorize the tool in their browser, and get a token
+/// with the code it is redirected back with
+fn authorize_in_browser(
+    network: &NetworkOptions,
+    options: &OAuthOptions,
+    site_url: &str,
+    prompt: &dyn Fn(&str),
+) -> Result<CachedToken, Box<dyn Error>> {
+    let redirect_uri = format!("http://localhost:{}/callback", options.port);
+    let listener = TcpListener::bind(("127.0.0.1", options.port)).context(format!(
+        "Unable to listen for the OAuth redirect on port {}",
+        options.port
+    ))?;
+    let state: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    let mut url = url::Url::parse(&format!("{}/authorize", AUTH_URL))?;
+
+    url.query_pairs_mut()
+        .append_pair("audience", "api.atlassian.com")
+        .append_pair("client_id", &options.client_id)
+        .append_pair("scope", SCOPES)
+        .append_pair("redirect_uri", &redirect_uri)
+        .append_pair("state", &state)
+        .append_pair("response_type", "code")
+        .append_pair("prompt", "consent");
+
+    prompt(url.as_str());
+
+    let code = wait_for_code(&listener, &state)?;
+    let response = post_token(
+        network,
+        json!({
+            "grant_type": "authorization_code",
+            "client_id": options.client_id,
+            "client_secret": options.client_secret,
+            "code": code,
+            "redirect_uri": redirect_uri,
+        }),
+    )?;
+
+    token_from_response(network, &response, None, None, site_url)
+}
+
+/// Get an access token for a site, using the token in the keyring if it is
+/// still valid, refreshing it if it has expired, or otherwise asking the
+/// user to authorize the tool in their browser. A refresh token that is
+/// turned down is forgotten, and the user is asked again. The keyring is
+/// only written when the token changes, and if it can't be used the session
+/// still goes ahead, with a warning.
+pub fn authorize(
+    network: &NetworkOptions,
+    options: &OAuthOptions,
+    site_url: &str,
+    prompt: &dyn Fn(&str),
+) -> Result<OAuthSession, Box<dyn Error>> {
+    let key = format!("{}@{}", options.client_id, site_url.trim_end_matches('/'));
+    let mut warnings = vec![];
+    let cached = read_token(&key).unwrap_or_else(|err| {
+        warnings.push(err.to_string());
+        None
+    });
+    let (token, changed) = match cached {
+        Some(token) if token.expires_at > now() + EXPIRY_MARGIN => (token, false),
+        Some(CachedToken {
+            refresh_token: Some(refresh_token),
+            cloud_id,
+            ..
+        }) => match send_token(
+            network,
+            json!({
+                "grant_type": "refresh_token",
+                "client_id": options.client_id,
+                "client_secret": options.client_secret,
+                "refresh_token": refresh_token,
+            }),
+        )? {
+            Ok(response) => (
+                token_from_response(
+                    network,
+                    &response,
+                    Some(refresh_token),
+                    Some(cloud_id),
+                    site_url,
+                )?,
+                true,
+            ),
+            Err((code, _)) if (400..500).contains(&code) => {
+                forget_token(&key);
+                (
+                    authorize_in_browser(network, options, site_url, prompt)?,
+                    true,
+                )
+            }
+            Err((code, body)) => {
+                return Err(From::from(format!(
+                    "OAuth token refresh failed with status {}: {}",
+                    code, body
+                )))
+            }
+        },
+        _ => (
+            authorize_in_browser(network, options, site_url, prompt)?,
+            true,
+        ),
+    };
+
+    if changed {
+        if let Err(err) = write_token(&key, &token) {
+            warnings.push(format!(
+                "{}, so the tool will have to be authorized again next time",
+                err
+            ));
+        }
+    }
+
+    Ok(OAuthSession {
+        access_token: token.access_token,
+        base_url: format!("{}/ex/jira/{}", API_URL, token.cloud_id),
+        warnings,
+    })
+}
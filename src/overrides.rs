@@ -0,0 +1,134 @@
+use crate::{chart::Chart, schedule};
+use chrono::NaiveDate;
+use easy_error::{self, ResultExt};
+use serde::Deserialize;
+use std::{collections::BTreeMap, error::Error, fs, path::Path};
+
+/// Changes to one item, for corrections agreed in planning that aren't in
+/// Jira yet
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Override {
+    /// The lane to move the item to, which is added if there isn't one
+    pub resource: Option<String>,
+    pub start: Option<NaiveDate>,
+    /// The days the item takes
+    pub duration: Option<i64>,
+    pub title: Option<String>,
+}
+
+/// The overrides of an overrides file, by issue key
+pub struct Overrides(BTreeMap<String, Override>);
+
+impl Overrides {
+    pub fn load(path: &Path) -> Result<Overrides, Box<dyn Error>> {
+        let content = fs::read_to_string(path).context(format!(
+            "Unable to read overrides file '{}'",
+            path.to_string_lossy()
+        ))?;
+        let overrides: BTreeMap<String, Override> = json5::from_str(&content).map_err(|err| {
+            format!(
+                "Invalid overrides file '{}': {}",
+                path.to_string_lossy(),
+                err
+            )
+        })?;
+
+        for (key, value) in overrides.iter() {
+            if value.duration.is_some_and(|days| days < 0) {
+                return Err(From::from(format!(
+                    "Duration of '{}' in overrides file '{}' is negative",
+                    key,
+                    path.to_string_lossy()
+                )));
+            }
+        }
+
+        Ok(Overrides(overrides))
+    }
+
+    /// Change the items with overrides. An item moved to another lane goes
+    /// after the last item of that lane and keeps the start it had, unless
+    /// it is given one, and the item that followed it in its old lane starts
+    /// where it did. Returns warnings for keys that aren't on the chart.
+    pub fn apply(&self, chart: &mut Chart) -> Vec<String> {
+        let mut warnings = vec![];
+
+        for (key, value) in self.0.iter() {
+            let starts: Vec<Option<NaiveDate>> = schedule::schedule(chart)
+                .iter()
+                .map(|item| item.start)
+                .collect();
+            let Some(mut i) = chart.items.iter().position(|item| item.id() == key) else {
+                warnings.push(format!(
+                    "Issue '{}' in the overrides file isn't on the chart",
+                    key
+                ));
+                continue;
+            };
+            let item = &mut chart.items[i];
+
+            if let Some(ref title) = value.title {
+                item.data.title = title.to_owned();
+            }
+
+            if value.duration.is_some() {
+                item.data.duration = value.duration;
+            }
+
+            if value.start.is_some() {
+                item.data.start_date = value.start;
+            }
+
+            let Some(ref resource) = value.resource else {
+                continue;
+            };
+            let to = match chart.resources.iter().position(|name| name == resource) {
+                Some(index) => index,
+                None => {
+                    chart.resources.push(resource.to_owned());
+                    chart.resources.len() - 1
+                }
+            };
+
+            let from = chart.items[i].data.resource_index;
+
+            if from == Some(to) {
+                continue;
+            }
+
+            let mut item = chart.items.remove(i);
+
+            item.data.start_date = item.data.start_date.or(starts[i]);
+            item.data.resource_index = Some(to);
+
+            if let Some(next) = chart.items.get_mut(i) {
+                if next.data.resource_index == from && next.data.start_date.is_none() {
+                    next.data.start_date = starts[i];
+                }
+            }
+
+            i = chart
+                .items
+                .iter()
+                .rposition(|item| item.data.resource_index == Some(to))
+                .map_or(chart.items.len(), |last| last + 1);
+
+            // The item it goes before may have followed on from the one
+            // before it
+            if chart
+                .items
+                .get(i)
+                .is_some_and(|next| next.data.start_date.is_none())
+            {
+                let start = schedule::schedule(chart)[i].start;
+
+                chart.items[i].data.start_date = start;
+            }
+
+            chart.items.insert(i, item);
+        }
+
+        warnings
+    }
+}
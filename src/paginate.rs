@@ -0,0 +1,112 @@
+use crate::{
+    axis,
+    chart::{Chart, Group, Item, NonWorking},
+    schedule,
+};
+use std::path::{Path, PathBuf};
+
+/// Split a chart into pages of at most `max_resources` lanes each. Every
+/// item gets its start date and resource, because the item it followed may
+/// be on another page. `gantt_chart` makes the time axis just wide enough
+/// for the items, so a page that doesn't reach the first or last day of the
+/// whole chart gets an untitled milestone on that day, giving every page the
+/// same axis.
+pub fn pages(chart: &Chart, max_resources: usize) -> Vec<Chart> {
+    let items = schedule::schedule(chart);
+    let first = items.iter().filter_map(|item| item.start).min();
+    let last = items.iter().filter_map(|item| item.end).max();
+    let count = chart.resources.len().div_ceil(max_resources);
+    let mut pages = vec![];
+
+    for page in 0..count {
+        let lanes = page * max_resources..((page + 1) * max_resources).min(chart.resources.len());
+        let mut page_items: Vec<Item> = items
+            .iter()
+            .filter(|item| lanes.contains(&item.resource.unwrap_or(0)))
+            .map(|item| {
+                let mut page_item = item.item.clone();
+
+                page_item.data.start_date = item.start;
+                page_item.data.resource_index = Some(item.resource.unwrap_or(0) - lanes.start);
+                page_item
+            })
+            .collect();
+        let page_first = page_items
+            .iter()
+            .filter_map(|item| item.data.start_date)
+            .min();
+        let page_last = items
+            .iter()
+            .filter(|item| lanes.contains(&item.resource.unwrap_or(0)))
+            .filter_map(|item| item.end)
+            .max();
+
+        if let Some(first) = first.filter(|first| page_first != Some(*first)) {
+            page_items.insert(0, axis::milestone("axis-start", first, 0));
+        }
+
+        if let Some(last) = last.filter(|last| page_last != Some(*last)) {
+            page_items.push(axis::milestone("axis-end", last, lanes.len() - 1));
+        }
+
+        pages.push(Chart {
+            title: format!("{} ({}/{})", chart.title, page + 1, count)
+                .trim_start()
+                .to_owned(),
+            marked_date: chart.marked_date,
+            resources: chart.resources[lanes.clone()].to_vec(),
+            items: page_items,
+            groups: chart
+                .groups
+                .iter()
+                .filter_map(|group| {
+                    let resources: Vec<usize> = group
+                        .resources
+                        .iter()
+                        .filter(|index| lanes.contains(index))
+                        .map(|index| index - lanes.start)
+                        .collect();
+
+                    (!resources.is_empty()).then(|| Group {
+                        name: group.name.to_owned(),
+                        color: group.color.to_owned(),
+                        resources,
+                    })
+                })
+                .collect(),
+            sprints: chart.sprints.clone(),
+            non_working: chart
+                .non_working
+                .iter()
+                .filter_map(|days| {
+                    let resources: Vec<usize> = days
+                        .resources
+                        .iter()
+                        .filter(|index| lanes.contains(index))
+                        .map(|index| index - lanes.start)
+                        .collect();
+
+                    (days.resources.is_empty() || !resources.is_empty()).then(|| NonWorking {
+                        resources,
+                        ..days.clone()
+                    })
+                })
+                .collect(),
+            units: chart.units.clone(),
+        });
+    }
+
+    pages
+}
+
+/// The file for a page of a chart, e.g. `plan-2.json5` for the second page
+/// of `plan.json5`
+pub fn page_path(path: &Path, page: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, page, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, page),
+    };
+
+    path.with_file_name(name)
+}
@@ -0,0 +1,122 @@
+use crate::columns::SourceSite;
+use easy_error::{self, ResultExt};
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A portfolio combines several named sources into a single chart
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PortfolioConfig {
+    #[serde(default)]
+    pub title: String,
+    /// How many sources to fetch at once, unless `--concurrency` is given
+    pub concurrency: Option<usize>,
+    /// A CSV file of `key,canonical` rows for issues that are in more than
+    /// one source, relative to the portfolio file
+    #[serde(rename = "keyMap")]
+    pub key_map: Option<PathBuf>,
+    pub sources: Vec<PortfolioSource>,
+}
+
+/// One source of issues in a portfolio. Each source becomes a group of lanes.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PortfolioSource {
+    pub name: String,
+    /// Prefix for the lane names of this source, defaulting to the name
+    pub prefix: Option<String>,
+    /// Color hint for the lanes of this source, e.g. `#3366cc`
+    pub color: Option<String>,
+    /// A Jira CSV export, relative to the portfolio file
+    pub file: Option<PathBuf>,
+    /// A Jira board to fetch the active sprints and backlog of
+    pub board: Option<u64>,
+    /// A JQL query to fetch the issues of, in the order it sorts them
+    pub jql: Option<String>,
+    /// A saved Jira filter to fetch the issues of
+    pub filter: Option<u64>,
+    /// The tool that produced the export, if different from `--source`, or
+    /// the site in the config file to fetch from, e.g. `jira:acme`
+    pub source: Option<SourceSite>,
+    /// Prefix for issue keys that are only a number, e.g. `GH` to make `123`
+    /// into `GH#123`, defaulting to the lane prefix
+    #[serde(rename = "keyPrefix")]
+    pub key_prefix: Option<String>,
+}
+
+/// What a portfolio source fetches from Jira
+#[derive(Clone, Copy, Debug)]
+pub enum Fetch<'a> {
+    Board(u64),
+    Filter(u64),
+    Jql(&'a str),
+}
+
+impl PortfolioConfig {
+    pub fn load(path: &Path) -> Result<PortfolioConfig, Box<dyn Error>> {
+        let content = fs::read_to_string(path).context(format!(
+            "Unable to read portfolio file '{}'",
+            path.to_string_lossy()
+        ))?;
+        let mut config: PortfolioConfig = json5::from_str(&content)?;
+
+        if config.sources.is_empty() {
+            return Err(From::from(format!(
+                "Portfolio file '{}' contains no sources",
+                path.to_string_lossy()
+            )));
+        }
+
+        // Make file paths relative to the portfolio file
+        if let Some(dir) = path.parent() {
+            if let Some(ref file) = config.key_map {
+                if file.is_relative() {
+                    config.key_map = Some(dir.join(file));
+                }
+            }
+
+            for source in config.sources.iter_mut() {
+                if let Some(ref file) = source.file {
+                    if file.is_relative() {
+                        source.file = Some(dir.join(file));
+                    }
+                }
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+impl PortfolioSource {
+    pub fn prefix(&self) -> &str {
+        self.prefix.as_deref().unwrap_or(&self.name)
+    }
+
+    /// What the source fetches from Jira, or `None` for an export
+    pub fn fetch(&self) -> Option<Fetch<'_>> {
+        if let Some(board) = self.board {
+            Some(Fetch::Board(board))
+        } else if let Some(filter) = self.filter {
+            Some(Fetch::Filter(filter))
+        } else {
+            self.jql.as_deref().map(Fetch::Jql)
+        }
+    }
+
+    /// The site in the config file the source is fetched from, if it isn't
+    /// the one of `--jira-url`
+    pub fn site(&self) -> Option<&str> {
+        self.source
+            .as_ref()
+            .and_then(|source| source.site.as_deref())
+    }
+
+    pub fn key_prefix(&self) -> &str {
+        self.key_prefix.as_deref().unwrap_or(self.prefix())
+    }
+}
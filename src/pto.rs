@@ -0,0 +1,435 @@
+use crate::{
+    chart::{Chart, NonWorking, Units},
+    config::Config,
+    hours::{Granularity, WorkingHours},
+    schedule,
+};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+use easy_error::{self, ResultExt};
+use gantt_chart::ItemData;
+use std::{
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The days each resource is away, read from iCalendar files, and the
+/// working calendar of weekends and holidays that everyone has off
+#[derive(Default)]
+pub struct Pto {
+    resources: HashMap<String, BTreeSet<NaiveDate>>,
+    holidays: BTreeSet<NaiveDate>,
+    /// The days of the week that are the weekend
+    weekend: Vec<Weekday>,
+    /// Whether weekends are days off too, so work only counts weekdays
+    weekends_off: bool,
+}
+
+impl Pto {
+    /// Load time off from `resource=calendar.ics` pairs, or directories of
+    /// calendars each named after its resource, e.g. `alice.ics`, and
+    /// holidays from calendar files and the config file. With `weekends_off`
+    /// nobody works the config file's weekend days either.
+    pub fn load(
+        specs: &[String],
+        holidays: &[PathBuf],
+        config: &Config,
+        weekends_off: bool,
+    ) -> Result<Pto, Box<dyn Error>> {
+        let mut pto = Pto {
+            holidays: config.holidays.iter().copied().collect(),
+            weekend: config.weekend_days(),
+            weekends_off,
+            ..Pto::default()
+        };
+
+        for path in holidays.iter() {
+            let days = read_calendar(path)?;
+
+            pto.holidays.extend(days);
+        }
+
+        for spec in specs.iter() {
+            if let Some((resource, file)) = spec.split_once('=') {
+                pto.add(resource.trim(), Path::new(file))?;
+            } else if Path::new(spec).is_dir() {
+                for entry in fs::read_dir(spec)
+                    .context(format!("Unable to read time off directory '{}'", spec))?
+                {
+                    let path = entry?.path();
+
+                    if path
+                        .extension()
+                        .is_some_and(|e| e.eq_ignore_ascii_case("ics"))
+                    {
+                        if let Some(resource) = path.file_stem() {
+                            pto.add(&resource.to_string_lossy(), &path)?;
+                        }
+                    }
+                }
+            } else {
+                return Err(From::from(format!(
+                    "'{}' must be a directory of calendars, or a resource and calendar file, e.g. alice=alice.ics",
+                    spec
+                )));
+            }
+        }
+
+        Ok(pto)
+    }
+
+    fn add(&mut self, resource: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+        let days = read_calendar(path)?;
+
+        self.resources
+            .entry(resource.to_owned())
+            .or_default()
+            .extend(days);
+
+        Ok(())
+    }
+
+    /// The days off of a chart resource. Portfolio resources are also
+    /// matched on their lane name without the source prefix.
+    fn days_off(&self, resource: &str) -> Option<&BTreeSet<NaiveDate>> {
+        self.resources.get(resource).or_else(|| {
+            resource
+                .rsplit_once(": ")
+                .and_then(|(_, lane)| self.resources.get(lane))
+        })
+    }
+
+    fn is_weekend(&self, date: NaiveDate) -> bool {
+        self.weekend.contains(&date.weekday())
+    }
+
+    /// Whether a day is a holiday or one of a resource's days off. Unless
+    /// weekends are off, durations are in calendar days, so days off at a
+    /// weekend take nothing away from the work.
+    fn is_day_off(&self, days_off: Option<&BTreeSet<NaiveDate>>, date: NaiveDate) -> bool {
+        if self.is_weekend(date) {
+            return self.weekends_off;
+        }
+
+        self.holidays.contains(&date) || days_off.is_some_and(|days| days.contains(&date))
+    }
+
+    /// Whether a resource works on a day in working hours, which are never
+    /// at the weekend, whether or not weekends are days off
+    pub fn is_working_day(&self, resource: &str, date: NaiveDate) -> bool {
+        !self.is_weekend(date) && !self.is_day_off(self.days_off(resource), date)
+    }
+
+    /// The days of each item that are worked, leaving out the days off it
+    /// is drawn over, for items that were fitted around them already
+    pub fn worked_days(&self, chart: &Chart) -> Vec<Option<i64>> {
+        let mut date: Option<NaiveDate> = None;
+        let mut resource: Option<usize> = None;
+
+        chart
+            .items
+            .iter()
+            .map(|item| {
+                if item.data.start_date.is_some() {
+                    date = item.data.start_date;
+                }
+
+                if item.data.resource_index.is_some() {
+                    resource = item.data.resource_index;
+                }
+
+                let days_off = resource
+                    .and_then(|index| chart.resources.get(index))
+                    .and_then(|name| self.days_off(name));
+                let worked = match (date, item.data.duration) {
+                    (Some(start), Some(days)) => Some(
+                        (0..days)
+                            .filter(|day| !self.is_day_off(days_off, start + Duration::days(*day)))
+                            .count() as i64,
+                    ),
+                    (_, days) => days,
+                };
+
+                if let Some(days) = item.data.duration {
+                    date = date.map(|date| date + Duration::days(days));
+                }
+
+                worked
+            })
+            .collect()
+    }
+
+    /// Move items that start on a day off to the next working day, and make
+    /// each item long enough to fit its work around the days off. `work` is
+    /// the duration of each item before time off was taken into account, so
+    /// this can be applied again after the items have moved. Returns whether
+    /// anything changed.
+    pub fn apply(&self, chart: &mut Chart, work: &[Option<i64>]) -> bool {
+        if self.resources.is_empty() && self.holidays.is_empty() && !self.weekends_off {
+            return false;
+        }
+
+        let mut changed = false;
+        // Where each item is drawn, worked out the way `schedule::schedule`
+        // does as the items before it move and stretch
+        let mut date: Option<NaiveDate> = None;
+        let mut resource: Option<usize> = None;
+
+        for (item, work) in chart.items.iter_mut().zip(work.iter()) {
+            if item.data.start_date.is_some() {
+                date = item.data.start_date;
+            }
+
+            if item.data.resource_index.is_some() {
+                resource = item.data.resource_index;
+            }
+
+            let days_off = resource
+                .and_then(|index| chart.resources.get(index))
+                .and_then(|name| self.days_off(name));
+
+            if !item.has_fixed_dates() && self.fit(&mut item.data, date, days_off, *work) {
+                changed = true;
+                date = item.data.start_date.or(date);
+            }
+
+            if let Some(days) = item.data.duration {
+                date = date.map(|date| date + Duration::days(days));
+            }
+        }
+
+        changed
+    }
+
+    /// Move an item that starts on a day off to the next working day, and
+    /// make it long enough to fit its work around the days off. Returns
+    /// whether it changed.
+    fn fit(
+        &self,
+        item: &mut ItemData,
+        start: Option<NaiveDate>,
+        days_off: Option<&BTreeSet<NaiveDate>>,
+        work: Option<i64>,
+    ) -> bool {
+        if days_off.is_none() && self.holidays.is_empty() && !self.weekends_off {
+            return false;
+        }
+
+        let (mut start, work) = match (start, work) {
+            (Some(start), Some(work)) if work > 0 => (start, work),
+            _ => return false,
+        };
+        let mut changed = false;
+
+        if self.is_day_off(days_off, start) {
+            while self.is_day_off(days_off, start) {
+                start += Duration::days(1);
+            }
+
+            item.start_date = Some(start);
+            changed = true;
+        }
+
+        let mut duration = 0;
+        let mut remaining = work;
+
+        while remaining > 0 {
+            if !self.is_day_off(days_off, start + Duration::days(duration)) {
+                remaining -= 1;
+            }
+
+            duration += 1;
+        }
+
+        if item.duration != Some(duration) {
+            item.duration = Some(duration);
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// The units of a chart scheduled with this calendar
+    pub fn units(
+        &self,
+        granularity: Granularity,
+        working_hours: &WorkingHours,
+        hours_per_day: f64,
+    ) -> Units {
+        Units {
+            duration: "calendarDays".to_owned(),
+            granularity,
+            hours_per_day,
+            working_hours: (granularity == Granularity::Hours).then(|| working_hours.to_string()),
+            working_days: self.weekends_off,
+            weekend_days: self.weekend.clone(),
+            holidays: self.holidays.iter().copied().collect(),
+            time_off: !self.resources.is_empty(),
+        }
+    }
+
+    /// Add the weekends, holidays and time off from the first to the last day
+    /// of the chart's items, so renderers can show why bars stretch over them
+    pub fn shade(&self, chart: &mut Chart) {
+        let (first, last) = {
+            let items = schedule::schedule(chart);
+
+            match (
+                items.iter().filter_map(|item| item.start).min(),
+                items.iter().filter_map(|item| item.last_day()).max(),
+            ) {
+                (Some(first), Some(last)) => (first, last),
+                _ => return,
+            }
+        };
+        let days: Vec<NaiveDate> = first.iter_days().take_while(|day| *day <= last).collect();
+
+        chart
+            .non_working
+            .extend(runs(&days, "weekend", vec![], |day| self.is_weekend(day)));
+        chart
+            .non_working
+            .extend(runs(&days, "holiday", vec![], |day| {
+                !self.is_weekend(day) && self.holidays.contains(&day)
+            }));
+
+        for (index, resource) in chart.resources.iter().enumerate() {
+            if let Some(days_off) = self.days_off(resource) {
+                chart
+                    .non_working
+                    .extend(runs(&days, "time-off", vec![index], |day| {
+                        !self.is_weekend(day)
+                            && !self.holidays.contains(&day)
+                            && days_off.contains(&day)
+                    }));
+            }
+        }
+    }
+}
+
+/// The runs of consecutive days that something holds for
+fn runs(
+    days: &[NaiveDate],
+    kind: &str,
+    resources: Vec<usize>,
+    holds: impl Fn(NaiveDate) -> bool,
+) -> Vec<NonWorking> {
+    let mut runs: Vec<NonWorking> = vec![];
+
+    for day in days.iter().copied().filter(|day| holds(*day)) {
+        match runs.last_mut() {
+            Some(run) if run.end_date + Duration::days(1) == day => run.end_date = day,
+            _ => runs.push(NonWorking {
+                start_date: day,
+                end_date: day,
+                kind: kind.to_owned(),
+                resources: resources.clone(),
+            }),
+        }
+    }
+
+    runs
+}
+
+fn read_calendar(path: &Path) -> Result<BTreeSet<NaiveDate>, Box<dyn Error>> {
+    let content = fs::read_to_string(path).context(format!(
+        "Unable to read calendar file '{}'",
+        path.to_string_lossy()
+    ))?;
+
+    parse_calendar(&content).map_err(|err| {
+        From::from(format!(
+            "Invalid calendar file '{}': {}",
+            path.to_string_lossy(),
+            err
+        ))
+    })
+}
+
+/// The days covered by the events of an iCalendar file. Cancelled events
+/// are ignored, and so are recurrence rules.
+fn parse_calendar(content: &str) -> Result<BTreeSet<NaiveDate>, String> {
+    // Long lines are folded by starting the continuation with a space or tab
+    let mut lines: Vec<String> = vec![];
+
+    for line in content.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+
+    let mut days = BTreeSet::new();
+    let mut event: Option<(Option<Date>, Option<Date>, bool)> = None;
+
+    for line in lines.iter() {
+        let (name, value) = match line.split_once(':') {
+            Some((name, value)) => (name, value.trim()),
+            None => continue,
+        };
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+
+        match (name.to_ascii_uppercase().as_str(), event.as_mut()) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some((None, None, false))
+            }
+            ("END", Some((start, end, cancelled))) if value.eq_ignore_ascii_case("VEVENT") => {
+                if !*cancelled {
+                    let start = start.ok_or("an event has no DTSTART")?;
+
+                    days.extend(event_days(start, *end));
+                }
+
+                event = None;
+            }
+            ("DTSTART", Some((start, _, _))) => *start = Some(parse_date(value, params)?),
+            ("DTEND", Some((_, end, _))) => *end = Some(parse_date(value, params)?),
+            ("STATUS", Some((_, _, cancelled))) => {
+                *cancelled = value.eq_ignore_ascii_case("CANCELLED")
+            }
+            _ => (),
+        }
+    }
+
+    Ok(days)
+}
+
+#[derive(Clone, Copy)]
+enum Date {
+    /// An all-day date
+    Day(NaiveDate),
+    Time(NaiveDateTime),
+}
+
+fn parse_date(value: &str, params: &str) -> Result<Date, String> {
+    let value = value.trim_end_matches('Z');
+
+    if params.to_ascii_uppercase().contains("VALUE=DATE") && !value.contains('T') {
+        NaiveDate::parse_from_str(value, "%Y%m%d").map(Date::Day)
+    } else {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .map(Date::Time)
+            .or_else(|_| NaiveDate::parse_from_str(value, "%Y%m%d").map(Date::Day))
+    }
+    .map_err(|_| format!("invalid date '{}'", value))
+}
+
+/// The days an event covers. The end of an all-day event is the day after
+/// its last day, and an event that ends at midnight doesn't cover the next
+/// day.
+fn event_days(start: Date, end: Option<Date>) -> Vec<NaiveDate> {
+    let (first, last) = match (start, end) {
+        (Date::Day(start), Some(Date::Day(end))) => (start, end - Duration::days(1)),
+        (Date::Day(start), _) => (start, start),
+        (Date::Time(start), Some(Date::Time(end))) => {
+            (start.date(), (end - Duration::seconds(1)).date())
+        }
+        (Date::Time(start), _) => (start.date(), start.date()),
+    };
+
+    first
+        .iter_days()
+        .take_while(|day| *day <= last.max(first))
+        .collect()
+}
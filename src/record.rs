@@ -0,0 +1,485 @@
+use crate::{
+    chart::{Period, Sprint},
+    columns::{ColumnIndex, Field},
+    config::Config,
+    i18n::Translations,
+    number::{self, NumberFormat},
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::ValueEnum;
+use csv::StringRecord;
+use serde_json::Value;
+use std::error::Error;
+
+const TOOLTIP_DESCRIPTION_LENGTH: usize = 200;
+pub const JIRA_DATE_TIME_FORMAT: &str = "%-d/%b/%y %I:%M %p";
+// The other date time formats Jira can be set up to export, and the ISO 8601
+// style date times of other trackers. Day first formats are tried before
+// month first ones, which are only told apart by AM and PM.
+const OTHER_DATE_TIME_FORMATS: [&str; 13] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%d %I:%M %p",
+    "%-d/%b/%y %H:%M",
+    "%d/%b/%Y %I:%M %p",
+    "%d/%b/%Y %H:%M",
+    "%d/%m/%Y %H:%M",
+    "%d.%m.%Y %H:%M",
+    "%m/%d/%Y %I:%M %p",
+    "%d %b %Y %H:%M",
+    "%b %d, %Y %I:%M %p",
+];
+const PLAN_DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%-d/%b/%y", "%d/%b/%Y"];
+
+/// A link from an issue to another, e.g. one that it blocks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueLink {
+    /// The name of the link type, e.g. `Blocks`
+    pub kind: String,
+    /// Whether the link goes out from the issue, e.g. the issue blocks the
+    /// other one rather than being blocked by it
+    pub outward: bool,
+    pub key: String,
+}
+
+/// One issue read from an export
+#[derive(Debug, Clone, Default)]
+pub struct JiraRecord {
+    pub key: String,
+    pub summary: String,
+    pub status: String,
+    pub assignee: String,
+    /// Whether Jira says the assignee's account is deactivated
+    pub assignee_inactive: bool,
+    pub original_estimate: Option<u32>,
+    /// The work still to do, in seconds
+    pub remaining_estimate: Option<u32>,
+    pub story_points: Option<f64>,
+    /// The work logged so far, in seconds
+    pub time_spent: Option<u32>,
+    pub created: Option<NaiveDate>,
+    /// When the issue was resolved
+    pub resolved: Option<NaiveDate>,
+    /// When the issue last moved to another status category, e.g. from To
+    /// Do to In Progress
+    pub status_changed: Option<NaiveDate>,
+    /// When work on the issue started, from a custom field
+    pub started: Option<NaiveDate>,
+    pub description: String,
+    pub target_start: Option<NaiveDate>,
+    pub target_end: Option<NaiveDate>,
+    pub due_date: Option<NaiveDate>,
+    pub team: String,
+    pub sprint: String,
+    /// The planned dates of the sprint, when the export or API gives them
+    pub sprint_start: Option<NaiveDate>,
+    pub sprint_end: Option<NaiveDate>,
+    /// The sprints the issue was carried over from, in the order listed
+    pub earlier_sprints: Vec<Sprint>,
+    /// The releases the issue is to go out in
+    pub fix_versions: Vec<String>,
+    /// The time left to resolve the issue within its SLA, in seconds, which
+    /// is negative once it has been breached
+    pub sla_remaining: Option<i64>,
+    pub issue_type: String,
+    pub epic: String,
+    /// The risk, e.g. `high`, in lower case
+    pub risk: String,
+    pub labels: Vec<String>,
+    /// The components of the project the issue belongs to, e.g. `API`
+    pub components: Vec<String>,
+    /// When the issue was flagged or blocked, from its changelog
+    pub blocked: Vec<Period>,
+    pub links: Vec<IssueLink>,
+    /// The name of the project the issue is in
+    pub project: String,
+}
+
+/// What the lanes of the chart are
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One lane per person, or per team for plans that assign teams
+    Assignee,
+    /// One lane per issue type, e.g. Bug, Story or Task
+    IssueType,
+    /// One lane per epic, or parent issue
+    Epic,
+    /// One lane per sprint
+    Sprint,
+    /// Committed scope and stretch scope, from --stretch-label
+    Commitment,
+}
+
+impl GroupBy {
+    /// The translation key of the name for issues with no value
+    pub fn empty_label(&self) -> &'static str {
+        match self {
+            GroupBy::Assignee => "unassigned",
+            GroupBy::IssueType => "no-issue-type",
+            GroupBy::Epic => "no-epic",
+            GroupBy::Sprint => "no-sprint",
+            GroupBy::Commitment => "committed",
+        }
+    }
+}
+
+impl JiraRecord {
+    /// Read an issue from a row of an export. The config's date formats are
+    /// tried before the usual formats for dates and date times, and its
+    /// working day is the length of a day in estimates such as `2d`.
+    pub fn from_row(
+        index: &ColumnIndex,
+        row: &StringRecord,
+        numbers: NumberFormat,
+        config: &Config,
+    ) -> Result<JiraRecord, Box<dyn Error>> {
+        let date_formats = &config.date_formats;
+        let key = index.get(row, Field::Key).to_owned();
+        let seconds = |field: Field, name: &str| -> Result<Option<u32>, String> {
+            match index.get(row, field) {
+                "" => Ok(None),
+                s => number::parse_number(s, numbers)
+                    .map(|amount| amount * index.estimate_unit as f64)
+                    .or_else(|| number::parse_duration(s, config.day_seconds()))
+                    .filter(|seconds| *seconds >= 0.0 && *seconds <= u32::MAX as f64)
+                    .map(|seconds| Some(seconds.round() as u32))
+                    .ok_or_else(|| format!("Invalid {} '{}' for issue '{}'", name, s, key)),
+            }
+        };
+        let original_estimate = seconds(Field::OriginalEstimate, "Original Estimate")?;
+        let remaining_estimate = seconds(Field::RemainingEstimate, "Remaining Estimate")?;
+        let story_points = match index.get(row, Field::StoryPoints) {
+            "" => None,
+            s => Some(
+                number::parse_number(s, numbers)
+                    .filter(|points| *points >= 0.0)
+                    .ok_or_else(|| format!("Invalid Story Points '{}' for issue '{}'", s, key))?,
+            ),
+        };
+        let time_spent = seconds(Field::TimeSpent, "Time Spent")?;
+        let created = Self::parse_date_time(
+            index.get(row, Field::Created),
+            "Created",
+            &key,
+            date_formats,
+        )?;
+        let resolved = Self::parse_date_time(
+            index.get(row, Field::Resolved),
+            "Resolved",
+            &key,
+            date_formats,
+        )?;
+        let status_changed = Self::parse_date_time(
+            index.get(row, Field::StatusChanged),
+            "Status Category Changed",
+            &key,
+            date_formats,
+        )?;
+        let started = Self::parse_date_time(
+            index.get(row, Field::Started),
+            "Start date",
+            &key,
+            date_formats,
+        )?;
+        // An issue carried over into later sprints is in the last one listed
+        let mut sprints: Vec<_> = index
+            .get_all(row, Field::Sprint)
+            .iter()
+            .map(|sprint| Self::parse_sprint(sprint))
+            .collect();
+        let (sprint, sprint_start, sprint_end) = sprints.pop().unwrap_or_default();
+        let earlier_sprints = sprints
+            .into_iter()
+            .map(|(name, start_date, end_date)| Sprint {
+                name,
+                start_date,
+                end_date,
+            })
+            .collect();
+        let target_start =
+            Self::parse_plan_date(index.get(row, Field::TargetStart), &key, date_formats)?;
+        let target_end =
+            Self::parse_plan_date(index.get(row, Field::TargetEnd), &key, date_formats)?;
+        let due_date = Self::parse_plan_date(index.get(row, Field::DueDate), &key, date_formats)?;
+        let sla_remaining = match index.get(row, Field::SlaRemaining) {
+            "" => None,
+            s => Some(Self::parse_sla(s).ok_or_else(|| {
+                format!("Invalid Time to resolution '{}' for issue '{}'", s, key)
+            })?),
+        };
+
+        Ok(JiraRecord {
+            summary: index.get(row, Field::Summary).to_owned(),
+            status: index.get(row, Field::Status).to_owned(),
+            assignee: index.get(row, Field::Assignee).to_owned(),
+            assignee_inactive: false,
+            original_estimate,
+            remaining_estimate,
+            story_points,
+            time_spent,
+            created,
+            resolved,
+            status_changed,
+            started,
+            description: index.get(row, Field::Description).to_owned(),
+            target_start,
+            target_end,
+            due_date,
+            team: index.get(row, Field::Team).to_owned(),
+            issue_type: index.get(row, Field::IssueType).to_owned(),
+            epic: index.get(row, Field::Epic).to_owned(),
+            risk: index.get(row, Field::Risk).to_lowercase(),
+            labels: index
+                .get_all(row, Field::Labels)
+                .iter()
+                .flat_map(|labels| labels.split([',', ' ']))
+                .filter(|label| !label.is_empty())
+                .map(|label| label.to_owned())
+                .collect(),
+            components: index
+                .get_all(row, Field::Components)
+                .iter()
+                .flat_map(|components| components.split(','))
+                .map(|component| component.trim())
+                .filter(|component| !component.is_empty())
+                .map(|component| component.to_owned())
+                .collect(),
+            sprint,
+            sprint_start,
+            sprint_end,
+            earlier_sprints,
+            fix_versions: index
+                .get_all(row, Field::FixVersions)
+                .iter()
+                .map(|version| version.to_string())
+                .collect(),
+            sla_remaining,
+            blocked: vec![],
+            links: index.get_links(row),
+            project: index.get(row, Field::Project).to_owned(),
+            key,
+        })
+    }
+
+    /// Parse the date of a date time column such as `Created`, which may
+    /// also be just a date
+    fn parse_date_time(
+        s: &str,
+        name: &str,
+        key: &str,
+        date_formats: &[String],
+    ) -> Result<Option<NaiveDate>, String> {
+        if s.is_empty() {
+            return Ok(None);
+        }
+
+        date_formats
+            .iter()
+            .map(|format| format.as_str())
+            .chain(std::iter::once(JIRA_DATE_TIME_FORMAT))
+            .chain(OTHER_DATE_TIME_FORMATS)
+            .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+            .map(|dt| dt.date())
+            .or_else(|| Self::parse_plan_date(s, key, date_formats).ok().flatten())
+            .map(Some)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid {} date '{}' for issue '{}', which isn't in a known format (give one with --date-format)",
+                    name, s, key
+                )
+            })
+    }
+
+    /// Parse a sprint, which is either its name or, from older Jira servers,
+    /// a serialized object like
+    /// `com.atlassian.greenhopper.service.sprint.Sprint@1a2b[id=1,name=Sprint 1,startDate=2023-01-02T09:00:00.000Z,...]`
+    pub fn parse_sprint(s: &str) -> (String, Option<NaiveDate>, Option<NaiveDate>) {
+        let fields = match (s.find("Sprint@"), s.find('['), s.rfind(']')) {
+            (Some(_), Some(open), Some(close)) if open < close => &s[open + 1..close],
+            _ => return (s.to_owned(), None, None),
+        };
+        // Names can contain commas, so a part without an '=' continues the
+        // value before it
+        let mut values: Vec<(&str, String)> = vec![];
+
+        for part in fields.split(',') {
+            match part.split_once('=') {
+                Some((name, value)) if !name.contains(' ') => values.push((name, value.to_owned())),
+                _ => {
+                    if let Some((_, value)) = values.last_mut() {
+                        value.push(',');
+                        value.push_str(part);
+                    }
+                }
+            }
+        }
+
+        let value = |name: &str| {
+            values
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, value)| value.as_str())
+                .unwrap_or_default()
+        };
+        let date = |name: &str| {
+            value(name)
+                .get(0..10)
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        };
+
+        (value("name").to_owned(), date("startDate"), date("endDate"))
+    }
+
+    /// Parse the time left on an SLA such as `4h 30m`, or `-2h` once it has
+    /// been breached, into seconds. SLA clocks run on calendar days, so a
+    /// day is 24 hours.
+    fn parse_sla(s: &str) -> Option<i64> {
+        let s = s.trim();
+        let (sign, duration) = match s.strip_prefix('-') {
+            Some(rest) => (-1.0, rest),
+            None => (1.0, s),
+        };
+
+        number::parse_duration(duration, 24.0 * 3600.0)
+            .map(|seconds| (sign * seconds).round() as i64)
+    }
+
+    fn parse_plan_date(
+        s: &str,
+        key: &str,
+        date_formats: &[String],
+    ) -> Result<Option<NaiveDate>, Box<dyn Error>> {
+        if s.is_empty() {
+            return Ok(None);
+        }
+
+        date_formats
+            .iter()
+            .map(|format| format.as_str())
+            .chain(PLAN_DATE_FORMATS)
+            .find_map(|format| NaiveDate::parse_from_str(s, format).ok())
+            .or_else(|| {
+                date_formats
+                    .iter()
+                    .map(|format| format.as_str())
+                    .chain(std::iter::once(JIRA_DATE_TIME_FORMAT))
+                    .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+                    .map(|dt| dt.date())
+            })
+            .map(Some)
+            .ok_or_else(|| From::from(format!("Invalid date '{}' for issue '{}'", s, key)))
+    }
+
+    /// The value of a field as it was read, for passing through to the chart
+    /// file. Numbers stay numbers, dates are `yyyy-mm-dd` and fields with
+    /// several values are arrays. Empty fields are `None`.
+    pub fn field(&self, field: Field) -> Option<Value> {
+        let text = |s: &str| (!s.is_empty()).then(|| Value::from(s));
+        let date = |date: Option<NaiveDate>| date.map(|date| Value::from(date.to_string()));
+        let list = |values: &[String]| (!values.is_empty()).then(|| Value::from(values));
+
+        match field {
+            Field::Key => text(&self.key),
+            Field::Summary => text(&self.summary),
+            Field::Status => text(&self.status),
+            Field::Assignee => text(&self.assignee),
+            Field::OriginalEstimate => self.original_estimate.map(Value::from),
+            Field::RemainingEstimate => self.remaining_estimate.map(Value::from),
+            Field::StoryPoints => self.story_points.map(Value::from),
+            Field::TimeSpent => self.time_spent.map(Value::from),
+            Field::Created => date(self.created),
+            Field::Resolved => date(self.resolved),
+            Field::StatusChanged => date(self.status_changed),
+            Field::Started => date(self.started),
+            Field::Description => text(&self.description),
+            Field::TargetStart => date(self.target_start),
+            Field::TargetEnd => date(self.target_end),
+            Field::DueDate => date(self.due_date),
+            Field::Team => text(&self.team),
+            Field::IssueType => text(&self.issue_type),
+            Field::Epic => text(&self.epic),
+            Field::Risk => text(&self.risk),
+            Field::Labels => list(&self.labels),
+            Field::Components => list(&self.components),
+            Field::Sprint => text(&self.sprint),
+            Field::FixVersions => list(&self.fix_versions),
+            Field::SlaRemaining => self.sla_remaining.map(Value::from),
+            Field::Project => text(&self.project),
+        }
+    }
+
+    /// The risk from the risk field, or else from a label such as
+    /// `risk:high`
+    pub fn risk(&self) -> Option<String> {
+        if !self.risk.is_empty() && self.risk != "none" {
+            return Some(self.risk.to_owned());
+        }
+
+        self.labels.iter().find_map(|label| {
+            label
+                .to_lowercase()
+                .strip_prefix("risk:")
+                .filter(|risk| !risk.is_empty())
+                .map(|risk| risk.to_owned())
+        })
+    }
+
+    /// The lane for the issue. Plans assign work to teams, so prefer the team
+    /// over the assignee when there is one.
+    pub fn lane(&self, group_by: GroupBy) -> &str {
+        match group_by {
+            GroupBy::Assignee if self.team.is_empty() => &self.assignee,
+            GroupBy::Assignee => &self.team,
+            GroupBy::IssueType => &self.issue_type,
+            GroupBy::Epic => &self.epic,
+            GroupBy::Sprint => &self.sprint,
+            // Only the stretch label of a chart tells the two apart
+            GroupBy::Commitment => "",
+        }
+    }
+
+    pub fn tooltip(&self, text: &Translations, assignee: &str, duration: Option<i64>) -> String {
+        let mut lines = vec![];
+
+        if self.summary.is_empty() {
+            lines.push(self.key.to_owned());
+        } else {
+            lines.push(format!("{}: {}", self.key, self.summary));
+        }
+
+        lines.push(format!("{}: {}", text.get("status"), self.status));
+        lines.push(format!("{}: {}", text.get("assignee"), assignee));
+
+        if let Some(days) = duration {
+            lines.push(format!(
+                "{}: {}",
+                text.get("estimate"),
+                text.format("days", days)
+            ));
+        }
+
+        if let Some(risk) = self.risk() {
+            lines.push(format!("{}: {}", text.get("risk"), risk));
+        }
+
+        let description = self.description.trim();
+
+        if !description.is_empty() {
+            let mut text: String = description
+                .chars()
+                .take(TOOLTIP_DESCRIPTION_LENGTH)
+                .collect();
+
+            if description.chars().count() > TOOLTIP_DESCRIPTION_LENGTH {
+                text.push('…');
+            }
+
+            lines.push("".to_owned());
+            lines.push(text);
+        }
+
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,83 @@
+use csv::StringRecord;
+use easy_error::{self, ResultExt};
+use std::{
+    cell::RefCell,
+    error::Error,
+    fs::File,
+    io::{self, Write},
+    path::Path,
+};
+
+/// Rows of an export that were skipped or couldn't be read, written to a CSV
+/// file with the reason appended so they can be fixed in the source tracker
+pub struct Rejects {
+    writer: RefCell<csv::Writer<Box<dyn Write>>>,
+    /// The header row last written, as exports in a portfolio can differ
+    headers: RefCell<Option<StringRecord>>,
+    count: RefCell<usize>,
+}
+
+impl Rejects {
+    pub fn create(path: &Path) -> Result<Rejects, Box<dyn Error>> {
+        let file = File::create(path).context(format!(
+            "Unable to create rejects file '{}'",
+            path.to_string_lossy()
+        ))?;
+
+        Ok(Rejects::to(Box::new(file)))
+    }
+
+    /// Rejects that are counted but written nowhere, for checking that an
+    /// export can be read before it is
+    pub fn discard() -> Rejects {
+        Rejects::to(Box::new(io::sink()))
+    }
+
+    fn to(writer: Box<dyn Write>) -> Rejects {
+        Rejects {
+            writer: RefCell::new(csv::WriterBuilder::new().flexible(true).from_writer(writer)),
+            headers: RefCell::new(None),
+            count: RefCell::new(0),
+        }
+    }
+
+    /// Write a row and the reason it was rejected. A header row is written
+    /// first whenever the headers differ from those of the last row.
+    pub fn reject(
+        &self,
+        headers: &StringRecord,
+        row: &StringRecord,
+        reason: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut writer = self.writer.borrow_mut();
+        let mut last_headers = self.headers.borrow_mut();
+
+        if last_headers.as_ref() != Some(headers) {
+            writer.write_record(headers.iter().chain(std::iter::once("Reason")))?;
+            *last_headers = Some(headers.clone());
+        }
+
+        // Pad short rows so the reason is always in the last column
+        let padding = headers.len().saturating_sub(row.len());
+
+        writer.write_record(
+            row.iter()
+                .chain(std::iter::repeat_n("", padding))
+                .chain(std::iter::once(reason)),
+        )?;
+        *self.count.borrow_mut() += 1;
+
+        Ok(())
+    }
+
+    /// The number of rows rejected so far
+    pub fn count(&self) -> usize {
+        *self.count.borrow()
+    }
+
+    pub fn finish(&self) -> Result<(), Box<dyn Error>> {
+        self.writer.borrow_mut().flush()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{chart::Chart, i18n::Translations, schedule};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use core::fmt::Arguments;
+use easy_error::{self, ResultExt};
+use gantt_chart::{GanttChartLog, GanttChartTool};
+use std::{
+    cell::RefCell,
+    env,
+    error::Error,
+    fs, process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Numbers the temporary files, so that charts drawn at the same time don't
+/// share them
+static RENDERS: AtomicUsize = AtomicUsize::new(0);
+
+/// The translation keys of the month headings, by the English names that
+/// `gantt_chart` draws them with
+const MONTHS: [(&str, &str); 12] = [
+    ("Jan", "month-jan"),
+    ("Feb", "month-feb"),
+    ("Mar", "month-mar"),
+    ("Apr", "month-apr"),
+    ("May", "month-may"),
+    ("Jun", "month-jun"),
+    ("Jul", "month-jul"),
+    ("Aug", "month-aug"),
+    ("Sep", "month-sep"),
+    ("Oct", "month-oct"),
+    ("Nov", "month-nov"),
+    ("Dec", "month-dec"),
+];
+
+/// Collects what `gantt_chart` would print, which is only ever an error
+#[derive(Default)]
+struct RenderLog(RefCell<Vec<String>>);
+
+impl GanttChartLog for RenderLog {
+    fn output(&self, args: Arguments) {
+        self.0.borrow_mut().push(args.to_string());
+    }
+
+    fn warning(&self, args: Arguments) {
+        self.0.borrow_mut().push(args.to_string());
+    }
+
+    fn error(&self, args: Arguments) {
+        self.0.borrow_mut().push(args.to_string());
+    }
+}
+
+/// Draw a chart as SVG with `gantt_chart`, with its month headings in the
+/// language of `text` and its days that aren't worked shaded. It only reads and writes files, so the chart goes
+/// through a pair of temporary files.
+pub fn svg(chart: &Chart, text: &Translations) -> Result<String, Box<dyn Error>> {
+    let dir = env::temp_dir();
+    let name = format!(
+        "jira_to_gantt_{}_{}",
+        process::id(),
+        RENDERS.fetch_add(1, Ordering::Relaxed)
+    );
+    let chart_file = dir.join(format!("{}.json5", name));
+    let svg_file = dir.join(format!("{}.svg", name));
+
+    fs::write(&chart_file, json5::to_string(chart)?).context(format!(
+        "Unable to write temporary chart file '{}'",
+        chart_file.to_string_lossy()
+    ))?;
+
+    let log = RenderLog::default();
+    let result = GanttChartTool::new(&log).run([
+        "gantt_chart".into(),
+        chart_file.clone().into_os_string(),
+        "-o".into(),
+        svg_file.clone().into_os_string(),
+    ]);
+    let svg = fs::read_to_string(&svg_file);
+
+    fs::remove_file(&chart_file).ok();
+    fs::remove_file(&svg_file).ok();
+    result.map_err(|err| format!("Unable to draw the chart: {}", err))?;
+
+    let svg = svg.map_err(|_| format!("Unable to draw the chart: {}", log.0.borrow().join(" ")))?;
+
+    Ok(with_month_names(
+        &with_non_working(&with_tooltips(&svg, chart), chart),
+        text,
+    ))
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Translate the month names of the headings, which `gantt_chart` only has
+/// in English
+fn with_month_names(svg: &str, text: &Translations) -> String {
+    let mut out = String::with_capacity(svg.len());
+    let mut rest = svg;
+
+    while let Some(at) = rest.find("<text class=\"heading\"") {
+        let Some(open) = rest[at..].find('>').map(|open| at + open + 1) else {
+            break;
+        };
+        let Some(close) = rest[open..].find("</text>").map(|close| open + close) else {
+            break;
+        };
+        let content = &rest[open..close];
+
+        out.push_str(&rest[..open]);
+
+        match MONTHS.iter().find(|(name, _)| *name == content.trim()) {
+            Some((name, key)) => out.push_str(&content.replacen(name, &escape(text.get(key)), 1)),
+            None => out.push_str(content),
+        }
+
+        rest = &rest[close..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Give the bar or milestone of each item with a tooltip a `<title>`, which
+/// browsers show when hovering over it. `gantt_chart` ignores tooltips, but
+/// it draws one bar or milestone for each item, in the order of the items.
+fn with_tooltips(svg: &str, chart: &Chart) -> String {
+    let mut out = String::with_capacity(svg.len());
+    let mut rest = svg;
+
+    for item in chart.items.iter() {
+        let bar = ["<rect class=\"resource-", "<path class=\"milestone\""]
+            .iter()
+            .filter_map(|start| rest.find(start).map(|at| (at, &start[1..5])))
+            .min();
+        let Some((at, tag)) = bar else {
+            break;
+        };
+        let Some(end) = rest[at..].find("/>").map(|end| at + end) else {
+            break;
+        };
+
+        match item.tooltip {
+            Some(ref tooltip) => {
+                out.push_str(&rest[..end]);
+                out.push_str(&format!("><title>{}</title></{}>", escape(tooltip), tag));
+            }
+            None => out.push_str(&rest[..end + 2]),
+        }
+
+        rest = &rest[end + 2..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// The number in an attribute of an element, e.g. `x1="220"`
+fn attribute(element: &str, name: &str) -> Option<f32> {
+    let start = element.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let end = element[start..].find('"')? + start;
+
+    element[start..end].parse().ok()
+}
+
+/// Shade the days of the chart's `nonWorking` behind the bars, which
+/// `gantt_chart` ignores. Its columns are the months from the one of the
+/// first start date, between vertical lines, and it draws one row for each
+/// item, in the order of the items, between horizontal lines. Time off only
+/// shades the rows of the items of the resources that are off.
+fn with_non_working(svg: &str, chart: &Chart) -> String {
+    let first = chart
+        .items
+        .iter()
+        .filter_map(|item| item.data.start_date)
+        .min()
+        .and_then(|date| date.with_day(1));
+    let Some(first) = first.filter(|_| !chart.non_working.is_empty()) else {
+        return svg.to_owned();
+    };
+    let mut columns: Vec<f32> = vec![];
+    let mut rows: Vec<f32> = vec![];
+
+    for (at, _) in svg.match_indices("<line ") {
+        let Some(line) = svg[at..].find("/>").map(|end| &svg[at..at + end]) else {
+            break;
+        };
+        let (Some(x1), Some(y1), Some(x2), Some(y2)) = (
+            attribute(line, "x1"),
+            attribute(line, "y1"),
+            attribute(line, "x2"),
+            attribute(line, "y2"),
+        ) else {
+            continue;
+        };
+
+        if y1 == y2 {
+            rows.push(y1);
+        } else if x1 == x2 && line.contains("class=\"inner-lines\"") {
+            columns.push(x1);
+        }
+    }
+
+    columns.sort_by(f32::total_cmp);
+    columns.dedup();
+    rows.sort_by(f32::total_cmp);
+    rows.dedup();
+
+    let (Some(top), Some(bottom)) = (rows.first(), rows.last()) else {
+        return svg.to_owned();
+    };
+    let months = columns.len().saturating_sub(1) as u32;
+    let end = first + Months::new(months);
+    // Where a day starts, or the end of the axis for the day after it
+    let x = |date: NaiveDate| {
+        let month = (date.year() - first.year()) * 12 + date.month() as i32 - first.month() as i32;
+        let month = month as usize;
+
+        if month + 1 >= columns.len() {
+            return columns[columns.len() - 1];
+        }
+
+        let start = first + Months::new(month as u32);
+        let days = ((start + Months::new(1)) - start).num_days() as f32;
+
+        columns[month] + (columns[month + 1] - columns[month]) * date.day0() as f32 / days
+    };
+    let items = schedule::schedule(chart);
+    let mut rects = vec![];
+
+    for non_working in chart.non_working.iter() {
+        let start = non_working.start_date.max(first);
+        let after = (non_working.end_date + Duration::days(1)).min(end);
+
+        if start >= after {
+            continue;
+        }
+
+        let spans: Vec<(f32, f32)> = if non_working.resources.is_empty() {
+            vec![(*top, *bottom)]
+        } else {
+            items
+                .iter()
+                .zip(rows.windows(2))
+                .filter(|(item, _)| {
+                    item.resource
+                        .is_some_and(|resource| non_working.resources.contains(&resource))
+                })
+                .map(|(_, row)| (row[0], row[1]))
+                .collect()
+        };
+
+        for (y, y_end) in spans {
+            rects.push(format!(
+                "<rect class=\"non-working {}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+                non_working.kind,
+                x(start),
+                y,
+                x(after) - x(start),
+                y_end - y
+            ));
+        }
+    }
+
+    let Some(at) = svg.find("</style>").filter(|_| !rects.is_empty()) else {
+        return svg.to_owned();
+    };
+    let after_style = at + "</style>".len();
+
+    // Drawn first, so that everything else is on top
+    format!(
+        "{}.non-working{{fill:#888888;fill-opacity:0.15;}}\n{}\n<g>{}</g>{}",
+        &svg[..at],
+        &svg[at..after_style],
+        rects.join(""),
+        &svg[after_style..]
+    )
+}
@@ -0,0 +1,946 @@
+use crate::{
+    availability::{self, Availability},
+    chart::{Chart, DurationSource},
+    deps::Dependency,
+    health,
+    i18n::Translations,
+    pto::Pto,
+    schedule::{self, ScheduledItem},
+};
+use chrono::{Datelike, Duration, NaiveDate};
+use clap::ValueEnum;
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+/// A report about the chart, written alongside it
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportKind {
+    /// Totals for the chart and each resource, and each resource's load for
+    /// each week against its capacity
+    Summary,
+    /// The open items of each resource that aren't waiting on another open
+    /// item, in the order they are scheduled
+    NextUp,
+    /// The open items that have started, with the share of their days that
+    /// have gone and of their work that is done
+    Health,
+    /// The days each issue and each resource spent flagged or blocked
+    Blocked,
+    /// The open issues whose assignee has left, by former assignee
+    Reassign,
+    /// The days scheduled for each resource in each ISO week, with a column
+    /// for each week, for capacity spreadsheets
+    WorkloadByWeek,
+    /// The open issues scheduled to finish after their SLA runs out, or
+    /// that have already breached it, soonest due first
+    Sla,
+}
+
+/// How the tables of reports are written
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Columns padded to line up
+    Text,
+    Csv,
+    Markdown,
+}
+
+/// The tables of the summary besides its table of resources. A CSV file
+/// holds one table, so in CSV each of these goes in a file of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SummaryTable {
+    /// When each epic can finish against when it is due
+    Epics,
+    /// Each resource's load for each week against its capacity
+    Capacity,
+}
+
+impl SummaryTable {
+    pub const ALL: [SummaryTable; 2] = [SummaryTable::Epics, SummaryTable::Capacity];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SummaryTable::Epics => "epics",
+            SummaryTable::Capacity => "capacity",
+        }
+    }
+}
+
+/// How `--print-schedule` lays out the schedule
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScheduleFormat {
+    /// Columns padded to line up
+    Table,
+    /// Tab separated values, for piping into other tools
+    Tsv,
+}
+
+/// Lay out rows as a table with a header row and columns padded to line up
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+
+    for row in rows.iter() {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_owned()
+    };
+    let mut lines = vec![line(headers.to_vec())];
+
+    lines.extend(
+        rows.iter()
+            .map(|row| line(row.iter().map(|s| s.as_str()).collect())),
+    );
+    lines.join("\n")
+}
+
+/// The working days of a resource that an item covers, by the Monday of
+/// each week. `gantt_chart` draws durations as calendar days, so weekends,
+/// holidays and time off inside an item aren't counted as scheduled work.
+fn working_days_by_week(item: &ScheduledItem, resource: &str, pto: &Pto) -> Vec<(NaiveDate, f64)> {
+    let mut weeks: Vec<(NaiveDate, f64)> = vec![];
+
+    if let (Some(start), Some(days)) = (item.start, item.item.data.duration) {
+        for day in 0..days.max(0) {
+            let date = start + Duration::days(day);
+
+            if !pto.is_working_day(resource, date) {
+                continue;
+            }
+
+            let week = availability::week_of(date);
+
+            match weeks.last_mut() {
+                Some((last, count)) if *last == week => *count += 1.0,
+                _ => weeks.push((week, 1.0)),
+            }
+        }
+    }
+
+    weeks
+}
+
+/// A file named after another with a suffix, e.g. `plan-next-up.csv` for
+/// `next-up` and `plan.csv`
+fn suffixed_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, suffix, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, suffix),
+    };
+
+    path.with_file_name(name)
+}
+
+/// The file for one of several reports when each goes in a file of its
+/// own, e.g. `plan-next-up.csv` for `next-up` with `plan.csv`
+pub fn report_path(path: &Path, kind: ReportKind) -> PathBuf {
+    let name = kind
+        .to_possible_value()
+        .map(|value| value.get_name().to_owned())
+        .unwrap_or_default();
+
+    suffixed_path(path, &name)
+}
+
+/// The file for one of the summary's other tables in CSV, e.g.
+/// `plan-summary-epics.csv` with `plan.csv`
+pub fn summary_table_path(path: &Path, table: SummaryTable) -> PathBuf {
+    suffixed_path(path, &format!("summary-{}", table.name()))
+}
+
+/// Lay out rows as a table in the given format
+pub fn table_as(
+    format: ReportFormat,
+    headers: &[&str],
+    rows: &[Vec<String>],
+) -> Result<String, Box<dyn Error>> {
+    match format {
+        ReportFormat::Text => Ok(table(headers, rows)),
+        ReportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+
+            writer.write_record(headers)?;
+
+            for row in rows.iter() {
+                writer.write_record(row)?;
+            }
+
+            Ok(String::from_utf8(writer.into_inner()?)?
+                .trim_end()
+                .to_owned())
+        }
+        ReportFormat::Markdown => {
+            let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+            let escape = |cell: &str| cell.replace('|', "\\|");
+            let mut lines = vec![
+                line(headers.iter().map(|h| escape(h)).collect()),
+                line(headers.iter().map(|_| "---".to_owned()).collect()),
+            ];
+
+            lines.extend(
+                rows.iter()
+                    .map(|row| line(row.iter().map(|cell| escape(cell)).collect())),
+            );
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+/// A heading followed by a table. CSV has no place for a heading, so only
+/// the table is written, and a report with one table is a plain CSV file.
+fn section(format: ReportFormat, heading: &str, table: String) -> String {
+    match format {
+        ReportFormat::Text => format!("{}\n{}", heading, table),
+        ReportFormat::Csv => table,
+        ReportFormat::Markdown => format!("## {}\n\n{}", heading, table),
+    }
+}
+
+/// A row for each epic, in the order its first issue is scheduled. The due
+/// date is that of the epic's own issue if it is in the chart, matched by
+/// key or summary, or else the latest due date of its issues. The slip is
+/// the days the earliest finish is after the due date.
+fn epic_rows(items: &[ScheduledItem], text: &Translations) -> Vec<Vec<String>> {
+    let mut epics: Vec<(&str, Vec<&ScheduledItem>)> = vec![];
+
+    for item in items.iter() {
+        match item.item.epic.as_deref() {
+            None | Some("") => (),
+            Some(epic) => match epics.iter_mut().find(|(name, _)| *name == epic) {
+                Some((_, epic_items)) => epic_items.push(item),
+                None => epics.push((epic, vec![item])),
+            },
+        }
+    }
+
+    epics
+        .iter()
+        .map(|(epic, epic_items)| {
+            let finish = epic_items.iter().filter_map(|item| item.last_day()).max();
+            let due_date = items
+                .iter()
+                .find(|item| {
+                    item.item.id.as_deref() == Some(*epic)
+                        || item.item.summary.as_deref() == Some(*epic)
+                })
+                .and_then(|item| item.item.due_date)
+                .or_else(|| {
+                    epic_items
+                        .iter()
+                        .filter_map(|item| item.item.due_date)
+                        .max()
+                });
+            let slip = match (finish, due_date) {
+                (Some(finish), Some(due_date)) if finish > due_date => {
+                    text.format("days", (finish - due_date).num_days())
+                }
+                _ => String::new(),
+            };
+
+            vec![
+                epic.to_string(),
+                epic_items.len().to_string(),
+                finish.map(|d| d.to_string()).unwrap_or_default(),
+                due_date.map(|d| d.to_string()).unwrap_or_default(),
+                slip,
+            ]
+        })
+        .collect()
+}
+
+fn format_days(days: f64) -> String {
+    if days.fract() == 0.0 {
+        format!("{}", days)
+    } else {
+        format!("{:.1}", days)
+    }
+}
+
+/// The items of the chart as scheduled, split into the committed items and
+/// the stretch items. Commitments are made on committed scope, so stretch
+/// items are only counted on a line of their own.
+fn committed(chart: &Chart) -> (Vec<ScheduledItem<'_>>, Vec<ScheduledItem<'_>>) {
+    schedule::schedule(chart)
+        .into_iter()
+        .partition(|item| item.item.stretch != Some(true))
+}
+
+/// When each epic can finish, given the dependencies and the work queued
+/// ahead of its issues in their lanes, against when it is due
+fn epics_table(
+    rows: &[Vec<String>],
+    text: &Translations,
+    format: ReportFormat,
+) -> Result<String, Box<dyn Error>> {
+    table_as(
+        format,
+        &[
+            text.get("report-epic"),
+            text.get("report-items"),
+            text.get("report-earliest-finish"),
+            text.get("report-due"),
+            text.get("report-slip"),
+        ],
+        rows,
+    )
+}
+
+/// Scheduled days against capacity, for each resource and week
+fn capacity_table(
+    chart: &Chart,
+    items: &[ScheduledItem],
+    availability: &Availability,
+    pto: &Pto,
+    text: &Translations,
+    format: ReportFormat,
+) -> Result<String, Box<dyn Error>> {
+    let mut load: BTreeMap<(usize, NaiveDate), f64> = BTreeMap::new();
+
+    for item in items.iter() {
+        if let Some(resource) = item.resource {
+            for (week, days) in working_days_by_week(item, &chart.resources[resource], pto) {
+                *load.entry((resource, week)).or_insert(0.0) += days;
+            }
+        }
+    }
+
+    let rows: Vec<Vec<String>> = load
+        .iter()
+        .map(|((resource, week), scheduled)| {
+            let name = &chart.resources[*resource];
+            let capacity = availability.capacity(name, *week);
+            let over = *scheduled > capacity;
+
+            vec![
+                week.to_string(),
+                name.to_owned(),
+                format_days(*scheduled),
+                format_days(capacity),
+                if capacity > 0.0 {
+                    format!("{:.0}%", scheduled / capacity * 100.0)
+                } else {
+                    "-".to_owned()
+                },
+                if over {
+                    text.get("report-over").to_owned()
+                } else {
+                    "".to_owned()
+                },
+            ]
+        })
+        .collect();
+
+    table_as(
+        format,
+        &[
+            text.get("report-week-of"),
+            text.get("report-resource"),
+            text.get("report-scheduled"),
+            text.get("report-capacity"),
+            text.get("report-load"),
+            "",
+        ],
+        &rows,
+    )
+}
+
+/// One of the summary's other tables as CSV, for the file of its own that
+/// it goes in
+pub fn summary_table(
+    chart: &Chart,
+    availability: &Availability,
+    pto: &Pto,
+    text: &Translations,
+    table: SummaryTable,
+) -> Result<String, Box<dyn Error>> {
+    let (items, _) = committed(chart);
+
+    match table {
+        SummaryTable::Epics => epics_table(&epic_rows(&items, text), text, ReportFormat::Csv),
+        SummaryTable::Capacity => {
+            capacity_table(chart, &items, availability, pto, text, ReportFormat::Csv)
+        }
+    }
+}
+
+pub fn summary(
+    chart: &Chart,
+    availability: &Availability,
+    pto: &Pto,
+    text: &Translations,
+    format: ReportFormat,
+) -> Result<String, Box<dyn Error>> {
+    let (items, stretch) = committed(chart);
+    let days = |item: &ScheduledItem| item.item.data.duration.unwrap_or(0);
+    let start = |items: &[&ScheduledItem]| {
+        items
+            .iter()
+            .filter_map(|item| item.start)
+            .min()
+            .map(|d| d.to_string())
+            .unwrap_or_default()
+    };
+    let end = |items: &[&ScheduledItem]| {
+        items
+            .iter()
+            .filter_map(|item| item.last_day())
+            .max()
+            .map(|d| d.to_string())
+            .unwrap_or_default()
+    };
+    let totals = |items: &[ScheduledItem]| {
+        format!(
+            "{}: {}, {}: {}, {}: {}",
+            text.get("report-issues"),
+            items.len(),
+            text.get("report-total-days"),
+            items.iter().map(days).sum::<i64>(),
+            text.get("report-end-date"),
+            end(&items.iter().collect::<Vec<_>>())
+        )
+    };
+    let row = |name: &str, items: &[&ScheduledItem]| {
+        vec![
+            name.to_owned(),
+            items.len().to_string(),
+            items.iter().map(|item| days(item)).sum::<i64>().to_string(),
+            start(items),
+            end(items),
+        ]
+    };
+
+    // Totals for each resource
+    let mut rows = vec![];
+
+    for (index, resource) in chart.resources.iter().enumerate() {
+        let resource_items: Vec<&ScheduledItem> = items
+            .iter()
+            .filter(|item| item.resource == Some(index))
+            .collect();
+
+        if resource_items.is_empty() && stretch.iter().any(|item| item.resource == Some(index)) {
+            continue;
+        }
+
+        rows.push(row(resource, &resource_items));
+    }
+
+    let headers = [
+        text.get("report-resource"),
+        text.get("report-items"),
+        text.get("report-days"),
+        text.get("report-start"),
+        text.get("report-end"),
+    ];
+
+    // A CSV file holds one table, so the totals are rows of the resource
+    // table, and the epics and weekly capacity go in files of their own
+    if format == ReportFormat::Csv {
+        if stretch.is_empty() {
+            rows.push(row(
+                text.get("report-total"),
+                &items.iter().collect::<Vec<_>>(),
+            ));
+        } else {
+            rows.push(row(
+                text.get("report-committed"),
+                &items.iter().collect::<Vec<_>>(),
+            ));
+            rows.push(row(
+                text.get("report-stretch"),
+                &stretch.iter().collect::<Vec<_>>(),
+            ));
+        }
+
+        return table_as(format, &headers, &rows);
+    }
+
+    let mut sections = vec![
+        section(
+            format,
+            text.get("report-summary"),
+            if stretch.is_empty() {
+                totals(&items)
+            } else {
+                format!(
+                    "{}: {}{}{}: {}",
+                    text.get("report-committed"),
+                    totals(&items),
+                    if format == ReportFormat::Markdown {
+                        "\n\n"
+                    } else {
+                        "\n"
+                    },
+                    text.get("report-stretch"),
+                    totals(&stretch)
+                )
+            },
+        ),
+        table_as(format, &headers, &rows)?,
+    ];
+
+    let rows = epic_rows(&items, text);
+
+    if !rows.is_empty() {
+        sections.push(section(
+            format,
+            text.get("report-epics"),
+            epics_table(&rows, text, format)?,
+        ));
+    }
+
+    sections.push(section(
+        format,
+        text.get("report-weekly-capacity"),
+        capacity_table(chart, &items, availability, pto, text, format)?,
+    ));
+
+    Ok(sections.join("\n\n"))
+}
+
+/// The open items of each resource in the order they are scheduled, leaving
+/// out those that depend on an item that is still open
+pub fn next_up(
+    chart: &Chart,
+    deps: &[Dependency],
+    text: &Translations,
+    format: ReportFormat,
+) -> Result<String, Box<dyn Error>> {
+    let items = schedule::schedule(chart);
+    let is_open = |item: &ScheduledItem| item.item.data.open != Some(false);
+    let open: HashSet<&str> = items
+        .iter()
+        .filter(|item| is_open(item))
+        .map(|item| item.item.id())
+        .collect();
+    let blocked: HashSet<&str> = deps
+        .iter()
+        .filter(|dep| open.contains(dep.before.as_str()))
+        .map(|dep| dep.after.as_str())
+        .collect();
+    let mut rows = vec![];
+
+    for (index, resource) in chart.resources.iter().enumerate() {
+        let mut next: Vec<&ScheduledItem> = items
+            .iter()
+            .filter(|item| {
+                item.resource == Some(index) && is_open(item) && !blocked.contains(item.item.id())
+            })
+            .collect();
+
+        next.sort_by_key(|item| item.start);
+
+        for item in next {
+            rows.push(vec![
+                resource.to_owned(),
+                item.item.data.title.to_owned(),
+                item.start.map(|d| d.to_string()).unwrap_or_default(),
+                item.item
+                    .data
+                    .duration
+                    .map(|days| days.to_string())
+                    .unwrap_or_default(),
+                item.item.risk.to_owned().unwrap_or_default(),
+            ]);
+        }
+    }
+
+    Ok(section(
+        format,
+        text.get("report-next-up"),
+        table_as(
+            format,
+            &[
+                text.get("report-resource"),
+                text.get("report-key"),
+                text.get("report-start"),
+                text.get("report-days"),
+                text.get("risk"),
+            ],
+            &rows,
+        )?,
+    ))
+}
+
+/// Each item with a health, and how its progress compares with the days
+/// that have gone by the marked date
+pub fn health(
+    chart: &Chart,
+    text: &Translations,
+    format: ReportFormat,
+) -> Result<String, Box<dyn Error>> {
+    let mut rows = vec![];
+
+    if let Some(today) = chart.marked_date {
+        for item in schedule::schedule(chart).iter() {
+            if let (Some(health), Some(start), Some(duration)) =
+                (item.item.health, item.start, item.item.data.duration)
+            {
+                rows.push(vec![
+                    item.resource
+                        .and_then(|index| chart.resources.get(index))
+                        .cloned()
+                        .unwrap_or_default(),
+                    item.item.data.title.to_owned(),
+                    item.last_day().map(|d| d.to_string()).unwrap_or_default(),
+                    format!("{}%", health::elapsed(start, duration, today)),
+                    format!("{}%", item.item.progress.unwrap_or_default()),
+                    text.get(health.name()).to_owned(),
+                ]);
+            }
+        }
+    }
+
+    Ok(section(
+        format,
+        text.get("report-health"),
+        table_as(
+            format,
+            &[
+                text.get("report-resource"),
+                text.get("report-key"),
+                text.get("report-end"),
+                text.get("report-elapsed"),
+                text.get("report-progress"),
+                text.get("report-health"),
+            ],
+            &rows,
+        )?,
+    ))
+}
+
+/// The items that were flagged or blocked, most blocked days first, then
+/// the blocked days of each resource. Periods that haven't ended count up
+/// to `today`.
+pub fn blocked(
+    chart: &Chart,
+    today: NaiveDate,
+    text: &Translations,
+    format: ReportFormat,
+) -> Result<String, Box<dyn Error>> {
+    let items = schedule::schedule(chart);
+    let resource_name = |item: &ScheduledItem| {
+        item.resource
+            .and_then(|index| chart.resources.get(index))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let days = |item: &ScheduledItem| {
+        item.item
+            .blocked
+            .iter()
+            .map(|period| period.days(today))
+            .sum::<i64>()
+    };
+    let mut blocked: Vec<&ScheduledItem> = items
+        .iter()
+        .filter(|item| !item.item.blocked.is_empty())
+        .collect();
+
+    blocked.sort_by_key(|item| std::cmp::Reverse(days(item)));
+
+    let rows: Vec<Vec<String>> = blocked
+        .iter()
+        .map(|item| {
+            vec![
+                resource_name(item),
+                item.item.data.title.to_owned(),
+                item.item.blocked.len().to_string(),
+                days(item).to_string(),
+            ]
+        })
+        .collect();
+    let by_issue = section(
+        format,
+        text.get("report-blocked"),
+        table_as(
+            format,
+            &[
+                text.get("report-resource"),
+                text.get("report-key"),
+                text.get("report-periods"),
+                text.get("report-blocked-days"),
+            ],
+            &rows,
+        )?,
+    );
+
+    // A CSV file holds one table, and the totals add up from the issues
+    if format == ReportFormat::Csv {
+        return Ok(by_issue);
+    }
+
+    let mut totals: Vec<(String, usize, i64)> = vec![];
+
+    for item in blocked.iter() {
+        let name = resource_name(item);
+
+        match totals.iter_mut().find(|(resource, _, _)| *resource == name) {
+            Some((_, issues, total)) => {
+                *issues += 1;
+                *total += days(item);
+            }
+            None => totals.push((name, 1, days(item))),
+        }
+    }
+
+    totals.sort_by_key(|(_, _, total)| std::cmp::Reverse(*total));
+
+    let total_rows: Vec<Vec<String>> = totals
+        .into_iter()
+        .map(|(resource, issues, total)| vec![resource, issues.to_string(), total.to_string()])
+        .collect();
+
+    Ok([
+        by_issue,
+        table_as(
+            format,
+            &[
+                text.get("report-resource"),
+                text.get("report-issues"),
+                text.get("report-blocked-days"),
+            ],
+            &total_rows,
+        )?,
+    ]
+    .join("\n\n"))
+}
+
+/// The dates each item is drawn with, one row per item in chart order
+pub fn schedule(chart: &Chart, format: ScheduleFormat) -> String {
+    let headers = [
+        "Key",
+        "Resource",
+        "Start",
+        "End",
+        "Days",
+        "Duration from",
+        "Id",
+    ];
+    let date = |date: Option<NaiveDate>| date.map(|d| d.to_string()).unwrap_or_default();
+    let rows: Vec<Vec<String>> = schedule::schedule(chart)
+        .iter()
+        .map(|item| {
+            vec![
+                item.item.data.title.to_owned(),
+                item.resource
+                    .and_then(|index| chart.resources.get(index))
+                    .cloned()
+                    .unwrap_or_default(),
+                date(item.start),
+                date(item.last_day()),
+                item.item
+                    .data
+                    .duration
+                    .map(|days| days.to_string())
+                    .unwrap_or_default(),
+                item.item
+                    .duration_source
+                    .map(|source| source.name())
+                    .unwrap_or("milestone")
+                    .to_owned(),
+                item.item.id().to_owned(),
+            ]
+        })
+        .collect();
+
+    match format {
+        ScheduleFormat::Table => table(&headers, &rows),
+        ScheduleFormat::Tsv => std::iter::once(headers.join("\t"))
+            .chain(rows.iter().map(|row| row.join("\t")))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    }
+}
+
+/// The last scheduled day of each open issue, as a CSV that Jira's CSV
+/// import can update the issues' due dates from. Closed issues, milestones
+/// and epics made by `--level epic` are left out.
+pub fn due_dates(chart: &Chart) -> Result<String, Box<dyn Error>> {
+    let mut csv = csv::Writer::from_writer(vec![]);
+
+    csv.write_record(["Issue key", "Due date"])?;
+
+    for item in schedule::schedule(chart).iter().filter(|item| {
+        item.item.data.open != Some(false)
+            && item.item.data.duration.is_some()
+            && item.item.duration_source != Some(DurationSource::Issues)
+    }) {
+        if let Some(last_day) = item.last_day() {
+            csv.write_record([item.item.id(), &last_day.format("%Y-%m-%d").to_string()])?;
+        }
+    }
+
+    Ok(String::from_utf8(csv.into_inner()?)?)
+}
+
+pub fn reassign(
+    chart: &Chart,
+    text: &Translations,
+    format: ReportFormat,
+) -> Result<String, Box<dyn Error>> {
+    let items = schedule::schedule(chart);
+    let mut orphaned: Vec<&ScheduledItem> = items
+        .iter()
+        .filter(|item| item.item.former_assignee.is_some())
+        .collect();
+
+    // Stable, so each person's issues stay in the order they are scheduled
+    orphaned.sort_by_key(|item| item.item.former_assignee.as_deref());
+
+    let rows: Vec<Vec<String>> = orphaned
+        .iter()
+        .map(|item| {
+            vec![
+                item.item.former_assignee.clone().unwrap_or_default(),
+                item.item.data.title.to_owned(),
+                item.item.epic.clone().unwrap_or_default(),
+                item.start.map(|d| d.to_string()).unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    Ok(section(
+        format,
+        text.get("report-reassign"),
+        table_as(
+            format,
+            &[
+                text.get("report-former-assignee"),
+                text.get("report-key"),
+                text.get("report-epic"),
+                text.get("report-start"),
+            ],
+            &rows,
+        )?,
+    ))
+}
+
+pub fn sla(
+    chart: &Chart,
+    today: NaiveDate,
+    text: &Translations,
+    format: ReportFormat,
+) -> Result<String, Box<dyn Error>> {
+    let items = schedule::schedule(chart);
+    let mut breaches: Vec<(&ScheduledItem, NaiveDate, NaiveDate)> = items
+        .iter()
+        .filter(|item| item.item.data.open != Some(false))
+        .filter_map(|item| {
+            let due = item.item.sla_due(today)?;
+            let last_day = item.last_day()?;
+
+            (last_day > due || item.item.sla_remaining.is_some_and(|seconds| seconds < 0))
+                .then_some((item, due, last_day))
+        })
+        .collect();
+
+    breaches.sort_by_key(|(_, due, _)| *due);
+
+    let rows: Vec<Vec<String>> = breaches
+        .iter()
+        .map(|(item, due, last_day)| {
+            vec![
+                item.item.data.title.to_owned(),
+                item.resource
+                    .and_then(|index| chart.resources.get(index))
+                    .cloned()
+                    .unwrap_or_default(),
+                due.to_string(),
+                last_day.to_string(),
+                text.format("days", (*last_day - *due).num_days().max(0)),
+            ]
+        })
+        .collect();
+
+    Ok(section(
+        format,
+        text.get("report-sla"),
+        table_as(
+            format,
+            &[
+                text.get("report-key"),
+                text.get("report-resource"),
+                text.get("report-sla-due"),
+                text.get("report-end"),
+                text.get("report-late"),
+            ],
+            &rows,
+        )?,
+    ))
+}
+
+pub fn workload_by_week(
+    chart: &Chart,
+    pto: &Pto,
+    text: &Translations,
+    format: ReportFormat,
+) -> Result<String, Box<dyn Error>> {
+    let mut load: BTreeMap<(usize, NaiveDate), f64> = BTreeMap::new();
+
+    for item in schedule::schedule(chart).iter() {
+        if let Some(resource) = item.resource {
+            for (week, days) in working_days_by_week(item, &chart.resources[resource], pto) {
+                *load.entry((resource, week)).or_insert(0.0) += days;
+            }
+        }
+    }
+
+    // Every week from the first to the last, so the columns line up with a
+    // calendar even when nobody is scheduled in a week
+    let weeks: Vec<NaiveDate> = match (
+        load.keys().map(|(_, week)| *week).min(),
+        load.keys().map(|(_, week)| *week).max(),
+    ) {
+        (Some(first), Some(last)) => first
+            .iter_weeks()
+            .take_while(|week| *week <= last)
+            .collect(),
+        _ => vec![],
+    };
+    let headers: Vec<String> = std::iter::once(text.get("report-resource").to_owned())
+        .chain(weeks.iter().map(|week| {
+            let iso = week.iso_week();
+
+            format!("{}-W{:02}", iso.year(), iso.week())
+        }))
+        .collect();
+    let rows: Vec<Vec<String>> = chart
+        .resources
+        .iter()
+        .enumerate()
+        .map(|(index, resource)| {
+            std::iter::once(resource.to_owned())
+                .chain(
+                    weeks
+                        .iter()
+                        .map(|week| format_days(load.get(&(index, *week)).copied().unwrap_or(0.0))),
+                )
+                .collect()
+        })
+        .collect();
+
+    Ok(section(
+        format,
+        text.get("report-workload-by-week"),
+        table_as(
+            format,
+            &headers.iter().map(|h| h.as_str()).collect::<Vec<&str>>(),
+            &rows,
+        )?,
+    ))
+}
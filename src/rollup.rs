@@ -0,0 +1,178 @@
+use crate::{
+    chart::{self, Chart, DurationSource, Item},
+    config::Config,
+    i18n::Translations,
+    schedule,
+};
+use chrono::{Duration, NaiveDate};
+use clap::ValueEnum;
+use gantt_chart::ItemData;
+use std::collections::{BTreeMap, HashSet};
+
+/// How far up the teams of the config file people's lanes are rolled up
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rollup {
+    /// One lane for each team, with the work of its manager and members
+    Team,
+    /// One lane for each team at the top of the hierarchy, with the work of
+    /// all the teams under it
+    Org,
+}
+
+/// The lane of a team, and the days its people are busy
+struct Lane {
+    name: String,
+    /// The people busy on each day, in lower case
+    busy: BTreeMap<NaiveDate, HashSet<String>>,
+    /// The days with open work
+    open: HashSet<NaiveDate>,
+}
+
+/// The lane that someone's work is rolled up into
+fn lane_of<'a>(config: &'a Config, person: &str, rollup: Rollup) -> Option<&'a str> {
+    let mut team = config.team_of(person)?;
+
+    if rollup == Rollup::Org {
+        // Guard against teams that manage each other
+        let mut seen = vec![team];
+
+        while let Some(parent) = config.parent_team(team) {
+            if seen.contains(&parent) {
+                break;
+            }
+
+            seen.push(parent);
+            team = parent;
+        }
+    }
+
+    Some(team)
+}
+
+/// Roll the lanes of people up into a lane for each of their teams. Each
+/// lane has a bar for each run of days that anyone in the team is busy,
+/// titled with the team's utilization over the run: the days its people are
+/// busy as a percentage of the days they all have. People who aren't in a
+/// team are rolled up into a `no team` lane. Lanes are in the order their
+/// first work appears.
+pub fn apply(chart: &Chart, config: &Config, rollup: Rollup, text: &Translations) -> Chart {
+    let no_team = text.get("no-team");
+    let mut lanes: Vec<Lane> = vec![];
+
+    for item in schedule::schedule(chart).iter() {
+        let (start, days) = match (item.start, item.item.data.duration) {
+            (Some(start), Some(days)) if days > 0 => (start, days),
+            _ => continue,
+        };
+        let person = match item.item.assignee {
+            Some(ref assignee) => assignee.as_str(),
+            None => item
+                .resource
+                .and_then(|index| chart.resources.get(index))
+                .map_or("", |name| name.as_str()),
+        };
+        let name = lane_of(config, person, rollup).unwrap_or(no_team);
+        let lane = match lanes.iter().position(|lane| lane.name == name) {
+            Some(index) => &mut lanes[index],
+            None => {
+                lanes.push(Lane {
+                    name: name.to_owned(),
+                    busy: BTreeMap::new(),
+                    open: HashSet::new(),
+                });
+                lanes.last_mut().unwrap()
+            }
+        };
+
+        for date in (0..days).map(|day| start + Duration::days(day)) {
+            lane.busy
+                .entry(date)
+                .or_default()
+                .insert(person.to_lowercase());
+
+            if item.item.data.open != Some(false) {
+                lane.open.insert(date);
+            }
+        }
+    }
+
+    let mut items = vec![];
+
+    for (index, lane) in lanes.iter().enumerate() {
+        // Everyone in the team, or those with work for people in no team
+        let mut people: HashSet<String> = config
+            .teams
+            .values()
+            .flat_map(|team| team.manager.iter().chain(team.members.iter()))
+            .filter(|person| lane_of(config, person, rollup) == Some(lane.name.as_str()))
+            .map(|person| person.to_lowercase())
+            .collect();
+
+        if people.is_empty() {
+            people = lane.busy.values().flatten().cloned().collect();
+        }
+
+        let mut runs: Vec<Vec<(&NaiveDate, usize)>> = vec![];
+
+        for (date, busy) in lane.busy.iter() {
+            match runs.last_mut() {
+                Some(run) if *run.last().unwrap().0 + Duration::days(1) == *date => {
+                    run.push((date, busy.len()))
+                }
+                _ => runs.push(vec![(date, busy.len())]),
+            }
+        }
+
+        for run in runs {
+            let start = *run[0].0;
+            let days = run.len();
+            let busy: usize = run.iter().map(|(_, busy)| busy).sum();
+            let utilization = (busy * 100) as f64 / (people.len().max(1) * days) as f64;
+
+            items.push(Item {
+                id: Some(chart::synthetic_id(
+                    "team",
+                    &format!("{} {}", lane.name, start),
+                )),
+                data: ItemData {
+                    title: format!("{:.0}%", utilization),
+                    start_date: Some(start),
+                    duration: Some(days as i64),
+                    resource_index: Some(index),
+                    open: Some(run.iter().any(|(date, _)| lane.open.contains(date))),
+                },
+                tooltip: None,
+                sprint: None,
+                risk: None,
+                progress: None,
+                health: None,
+                color: None,
+                blocked: vec![],
+                depends_on: vec![],
+                stretch: None,
+                fields: BTreeMap::new(),
+                duration_source: Some(DurationSource::Issues),
+                estimate: None,
+                epic: None,
+                assignee: None,
+                created: None,
+                summary: None,
+                due_date: None,
+                former_assignee: None,
+                fix_versions: vec![],
+                sla_remaining: None,
+            });
+        }
+    }
+
+    Chart {
+        title: chart.title.to_owned(),
+        marked_date: chart.marked_date,
+        resources: lanes.into_iter().map(|lane| lane.name).collect(),
+        items,
+        groups: vec![],
+        sprints: chart.sprints.clone(),
+        non_working: vec![],
+        units: chart.units.clone(),
+    }
+}
@@ -0,0 +1,66 @@
+use crate::chart::{Chart, Item};
+use chrono::{Duration, NaiveDate};
+
+/// An item with the dates and resource it is drawn with
+pub struct ScheduledItem<'a> {
+    pub item: &'a Item,
+    pub start: Option<NaiveDate>,
+    /// The day after the item's last day, or the start for a milestone
+    pub end: Option<NaiveDate>,
+    pub resource: Option<usize>,
+}
+
+impl ScheduledItem<'_> {
+    /// The last day of the item, or the day of a milestone
+    pub fn last_day(&self) -> Option<NaiveDate> {
+        match self.item.data.duration {
+            Some(days) if days > 0 => self.end.and_then(|end| end.pred_opt()),
+            _ => self.start,
+        }
+    }
+}
+
+/// Work out where each item is drawn the same way `gantt_chart` does. An
+/// item without a start date starts where the previous item ended, and an
+/// item without a resource has the previous item's resource.
+pub fn schedule(chart: &Chart) -> Vec<ScheduledItem<'_>> {
+    let mut date: Option<NaiveDate> = None;
+    let mut resource: Option<usize> = None;
+
+    chart
+        .items
+        .iter()
+        .map(|item| {
+            if item.data.start_date.is_some() {
+                date = item.data.start_date;
+            }
+
+            if item.data.resource_index.is_some() {
+                resource = item.data.resource_index;
+            }
+
+            let start = date;
+
+            if let Some(days) = item.data.duration {
+                date = date.map(|date| date + Duration::days(days));
+            }
+
+            ScheduledItem {
+                item,
+                start,
+                end: date,
+                resource,
+            }
+        })
+        .collect()
+}
+
+/// Give every item the start date it is drawn with, so tools that read the
+/// chart don't have to chain each item after the one before
+pub fn fill_starts(chart: &mut Chart) {
+    let starts: Vec<Option<NaiveDate>> = schedule(chart).iter().map(|item| item.start).collect();
+
+    for (item, start) in chart.items.iter_mut().zip(starts) {
+        item.data.start_date = start;
+    }
+}
@@ -0,0 +1,227 @@
+use crate::chart::{DurationSource, Item, Period};
+use chrono::NaiveDate;
+use easy_error::{self, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    mem,
+    path::PathBuf,
+    process,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Numbers the spill directories, so that charts built at the same time,
+/// e.g. for the sources of a portfolio, don't share them
+static SPILLS: AtomicUsize = AtomicUsize::new(0);
+
+/// An amount of memory, e.g. `512M` or `2G`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemorySize(pub usize);
+
+impl FromStr for MemorySize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{}' must be a size such as 512M or 2G", s);
+        let s = s.trim();
+        let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+            Some(position) => s.split_at(position),
+            None => (s, ""),
+        };
+        let scale: usize = match unit.to_ascii_uppercase().trim_end_matches('B') {
+            "" => 1,
+            "K" => 1 << 10,
+            "M" => 1 << 20,
+            "G" => 1 << 30,
+            _ => return Err(invalid()),
+        };
+
+        number
+            .parse::<usize>()
+            .ok()
+            .filter(|number| *number > 0)
+            .and_then(|number| number.checked_mul(scale))
+            .map(MemorySize)
+            .ok_or_else(invalid)
+    }
+}
+
+/// An item with the fields that aren't written to chart files, so that it
+/// comes back from a spill file exactly as it went in
+#[derive(Serialize, Deserialize)]
+struct SpilledItem {
+    item: Item,
+    duration_source: Option<DurationSource>,
+    estimate: Option<u32>,
+    epic: Option<String>,
+    assignee: Option<String>,
+    created: Option<NaiveDate>,
+    summary: Option<String>,
+    due_date: Option<NaiveDate>,
+    former_assignee: Option<String>,
+    fix_versions: Vec<String>,
+    sla_remaining: Option<i64>,
+}
+
+/// Writes the items of each lane to a temporary file of its own once the
+/// items held in memory take up more than a limit, and reads them back when
+/// the chart is put together. The files are removed when it is dropped.
+pub struct Spill {
+    limit: usize,
+    /// Roughly how much memory the items held in memory take up
+    held: usize,
+    dir: PathBuf,
+    /// The lanes that have items in their files
+    spilled: Vec<bool>,
+}
+
+impl Spill {
+    pub fn new(limit: MemorySize) -> Spill {
+        Spill {
+            limit: limit.0,
+            held: 0,
+            dir: env::temp_dir().join(format!(
+                "jira_to_gantt_spill_{}_{}",
+                process::id(),
+                SPILLS.fetch_add(1, Ordering::Relaxed)
+            )),
+            spilled: vec![],
+        }
+    }
+
+    fn lane_path(&self, lane: usize) -> PathBuf {
+        self.dir.join(format!("lane_{}.jsonl", lane))
+    }
+
+    /// Note the item just added to a lane, writing all the lanes out if that
+    /// takes them over the limit
+    pub fn hold(&mut self, lane: usize, lanes: &mut [Vec<Item>]) -> Result<(), Box<dyn Error>> {
+        self.held += lanes[lane].last().map_or(0, approximate_size);
+
+        if self.held <= self.limit {
+            return Ok(());
+        }
+
+        if self.spilled.is_empty() {
+            fs::create_dir_all(&self.dir).context(format!(
+                "Unable to create spill directory '{}'",
+                self.dir.to_string_lossy()
+            ))?;
+        }
+
+        self.spilled.resize(lanes.len(), false);
+
+        for (lane, items) in lanes.iter_mut().enumerate() {
+            if items.is_empty() {
+                continue;
+            }
+
+            let path = self.lane_path(lane);
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .context(format!(
+                    "Unable to write spill file '{}'",
+                    path.to_string_lossy()
+                ))?;
+            let mut writer = BufWriter::new(file);
+
+            for mut item in mem::take(items) {
+                let spilled = SpilledItem {
+                    duration_source: item.duration_source.take(),
+                    estimate: item.estimate.take(),
+                    epic: item.epic.take(),
+                    assignee: item.assignee.take(),
+                    created: item.created.take(),
+                    summary: item.summary.take(),
+                    due_date: item.due_date.take(),
+                    former_assignee: item.former_assignee.take(),
+                    fix_versions: mem::take(&mut item.fix_versions),
+                    sla_remaining: item.sla_remaining.take(),
+                    item,
+                };
+
+                serde_json::to_writer(&mut writer, &spilled)?;
+                writeln!(writer)?;
+            }
+
+            writer.flush()?;
+            self.spilled[lane] = true;
+        }
+
+        self.held = 0;
+
+        Ok(())
+    }
+
+    /// All the items of a lane, those written out first and then those still
+    /// held
+    pub fn take(&mut self, lane: usize, held: Vec<Item>) -> Result<Vec<Item>, Box<dyn Error>> {
+        if !self.spilled.get(lane).copied().unwrap_or(false) {
+            return Ok(held);
+        }
+
+        let path = self.lane_path(lane);
+        let file = File::open(&path).context(format!(
+            "Unable to read spill file '{}'",
+            path.to_string_lossy()
+        ))?;
+        let mut items = vec![];
+
+        for line in BufReader::new(file).lines() {
+            let spilled: SpilledItem = serde_json::from_str(&line?)?;
+
+            items.push(Item {
+                duration_source: spilled.duration_source,
+                estimate: spilled.estimate,
+                epic: spilled.epic,
+                assignee: spilled.assignee,
+                created: spilled.created,
+                summary: spilled.summary,
+                due_date: spilled.due_date,
+                former_assignee: spilled.former_assignee,
+                fix_versions: spilled.fix_versions,
+                sla_remaining: spilled.sla_remaining,
+                ..spilled.item
+            });
+        }
+
+        fs::remove_file(&path).ok();
+        items.extend(held);
+
+        Ok(items)
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+/// Roughly how much memory an item takes up, counting its strings
+fn approximate_size(item: &Item) -> usize {
+    let text = |s: &Option<String>| s.as_ref().map_or(0, |s| s.len());
+
+    mem::size_of::<Item>()
+        + item.data.title.len()
+        + text(&item.id)
+        + text(&item.tooltip)
+        + text(&item.sprint)
+        + text(&item.risk)
+        + text(&item.color)
+        + text(&item.epic)
+        + text(&item.assignee)
+        + text(&item.summary)
+        + item
+            .fix_versions
+            .iter()
+            .map(|version| version.len())
+            .sum::<usize>()
+        + item.blocked.len() * mem::size_of::<Period>()
+}
@@ -0,0 +1,182 @@
+use crate::chart::Chart;
+use easy_error::{self, ResultExt};
+use ring::digest::{Context, Digest, SHA256};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    error::Error,
+    ffi::OsString,
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+/// The field of the chart file that the stamp is written to
+const STAMP_FIELD: &str = "stamp";
+
+/// How a chart file was made, so that a published chart can be traced back
+/// to the tool, options and export that produced it. Hashes are SHA-256,
+/// written as `sha256:` and then hex.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Stamp {
+    /// The name and version of the tool, e.g. `jira-to-gantt 2.0.1`
+    pub tool: String,
+    /// When the chart was made, in UTC
+    pub generated: String,
+    /// The hash of the command line, after the name of the tool
+    pub options: String,
+    /// The hash of the input file, when the issues were read from one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    /// The hash of the chart data without the stamp, to tell whether the
+    /// file has been changed since
+    #[serde(default)]
+    pub chart: String,
+}
+
+impl Stamp {
+    pub fn new(options: String, input: Option<String>) -> Stamp {
+        Stamp {
+            tool: format!("jira-to-gantt {}", env!("CARGO_PKG_VERSION")),
+            generated: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            options,
+            input,
+            chart: String::new(),
+        }
+    }
+}
+
+fn hex(digest: Digest) -> String {
+    let hex: String = digest
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    format!("sha256:{}", hex)
+}
+
+/// The hash of a command line, with the arguments separated by NULs so that
+/// `-o a b` and `-o "a b"` differ
+pub fn options_hash(args: &[OsString]) -> String {
+    let mut context = Context::new(&SHA256);
+
+    for (index, arg) in args.iter().enumerate() {
+        if index > 0 {
+            context.update(&[0]);
+        }
+
+        context.update(arg.to_string_lossy().as_bytes());
+    }
+
+    hex(context.finish())
+}
+
+/// The hash of a file, read a block at a time so big exports aren't held in
+/// memory
+pub fn file_hash(path: &Path) -> Result<String, Box<dyn Error>> {
+    let file =
+        File::open(path).context(format!("Unable to open file '{}'", path.to_string_lossy()))?;
+    let mut reader = BufReader::new(file);
+    let mut context = Context::new(&SHA256);
+    let mut buffer = [0; 64 * 1024];
+
+    loop {
+        match reader.read(&mut buffer)? {
+            0 => break,
+            count => context.update(&buffer[..count]),
+        }
+    }
+
+    Ok(hex(context.finish()))
+}
+
+/// Numbers as they read back from any layout. JSON5 writes a whole float
+/// such as `8.0` as `8`, which reads back as an integer.
+fn canonical(value: &mut Value) {
+    match value {
+        Value::Number(number) if !number.is_i64() && !number.is_u64() => {
+            if let Some(float) = number.as_f64() {
+                if float.fract() == 0.0 && float.abs() < (1u64 << 53) as f64 {
+                    *value = Value::from(float as i64);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(canonical),
+        Value::Object(fields) => fields.values_mut().for_each(canonical),
+        _ => (),
+    }
+}
+
+/// The hash of chart data without its stamp. JSON objects are sorted by key
+/// and numbers are made canonical, so the same chart always hashes the same
+/// however it is laid out.
+fn chart_hash(chart: &Value) -> Result<String, Box<dyn Error>> {
+    let mut chart = chart.clone();
+
+    if let Some(fields) = chart.as_object_mut() {
+        fields.remove(STAMP_FIELD);
+    }
+
+    canonical(&mut chart);
+
+    Ok(hex(ring::digest::digest(
+        &SHA256,
+        serde_json::to_string(&chart)?.as_bytes(),
+    )))
+}
+
+/// Chart data with its stamp after the chart's own fields, which keep their
+/// order when it is written
+#[derive(Serialize)]
+pub struct Stamped<'a> {
+    #[serde(flatten)]
+    chart: &'a Chart,
+    stamp: Stamp,
+}
+
+/// Add a stamp to a chart, with the hash of the chart as it is
+pub fn apply(chart: &Chart, mut stamp: Stamp) -> Result<Stamped<'_>, Box<dyn Error>> {
+    stamp.chart = chart_hash(&serde_json::to_value(chart)?)?;
+
+    Ok(Stamped { chart, stamp })
+}
+
+/// Check the stamp of chart data, and that the input file and command line
+/// are those it was made from when they are given. Returns the stamp.
+pub fn verify(
+    content: &str,
+    input: Option<&Path>,
+    args: Option<&[OsString]>,
+) -> Result<Stamp, Box<dyn Error>> {
+    let chart: Value = json5::from_str(content)?;
+    let stamp: Stamp = match chart.get(STAMP_FIELD) {
+        Some(stamp) => serde_json::from_value(stamp.clone())?,
+        None => return Err(From::from("it has no stamp, so make it with --stamp")),
+    };
+
+    if chart_hash(&chart)? != stamp.chart {
+        return Err(From::from("it has been changed since it was stamped"));
+    }
+
+    if let Some(path) = input {
+        match stamp.input {
+            Some(ref hash) if *hash == file_hash(path)? => (),
+            Some(_) => {
+                return Err(From::from(format!(
+                    "it wasn't made from '{}'",
+                    path.to_string_lossy()
+                )))
+            }
+            None => return Err(From::from("it wasn't made from an input file")),
+        }
+    }
+
+    if let Some(args) = args {
+        if options_hash(args) != stamp.options {
+            return Err(From::from("it wasn't made with those options"));
+        }
+    }
+
+    Ok(stamp)
+}
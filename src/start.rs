@@ -0,0 +1,49 @@
+use crate::record::JiraRecord;
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+/// Works out when work on an issue can start, for issues without planned
+/// dates, since teams differ on when work "starts". Each lane starts on the
+/// earliest start of its first issue, or on its Created date without one.
+/// Each later issue follows the one before it in its lane, or starts on its
+/// earliest start if that is later.
+///
+/// Tools built on this crate can give their own to
+/// `JiraToGanttTool::with_start`.
+pub trait InferStart {
+    /// The earliest day an issue can start, if it is known. `today` is the
+    /// day the chart is made.
+    fn earliest_start(&self, record: &JiraRecord, today: NaiveDate) -> Option<NaiveDate>;
+}
+
+/// The ways of inferring when work starts that come with the tool
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StartFrom {
+    /// Each lane starts on the Created date of its first issue
+    Created,
+    /// Issues in a sprint don't start before their sprint does
+    SprintStart,
+    /// Issues that have moved to another status since they were created and
+    /// aren't resolved, such as those in progress, started when they moved
+    StatusTransition,
+    /// Issues start on the date of a start date field, e.g. the Start date
+    /// custom field, read as the `started` column
+    CustomField,
+    /// Every lane starts today, and the scheduler puts the issues one after
+    /// another from there
+    Scheduler,
+}
+
+impl InferStart for StartFrom {
+    fn earliest_start(&self, record: &JiraRecord, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            StartFrom::Created => None,
+            StartFrom::SprintStart => record.sprint_start,
+            StartFrom::StatusTransition => record
+                .status_changed
+                .filter(|changed| record.resolved.is_none() && Some(*changed) > record.created),
+            StartFrom::CustomField => record.started,
+            StartFrom::Scheduler => Some(today),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{chart::Chart, config::Config, schedule};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use std::{collections::HashSet, error::Error, io::Write};
+
+/// How suggested sprints are written
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SuggestFormat {
+    /// A row for each issue with its sprint, resource and days
+    Csv,
+    /// A JQL query for each sprint, to search for and bulk edit its issues
+    Jql,
+}
+
+/// An issue that fits into an upcoming sprint
+pub struct Suggestion {
+    pub sprint: String,
+    pub resource: String,
+    pub key: String,
+    pub days: i64,
+}
+
+/// The days of work a resource gets through in a sprint. `--capacity` comes
+/// first, then the config file's capacity for the resource, then its
+/// velocity, which is the days of its closed issues over the sprints they
+/// were in, and last the config file's capacity for `*`.
+fn capacity(chart: &Chart, config: &Config, resource: usize, given: Option<f64>) -> Option<f64> {
+    let name = &chart.resources[resource];
+    let configured = |name: &str| {
+        config
+            .sprint_capacity
+            .iter()
+            .find(|(resource, _)| resource.eq_ignore_ascii_case(name))
+            .map(|(_, days)| *days)
+    };
+    let velocity = || {
+        let done: Vec<_> = chart
+            .items
+            .iter()
+            .filter(|item| item.data.resource_index == Some(resource))
+            .filter(|item| item.data.open == Some(false))
+            .filter(|item| item.sprint.is_some())
+            .collect();
+        let sprints: HashSet<&str> = done
+            .iter()
+            .filter_map(|item| item.sprint.as_deref())
+            .collect();
+        let days: i64 = done.iter().filter_map(|item| item.data.duration).sum();
+
+        if sprints.is_empty() || days == 0 {
+            None
+        } else {
+            Some(days as f64 / sprints.len() as f64)
+        }
+    };
+
+    given
+        .or_else(|| configured(name))
+        .or_else(velocity)
+        .or_else(|| configured("*"))
+}
+
+/// The names of the next sprints, those of the chart that start from today
+/// on and then numbered ones after them
+fn sprint_names(chart: &Chart, count: usize, today: NaiveDate) -> Vec<String> {
+    let mut upcoming: Vec<_> = chart
+        .sprints
+        .iter()
+        .filter(|sprint| sprint.start_date.is_some_and(|start| start >= today))
+        .collect();
+
+    upcoming.sort_by_key(|sprint| sprint.start_date);
+
+    (0..count)
+        .map(|index| match upcoming.get(index) {
+            Some(sprint) => sprint.name.to_owned(),
+            None => format!("Next sprint {}", index + 1),
+        })
+        .collect()
+}
+
+/// Fill the next sprints of each resource with its open issues that aren't
+/// in a sprint yet, in the order they are scheduled, until the sprint's
+/// capacity is used up. Issues stay in order, so one that doesn't fit starts
+/// the next sprint, and one bigger than a whole sprint has a sprint to
+/// itself. Milestones and issues without a duration are left out. Resources
+/// without a capacity are returned apart, to warn about.
+pub fn suggest(
+    chart: &Chart,
+    config: &Config,
+    sprints: usize,
+    given: Option<f64>,
+    today: NaiveDate,
+) -> (Vec<Suggestion>, Vec<String>) {
+    let names = sprint_names(chart, sprints, today);
+    let scheduled = schedule::schedule(chart);
+    let mut suggestions = vec![];
+    let mut unknown = vec![];
+
+    for (resource, name) in chart.resources.iter().enumerate() {
+        let mut items = scheduled
+            .iter()
+            .filter(|scheduled| scheduled.resource == Some(resource))
+            .filter(|scheduled| {
+                scheduled.item.data.open != Some(false) && scheduled.item.sprint.is_none()
+            })
+            .filter_map(|scheduled| {
+                scheduled
+                    .item
+                    .data
+                    .duration
+                    .map(|days| (scheduled.item, days))
+            })
+            .peekable();
+
+        if items.peek().is_none() {
+            continue;
+        }
+
+        let capacity = match capacity(chart, config, resource, given) {
+            Some(capacity) => capacity,
+            None => {
+                unknown.push(name.to_owned());
+                continue;
+            }
+        };
+        let mut sprint = 0;
+        let mut used = 0.0;
+
+        for (item, days) in items {
+            if used > 0.0 && used + days as f64 > capacity {
+                sprint += 1;
+                used = 0.0;
+            }
+
+            if sprint >= names.len() {
+                break;
+            }
+
+            used += days as f64;
+            suggestions.push(Suggestion {
+                sprint: names[sprint].to_owned(),
+                resource: name.to_owned(),
+                key: item.id().to_owned(),
+                days,
+            });
+        }
+    }
+
+    // Sprint by sprint, keeping the resources in order within each
+    suggestions.sort_by_key(|suggestion| names.iter().position(|name| *name == suggestion.sprint));
+
+    (suggestions, unknown)
+}
+
+/// Write suggestions as CSV, or as a JQL query for each sprint that finds
+/// its issues, for a bulk edit of their sprint in Jira
+pub fn write(
+    writer: &mut dyn Write,
+    suggestions: &[Suggestion],
+    format: SuggestFormat,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        SuggestFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(writer);
+
+            csv.write_record(["Sprint", "Resource", "Issue key", "Days"])?;
+
+            for suggestion in suggestions.iter() {
+                csv.write_record([
+                    suggestion.sprint.as_str(),
+                    suggestion.resource.as_str(),
+                    suggestion.key.as_str(),
+                    &suggestion.days.to_string(),
+                ])?;
+            }
+
+            csv.flush()?;
+        }
+        SuggestFormat::Jql => {
+            let mut sprints: Vec<&str> = vec![];
+
+            for suggestion in suggestions.iter() {
+                if !sprints.contains(&suggestion.sprint.as_str()) {
+                    sprints.push(&suggestion.sprint);
+                }
+            }
+
+            for sprint in sprints {
+                let keys: Vec<&str> = suggestions
+                    .iter()
+                    .filter(|suggestion| suggestion.sprint == sprint)
+                    .map(|suggestion| suggestion.key.as_str())
+                    .collect();
+
+                writeln!(writer, "# {}", sprint)?;
+                writeln!(writer, "key in ({})", keys.join(", "))?;
+            }
+        }
+    }
+
+    Ok(())
+}
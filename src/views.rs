@@ -0,0 +1,160 @@
+use crate::{
+    chart::{Chart, Item, NonWorking},
+    i18n::Translations,
+    record::GroupBy,
+    render, schedule,
+};
+use clap::ValueEnum;
+use std::error::Error;
+
+/// A way of grouping the same chart for a page of a workbook
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    /// One lane per person, or per team for plans
+    Assignee,
+    /// One lane per epic
+    Epic,
+    /// One lane per sprint
+    Sprint,
+}
+
+impl View {
+    fn group_by(&self) -> GroupBy {
+        match self {
+            View::Assignee => GroupBy::Assignee,
+            View::Epic => GroupBy::Epic,
+            View::Sprint => GroupBy::Sprint,
+        }
+    }
+
+    /// The translation key of the heading of the view
+    fn heading(&self) -> &'static str {
+        match self {
+            View::Assignee => "view-assignee",
+            View::Epic => "view-epic",
+            View::Sprint => "view-sprint",
+        }
+    }
+}
+
+/// Put the items of a chart in new lanes. Each item keeps the dates it is
+/// drawn with, so every view shows the same schedule. Lanes are in the order
+/// their first item appears. Time off stays with the lanes named after the
+/// resources that are off, and is left out of views without them.
+pub fn regroup(chart: &Chart, view: View, text: &Translations) -> Chart {
+    let group_by = view.group_by();
+    let mut resources: Vec<String> = vec![];
+    let mut lanes: Vec<Vec<Item>> = vec![];
+
+    for scheduled in schedule::schedule(chart).iter() {
+        let old_lane = || {
+            scheduled
+                .resource
+                .and_then(|index| chart.resources.get(index))
+                .cloned()
+        };
+        let name = match view {
+            View::Assignee => scheduled.item.assignee.clone().or_else(old_lane),
+            View::Epic => scheduled.item.epic.clone(),
+            View::Sprint => scheduled.item.sprint.clone(),
+        }
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| text.get(group_by.empty_label()).to_owned());
+        let index = match resources.iter().position(|resource| *resource == name) {
+            Some(index) => index,
+            None => {
+                resources.push(name);
+                lanes.push(vec![]);
+                resources.len() - 1
+            }
+        };
+        let mut item = scheduled.item.clone();
+
+        item.data.start_date = scheduled.start;
+        item.data.resource_index = Some(index);
+        lanes[index].push(item);
+    }
+
+    let non_working = chart
+        .non_working
+        .iter()
+        .filter_map(|days| {
+            let lanes: Vec<usize> = days
+                .resources
+                .iter()
+                .filter_map(|index| chart.resources.get(*index))
+                .filter_map(|name| resources.iter().position(|resource| resource == name))
+                .collect();
+
+            (days.resources.is_empty() || !lanes.is_empty()).then(|| NonWorking {
+                resources: lanes,
+                ..days.clone()
+            })
+        })
+        .collect();
+
+    Chart {
+        title: chart.title.to_owned(),
+        marked_date: chart.marked_date,
+        resources,
+        items: lanes.into_iter().flatten().collect(),
+        groups: vec![],
+        sprints: chart.sprints.clone(),
+        non_working,
+        units: chart.units.clone(),
+    }
+}
+
+/// An HTML page with the chart drawn once for each view, each on a page of
+/// its own when printed
+pub fn workbook(
+    chart: &Chart,
+    views: &[View],
+    text: &Translations,
+) -> Result<String, Box<dyn Error>> {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    let mut html = vec![
+        "<!DOCTYPE html>".to_owned(),
+        "<html>".to_owned(),
+        "<head>".to_owned(),
+        "<meta charset=\"utf-8\">".to_owned(),
+        format!("<title>{}</title>", escape(&chart.title)),
+        "<style>body{font-family:Arial,sans-serif;margin:2em;}section{page-break-after:always;margin-bottom:3em;}section:last-child{page-break-after:auto;}svg{max-width:100%;height:auto;}</style>".to_owned(),
+        "</head>".to_owned(),
+        "<body>".to_owned(),
+    ];
+
+    for view in views.iter() {
+        let heading = match chart.title.as_str() {
+            "" => text.get(view.heading()).to_owned(),
+            title => format!("{}: {}", title, text.get(view.heading())),
+        };
+        let view_chart = regroup(chart, *view, text);
+        let svg = if view_chart.items.is_empty() {
+            String::new()
+        } else {
+            let svg = render::svg(&view_chart, text)?;
+
+            // Leave out anything before the element, such as an XML
+            // declaration, which doesn't belong inside HTML
+            svg.find("<svg")
+                .map(|start| svg[start..].to_owned())
+                .unwrap_or(svg)
+        };
+
+        html.push("<section>".to_owned());
+        html.push(format!("<h1>{}</h1>", escape(&heading)));
+        html.push(svg);
+        html.push("</section>".to_owned());
+    }
+
+    html.push("</body>".to_owned());
+    html.push("</html>".to_owned());
+
+    Ok(html.join("\n"))
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::{fixture, run};
+use serde_json::{json, Value};
+
+fn anonymized(test_name: &str, salt: &str) -> Value {
+    let output = run(
+        test_name,
+        &[
+            fixture("ordering.csv").to_str().unwrap(),
+            "--anon-salt",
+            salt,
+            "--tooltips",
+        ],
+    );
+
+    serde_json::from_str(&output).unwrap()
+}
+
+#[test]
+fn pseudonyms_are_keyed_by_the_salt() {
+    let chart = anonymized("pseudonyms_are_keyed_by_the_salt", "s3cret");
+
+    // The first four bytes of HMAC-SHA256 of the name, keyed by the salt
+    assert_eq!(
+        chart["resources"],
+        json!(["Person 222da450", "unassigned", "Person 765542af"])
+    );
+    assert_eq!(
+        anonymized("pseudonyms_are_keyed_by_the_salt_other", "other")["resources"],
+        json!(["Person 6610e1eb", "unassigned", "Person 8244fe1a"])
+    );
+}
+
+#[test]
+fn pseudonyms_leave_out_tooltips() {
+    let chart = anonymized("pseudonyms_leave_out_tooltips", "s3cret");
+
+    assert!(chart["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|item| item.get("tooltip").is_none()));
+}
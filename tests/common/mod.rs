@@ -0,0 +1,71 @@
+use core::fmt::Arguments;
+use jira_to_gantt::{JiraToGanttLog, JiraToGanttTool};
+use std::{cell::RefCell, env, fs, path::PathBuf};
+
+pub struct TestLogger;
+
+impl JiraToGanttLog for TestLogger {
+    fn output(&self, _args: Arguments) {}
+    fn warning(&self, _args: Arguments) {}
+    fn error(&self, _args: Arguments) {}
+}
+
+/// Keeps what the tool prints, which is where invalid options are reported
+#[derive(Default)]
+pub struct CaptureLog(RefCell<String>);
+
+impl JiraToGanttLog for CaptureLog {
+    fn output(&self, args: Arguments) {
+        self.0.borrow_mut().push_str(&args.to_string());
+    }
+    fn warning(&self, _args: Arguments) {}
+    fn error(&self, _args: Arguments) {}
+}
+
+// Not every test file reads the checked in fixtures
+#[allow(dead_code)]
+pub fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+/// Run the tool with some arguments and return what it wrote to the output
+/// file, which is named after the test
+#[allow(dead_code)]
+pub fn run(test_name: &str, args: &[&str]) -> String {
+    let output = env::temp_dir().join(format!("jira_to_gantt_{}.json5", test_name));
+    let mut all_args = vec!["jira-to-gantt"];
+
+    all_args.extend_from_slice(args);
+    all_args.extend_from_slice(&["-o", output.to_str().unwrap()]);
+
+    JiraToGanttTool::new(&TestLogger)
+        .run(all_args.into_iter().map(Into::into))
+        .unwrap();
+
+    fs::read_to_string(&output).unwrap()
+}
+
+/// Run the tool with some arguments that it should turn down, and return
+/// the error, or what it printed if the options themselves are invalid
+#[allow(dead_code)]
+pub fn run_err(test_name: &str, args: &[&str]) -> String {
+    let output = env::temp_dir().join(format!("jira_to_gantt_{}.json5", test_name));
+    let mut all_args = vec!["jira-to-gantt"];
+    let log = CaptureLog::default();
+
+    all_args.extend_from_slice(args);
+    all_args.extend_from_slice(&["-o", output.to_str().unwrap()]);
+    fs::remove_file(&output).ok();
+
+    let result = JiraToGanttTool::new(&log).run(all_args.into_iter().map(Into::into));
+
+    assert!(!output.exists(), "a chart was written");
+
+    match result {
+        Ok(()) => log.0.into_inner(),
+        Err(error) => error.to_string(),
+    }
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use common::{fixture, run, run_err};
+use serde_json::Value;
+use std::{env, fs};
+
+#[test]
+fn dependencies_delay_later_issues() {
+    let output = run(
+        "dependencies_delay_later_issues",
+        &[
+            fixture("deps_issues.csv").to_str().unwrap(),
+            "--deps",
+            fixture("deps.csv").to_str().unwrap(),
+        ],
+    );
+    let chart: Value = serde_json::from_str(&output).unwrap();
+    let start = |title: &str| {
+        chart["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["title"] == title)
+            .unwrap()["startDate"]
+            .clone()
+    };
+
+    // A-1 takes two days from 2023-01-01, then A-3 waits two more
+    assert_eq!(start("A-3"), "2023-01-05");
+    // B-1 starts the day after A-3 finishes
+    assert_eq!(start("B-1"), "2023-01-07");
+}
+
+#[test]
+fn dependencies_leave_fixed_dates_alone() {
+    let output = run(
+        "dependencies_leave_fixed_dates_alone",
+        &[
+            fixture("deps_fixed_dates.csv").to_str().unwrap(),
+            "--deps",
+            fixture("deps.csv").to_str().unwrap(),
+            "--date-fields",
+            "Custom field (Start date),Custom field (End date)",
+        ],
+    );
+    let chart: Value = serde_json::from_str(&output).unwrap();
+    let item = |title: &str| {
+        chart["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["title"] == title)
+            .unwrap()
+            .clone()
+    };
+
+    // A-3 keeps its own dates, though it should wait for A-1
+    assert_eq!(item("A-3")["startDate"], "2023-01-02");
+    assert_eq!(item("A-3")["duration"], 2);
+    // B-1 still waits for A-3 to finish
+    assert_eq!(item("B-1")["startDate"], "2023-01-04");
+}
+
+#[test]
+fn focus_keeps_dependencies_within_radius() {
+    let output = run(
+        "focus_keeps_dependencies_within_radius",
+        &[
+            fixture("deps_issues.csv").to_str().unwrap(),
+            "--deps",
+            fixture("deps.csv").to_str().unwrap(),
+            "--focus",
+            "A-1",
+        ],
+    );
+    let chart: Value = serde_json::from_str(&output).unwrap();
+    let titles: Vec<&str> = chart["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect();
+
+    // B-1 is two hops away and A-2 only shares a lane, so only A-3 joins A-1,
+    // on the day it was scheduled for
+    assert_eq!(titles, ["A-1", "A-3"]);
+    assert_eq!(chart["resources"], serde_json::json!(["bob", "alice"]));
+    assert_eq!(chart["items"][1]["startDate"], "2023-01-05");
+}
+
+#[test]
+fn focus_radius_counts_hops() {
+    let titles = |radius: &str| {
+        let output = run(
+            &format!("focus_radius_counts_hops_{}", radius),
+            &[
+                fixture("deps_issues.csv").to_str().unwrap(),
+                "--deps",
+                fixture("deps.csv").to_str().unwrap(),
+                "--focus",
+                "A-1",
+                "--radius",
+                radius,
+            ],
+        );
+        let chart: Value = serde_json::from_str(&output).unwrap();
+
+        chart["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["title"].as_str().unwrap().to_owned())
+            .collect::<Vec<String>>()
+    };
+
+    assert_eq!(titles("0"), ["A-1"]);
+    assert_eq!(titles("2"), ["A-1", "A-3", "B-1"]);
+}
+
+#[test]
+fn focus_on_missing_issue_is_an_error() {
+    assert_eq!(
+        run_err(
+            "focus_on_missing_issue",
+            &[
+                fixture("deps_issues.csv").to_str().unwrap(),
+                "--focus",
+                "Z-9"
+            ]
+        ),
+        "Issue 'Z-9' of --focus isn't on the chart"
+    );
+}
+
+#[test]
+fn dependencies_survive_title_overrides() {
+    let overrides =
+        env::temp_dir().join("jira_to_gantt_dependencies_survive_title_overrides.json5");
+
+    fs::write(&overrides, r#"{ "A-3": { title: "Three renamed" } }"#).unwrap();
+
+    let output = run(
+        "dependencies_survive_title_overrides",
+        &[
+            fixture("deps_issues.csv").to_str().unwrap(),
+            "--deps",
+            fixture("deps.csv").to_str().unwrap(),
+            "--overrides",
+            overrides.to_str().unwrap(),
+        ],
+    );
+    let chart: Value = serde_json::from_str(&output).unwrap();
+    let item = |id: &str| {
+        chart["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["id"] == id)
+            .unwrap()
+            .clone()
+    };
+
+    // Dependencies are on keys, so renaming A-3 still delays it and B-1
+    assert_eq!(item("A-3")["title"], "Three renamed");
+    assert_eq!(item("A-3")["startDate"], "2023-01-05");
+    assert_eq!(item("B-1")["startDate"], "2023-01-07");
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::{fixture, run, run_err};
+use serde_json::Value;
+
+/// A name for the output file of an expression
+fn test_name(expression: &str) -> String {
+    format!(
+        "filter_{}",
+        expression
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>()
+    )
+}
+
+/// Chart the issues of the fixture that match an expression, returning
+/// their keys in order
+fn filter(expression: &str) -> Vec<String> {
+    let output = run(
+        &test_name(expression),
+        &[
+            fixture("filter.csv").to_str().unwrap(),
+            "--filter",
+            expression,
+        ],
+    );
+    let chart: Value = serde_json::from_str(&output).unwrap();
+    let mut keys: Vec<String> = chart["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_str().unwrap().to_owned())
+        .collect();
+
+    keys.sort();
+
+    keys
+}
+
+/// What the tool says about an expression that isn't valid
+fn error(expression: &str) -> String {
+    run_err(
+        &test_name(expression),
+        &[
+            fixture("filter.csv").to_str().unwrap(),
+            "--filter",
+            expression,
+        ],
+    )
+}
+
+#[test]
+fn and_binds_tighter_than_or() {
+    assert_eq!(
+        filter(r#"assignee == "alice" || status == "Open" && labels ~ "urgent""#),
+        ["F-1", "F-4"]
+    );
+    assert_eq!(
+        filter(r#"(assignee == "alice" || status == "Open") && labels ~ "urgent""#),
+        ["F-4"]
+    );
+    // `!` applies to the comparison after it, not to the rest
+    assert_eq!(
+        filter(r#"!status == "Closed" && assignee == "bob""#),
+        ["F-3"]
+    );
+}
+
+#[test]
+fn strings_escape_quotes_and_backslashes() {
+    assert_eq!(filter(r#"summary == "Say \"hi\"""#), ["F-1"]);
+    assert_eq!(filter(r#"summary == "Back\\slash""#), ["F-2"]);
+}
+
+#[test]
+fn not_matches_when_no_value_matches() {
+    // F-2 has an urgent label as well, and F-3 has no labels at all
+    assert_eq!(filter(r#"labels !~ "team-*""#), ["F-3", "F-4"]);
+}
+
+#[test]
+fn numbers_compare_as_numbers_and_the_rest_as_text() {
+    // As text, "4000" would come after "30000"
+    assert_eq!(filter("originalEstimate > 30000"), ["F-2", "F-4"]);
+    assert_eq!(filter(r#"summary > "p""#), ["F-1", "F-3"]);
+    assert_eq!(filter(r#"dueDate >= "2023-02-01""#), ["F-2", "F-4"]);
+}
+
+#[test]
+fn errors_give_the_column() {
+    assert!(
+        error("status == ").contains("expected a quoted string or a number at column 11, the end")
+    );
+    assert!(error(r#"status = "x""#).contains("unexpected '=' at column 8"));
+    assert!(error(r#"(status == "x""#).contains("expected ')' at column 15, the end"));
+    assert!(error(r#"summary == "abc"#).contains("unclosed string at column 12"));
+    assert!(error(r#"owner == "x""#).contains("'owner' isn't a field that can be read"));
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::{run, TestLogger};
+use jira_to_gantt::JiraToGanttTool;
+use serde_json::Value;
+use std::{env, fs};
+
+fn gen_fixture(name: &str, args: &[&str]) -> String {
+    let output = env::temp_dir().join(format!("jira_to_gantt_{}.csv", name));
+    let mut all_args = vec!["jira-to-gantt", "gen-fixture", output.to_str().unwrap()];
+
+    all_args.extend_from_slice(args);
+    JiraToGanttTool::new(&TestLogger)
+        .run(all_args.into_iter().map(Into::into))
+        .unwrap();
+
+    output.to_str().unwrap().to_owned()
+}
+
+#[test]
+fn generated_fixtures_are_repeatable_and_readable() {
+    let args = ["--rows", "50", "--assignees", "3", "--spread", "skewed"];
+    let first = gen_fixture("fixture_first", &args);
+    let second = gen_fixture("fixture_second", &args);
+
+    assert_eq!(
+        fs::read_to_string(&first).unwrap(),
+        fs::read_to_string(&second).unwrap()
+    );
+
+    let chart: Value = serde_json::from_str(&run("generated_fixture", &[first.as_str()])).unwrap();
+
+    assert_eq!(chart["items"].as_array().unwrap().len(), 50);
+    assert_eq!(chart["resources"].as_array().unwrap().len(), 3);
+}
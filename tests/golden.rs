@@ -0,0 +1,14 @@
+mod common;
+
+use common::TestLogger;
+use jira_to_gantt::JiraToGanttTool;
+
+// The cases and golden files are in tests/golden. After a change to the
+// output, run `jira-to-gantt --self-test --bless tests/golden` and review the
+// differences before checking them in.
+#[test]
+fn golden_charts_and_reports_match() {
+    JiraToGanttTool::new(&TestLogger)
+        .run(["jira-to-gantt", "--self-test"].into_iter().map(Into::into))
+        .unwrap();
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::{fixture, run, run_err};
+use serde_json::Value;
+use std::{env, fs, path::PathBuf};
+
+/// An export of one issue, created just before the holidays
+fn export(test_name: &str) -> PathBuf {
+    let input = env::temp_dir().join(format!("jira_to_gantt_{}.csv", test_name));
+
+    fs::write(
+        &input,
+        "Issue key,Summary,Status,Assignee,Original Estimate,Created\n\
+         PROJ-1,One,Open,bob,288000,2/Jan/23 9:00 AM\n",
+    )
+    .unwrap();
+
+    input
+}
+
+#[test]
+fn calendar_events_become_holidays() {
+    let input = export("holidays_export");
+    let output = run(
+        "calendar_events_become_holidays",
+        &[
+            input.to_str().unwrap(),
+            "--holidays",
+            fixture("holidays.ics").to_str().unwrap(),
+            "--shade-nonworking",
+        ],
+    );
+    let chart: Value = serde_json::from_str(&output).unwrap();
+    let holidays: Vec<(&str, &str)> = chart["nonWorking"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|days| days["kind"] == "holiday")
+        .map(|days| {
+            (
+                days["startDate"].as_str().unwrap(),
+                days["endDate"].as_str().unwrap(),
+            )
+        })
+        .collect();
+
+    // The end of an all-day event is the day after it, so work starts after
+    // the winter break. A folded DTSTART is read whole, the cancelled event
+    // on the 10th is left out, and an event that ends at midnight doesn't
+    // cover the next day.
+    assert_eq!(chart["items"][0]["startDate"], "2023-01-04");
+    assert_eq!(
+        holidays,
+        [("2023-01-09", "2023-01-09"), ("2023-01-11", "2023-01-12")]
+    );
+}
+
+#[test]
+fn holidays_are_not_scheduled_work() {
+    let input = export("holidays_workload");
+    let report = env::temp_dir().join("jira_to_gantt_holidays_workload_report.csv");
+
+    run(
+        "holidays_are_not_scheduled_work",
+        &[
+            input.to_str().unwrap(),
+            "--holidays",
+            fixture("holidays.ics").to_str().unwrap(),
+            "--report",
+            "workload-by-week",
+            "--report-format",
+            "csv",
+            "--report-file",
+            report.to_str().unwrap(),
+        ],
+    );
+
+    // The item runs from the 4th to the 17th, over the holidays on the 9th,
+    // 11th and 12th
+    assert_eq!(
+        fs::read_to_string(&report).unwrap(),
+        "Resource,2023-W01,2023-W02,2023-W03\nbob,3,2,2\n"
+    );
+}
+
+fn calendar_error(test_name: &str, content: &str) -> String {
+    let calendar = env::temp_dir().join(format!("jira_to_gantt_{}.ics", test_name));
+
+    fs::write(&calendar, content).unwrap();
+
+    run_err(
+        test_name,
+        &[
+            fixture("ordering.csv").to_str().unwrap(),
+            "--holidays",
+            calendar.to_str().unwrap(),
+        ],
+    )
+}
+
+#[test]
+fn invalid_calendars_are_errors() {
+    assert!(calendar_error(
+        "invalid_calendar_date",
+        "BEGIN:VEVENT\nDTSTART;VALUE=DATE:2023-01-05\nEND:VEVENT\n"
+    )
+    .ends_with("invalid date '2023-01-05'"));
+    assert!(calendar_error(
+        "invalid_calendar_start",
+        "BEGIN:VEVENT\nSUMMARY:Offsite\nEND:VEVENT\n"
+    )
+    .ends_with("an event has no DTSTART"));
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::{fixture, run};
+use serde_json::Value;
+use std::{env, fs};
+
+#[test]
+fn working_hours_go_around_holidays() {
+    let config = env::temp_dir().join("jira_to_gantt_hours_holidays.toml");
+
+    fs::write(&config, "holidays = [\"2023-01-03\"]\n").unwrap();
+
+    let output = run(
+        "working_hours_go_around_holidays",
+        &[
+            fixture("hours.csv").to_str().unwrap(),
+            "--granularity",
+            "hours",
+            "--config",
+            config.to_str().unwrap(),
+        ],
+    );
+    let chart: Value = serde_json::from_str(&output).unwrap();
+    let scheduled: Vec<(&str, &str, i64)> = chart["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| {
+            (
+                item["id"].as_str().unwrap(),
+                item["startDate"].as_str().unwrap(),
+                item["duration"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+
+    // Nobody works on the Tuesday, so the second issue's afternoon carries
+    // over to Wednesday, and each issue starts where the one before ends
+    assert_eq!(
+        scheduled,
+        [
+            ("PROJ-1", "2023-01-02", 1),
+            ("PROJ-2", "2023-01-02", 3),
+            ("PROJ-3", "2023-01-04", 2),
+            ("PROJ-4", "2023-01-05", 1),
+        ]
+    );
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use common::{fixture, run};
+use serde_json::Value;
+use std::{env, fs};
+
+fn parse(output: &str) -> Value {
+    serde_json::from_str(output).unwrap()
+}
+
+fn titles(chart: &Value) -> Vec<&str> {
+    chart["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn keeps_export_order() {
+    let chart = parse(&run(
+        "keeps_export_order",
+        &[fixture("ordering.csv").to_str().unwrap()],
+    ));
+
+    // Resources in order of first appearance, items grouped by resource in
+    // export order
+    assert_eq!(
+        chart["resources"],
+        serde_json::json!(["bob", "unassigned", "alice"])
+    );
+    assert_eq!(
+        titles(&chart),
+        ["PROJ-10", "PROJ-1", "PROJ-2", "PROJ-9", "PROJ-3"]
+    );
+}
+
+#[test]
+fn head_counts_issues_after_filters() {
+    let chart = parse(&run(
+        "head_counts_issues_after_filters",
+        &[
+            fixture("ordering.csv").to_str().unwrap(),
+            "--head",
+            "3",
+            "--exclude-status",
+            "Closed",
+        ],
+    ));
+
+    // PROJ-9 is closed, so PROJ-1 is the third issue kept
+    assert_eq!(titles(&chart), ["PROJ-10", "PROJ-1", "PROJ-2"]);
+}
+
+#[test]
+fn output_is_repeatable() {
+    let args = [fixture("ordering.csv").to_str().unwrap().to_owned()];
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+
+    assert_eq!(
+        run("output_is_repeatable_1", &args),
+        run("output_is_repeatable_2", &args)
+    );
+}
+
+#[test]
+fn stable_sort_ignores_export_order() {
+    let sorted = run(
+        "stable_sort_ignores_export_order_1",
+        &[fixture("ordering.csv").to_str().unwrap(), "--stable-sort"],
+    );
+    let chart = parse(&sorted);
+
+    assert_eq!(
+        chart["resources"],
+        serde_json::json!(["alice", "bob", "unassigned"])
+    );
+    assert_eq!(
+        titles(&chart),
+        ["PROJ-3", "PROJ-9", "PROJ-1", "PROJ-10", "PROJ-2"]
+    );
+    assert_eq!(
+        sorted,
+        run(
+            "stable_sort_ignores_export_order_2",
+            &[
+                fixture("ordering_shuffled.csv").to_str().unwrap(),
+                "--stable-sort"
+            ],
+        )
+    );
+}
+
+#[test]
+fn merges_inputs_in_order() {
+    let export = fs::read_to_string(fixture("ordering.csv")).unwrap();
+    let lines: Vec<&str> = export.lines().collect();
+    let first = env::temp_dir().join("jira_to_gantt_merges_inputs_1.csv");
+    let second = env::temp_dir().join("jira_to_gantt_merges_inputs_2.csv");
+
+    // Exports in batches that overlap by one issue
+    fs::write(&first, lines[..4].join("\n")).unwrap();
+    fs::write(&second, [&lines[..1], &lines[3..]].concat().join("\n")).unwrap();
+
+    assert_eq!(
+        run(
+            "merges_inputs_in_order",
+            &[first.to_str().unwrap(), "--input", second.to_str().unwrap(),],
+        ),
+        run(
+            "merges_inputs_in_order_whole",
+            &[fixture("ordering.csv").to_str().unwrap()],
+        )
+    );
+}
+
+#[test]
+fn merges_directory_in_name_order() {
+    let export = fs::read_to_string(fixture("ordering.csv")).unwrap();
+    let lines: Vec<&str> = export.lines().collect();
+    let dir = env::temp_dir().join("jira_to_gantt_merges_directory");
+
+    fs::create_dir_all(&dir).unwrap();
+    // Written out of order, with a file that isn't an export to skip
+    fs::write(
+        dir.join("2.csv"),
+        [&lines[..1], &lines[4..]].concat().join("\n"),
+    )
+    .unwrap();
+    fs::write(dir.join("1.csv"), lines[..4].join("\n")).unwrap();
+    fs::write(dir.join("3.csv"), "Name,Notes\nx,y\n").unwrap();
+
+    assert_eq!(
+        run("merges_directory_in_name_order", &[dir.to_str().unwrap()]),
+        run(
+            "merges_directory_in_name_order_whole",
+            &[fixture("ordering.csv").to_str().unwrap()],
+        )
+    );
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use common::{fixture, run};
+use std::{env, fs};
+
+#[test]
+fn writes_rejected_rows_with_reasons() {
+    let rejects = env::temp_dir().join("jira_to_gantt_rejects.csv");
+    let chart = run(
+        "writes_rejected_rows_with_reasons",
+        &[
+            fixture("rejects.csv").to_str().unwrap(),
+            "--rejects",
+            rejects.to_str().unwrap(),
+        ],
+    );
+
+    assert!(chart.contains("\"A-1\""));
+    assert!(!chart.contains("\"A-2\""));
+    assert_eq!(
+        fs::read_to_string(&rejects).unwrap(),
+        "Issue key,Summary,Status,Assignee,Original Estimate,Created,Reason\n\
+         ,No key,Open,bob,28800,01/Jan/23 9:00 AM,No issue key\n\
+         A-2,Bad estimate,Open,bob,lots,01/Jan/23 9:00 AM,Invalid Original Estimate 'lots' for issue 'A-2'\n\
+         A-3,Bad date,Open,alice,28800,yesterday,\"Invalid Created date 'yesterday' for issue 'A-3', which isn't in a known format (give one with --date-format)\"\n"
+    );
+}
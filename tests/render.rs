@@ -0,0 +1,58 @@
+mod common;
+
+use common::{fixture, run};
+
+#[test]
+fn svg_bars_have_tooltips() {
+    let svg = run(
+        "svg_bars_have_tooltips",
+        &[
+            fixture("ordering.csv").to_str().unwrap(),
+            "--tooltips",
+            "--svg",
+        ],
+    );
+
+    // One for each of the five issues, on the bar it is drawn with
+    assert_eq!(svg.matches("<title>").count(), 5);
+    assert!(svg.contains("height=\"20\"><title>PROJ-10: Ten\n"));
+}
+
+#[test]
+fn svg_month_headings_follow_lang() {
+    let svg = run(
+        "svg_month_headings_follow_lang",
+        &[
+            fixture("ordering.csv").to_str().unwrap(),
+            "--lang",
+            "es",
+            "--svg",
+        ],
+    );
+
+    assert!(svg.contains("<text class=\"heading\" x=\"260\" y=\"60\">\nene\n"));
+    assert!(!svg.contains("\nJan\n"));
+}
+
+#[test]
+fn svg_shades_non_working_days() {
+    let svg = run(
+        "svg_shades_non_working_days",
+        &[
+            fixture("ordering.csv").to_str().unwrap(),
+            "--shade-nonworking",
+            "--pto",
+            &format!("alice={}", fixture("holidays.ics").to_str().unwrap()),
+            "--svg",
+        ],
+    );
+
+    // The weekend is shaded across every row, and alice's time off on the
+    // 2nd and 3rd only across the rows of her two issues
+    assert!(svg.contains(
+        "<rect class=\"non-working weekend\" x=\"235.48387\" y=\"80\" width=\"5.1612854\" height=\"150\"/>"
+    ));
+    assert_eq!(svg.matches("class=\"non-working time-off\"").count(), 2);
+    assert!(svg.contains("<rect class=\"non-working time-off\" x=\"222.58064\" y=\"170\""));
+    assert!(svg.contains("<rect class=\"non-working time-off\" x=\"222.58064\" y=\"200\""));
+}
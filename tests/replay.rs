@@ -0,0 +1,114 @@
+mod common;
+
+use common::{fixture, run};
+use serde_json::{json, Value};
+use std::{env, fs};
+
+#[test]
+fn replays_recorded_board() {
+    assert_eq!(
+        run(
+            "replays_recorded_board",
+            &[
+                "--board",
+                "1",
+                "--replay",
+                fixture("board_replay").to_str().unwrap(),
+            ],
+        ),
+        fs::read_to_string(fixture("board_replay.json5")).unwrap()
+    );
+}
+
+#[test]
+fn replays_recorded_filter() {
+    assert_eq!(
+        run(
+            "replays_recorded_filter",
+            &[
+                "--filter-id",
+                "10042",
+                "--replay",
+                fixture("filter_replay").to_str().unwrap(),
+            ],
+        ),
+        fs::read_to_string(fixture("filter_replay.json5")).unwrap()
+    );
+}
+
+#[test]
+fn replays_recorded_jql_search() {
+    let output = run(
+        "replays_recorded_jql_search",
+        &[
+            "--jql",
+            "project = PROJ AND fixVersion = \"2.0\" ORDER BY Rank",
+            "--replay",
+            fixture("filter_replay").to_str().unwrap(),
+        ],
+    );
+    let chart: Value = serde_json::from_str(&output).unwrap();
+    let filter_chart: Value =
+        serde_json::from_str(&fs::read_to_string(fixture("filter_replay.json5")).unwrap()).unwrap();
+
+    // The same search as the filter, but without the filter's name
+    assert_eq!(chart["title"], "");
+    assert_eq!(chart["items"], filter_chart["items"]);
+    assert_eq!(chart["resources"], filter_chart["resources"]);
+}
+
+#[test]
+fn replays_recorded_board_of_named_site() {
+    let config = env::temp_dir().join("jira_to_gantt_named_site.toml");
+
+    // A site's responses are in a directory named after it
+    fs::write(
+        &config,
+        "[sites.board_replay]\nurl = \"https://example.atlassian.net\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        run(
+            "replays_recorded_board_of_named_site",
+            &[
+                "--config",
+                config.to_str().unwrap(),
+                "--source",
+                "jira:board_replay",
+                "--board",
+                "1",
+                "--replay",
+                fixture("").to_str().unwrap(),
+            ],
+        ),
+        fs::read_to_string(fixture("board_replay.json5")).unwrap()
+    );
+}
+
+#[test]
+fn replays_custom_fields_of_search() {
+    let output = run(
+        "replays_custom_fields_of_search",
+        &[
+            "--jql",
+            "project = PROJ",
+            "--replay",
+            fixture("fields_replay").to_str().unwrap(),
+            "--estimate-order",
+            "story-points,default",
+            "--group-by",
+            "sprint",
+        ],
+    );
+    let chart: Value = serde_json::from_str(&output).unwrap();
+
+    // Story points, sprints and risks are in custom fields that are looked
+    // up by name, and older servers give sprints as serialized objects
+    assert_eq!(chart["resources"], json!(["Sprint 8", "Sprint 9"]));
+    assert_eq!(chart["items"][0]["duration"], 4);
+    assert_eq!(chart["items"][0]["risk"], "high");
+    assert_eq!(chart["items"][1]["duration"], 6);
+    assert_eq!(chart["sprints"][0]["name"], "Sprint 7");
+    assert_eq!(chart["sprints"][2]["startDate"], "2023-04-03");
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::run;
+use std::{env, fs};
+
+#[test]
+fn spilled_chart_is_the_same_as_one_held_in_memory() {
+    let input = env::temp_dir().join("jira_to_gantt_spilled_export.csv");
+    let mut rows = vec!["Issue key,Summary,Status,Assignee,Original Estimate,Created".to_owned()];
+
+    for number in 1..=500 {
+        rows.push(format!(
+            "PROJ-{},Issue {},Open,person{},{},{}/Jan/23 9:00 AM",
+            number,
+            number,
+            number % 7,
+            28800 * (number % 5 + 1),
+            number % 28 + 1
+        ));
+    }
+
+    fs::write(&input, rows.join("\n")).unwrap();
+
+    let input = input.to_str().unwrap();
+
+    // A kilobyte holds a few items, so most lanes are written out and read
+    // back more than once
+    let held = run("spilled_chart_held", &[input, "--tooltips"]);
+    let spilled = run(
+        "spilled_chart_spilled",
+        &[input, "--tooltips", "--max-memory", "1K"],
+    );
+
+    assert_eq!(spilled, held);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{fixture, run, TestLogger};
+use jira_to_gantt::JiraToGanttTool;
+use std::{env, fs};
+
+/// Stamp a chart with some layout options and verify the file it wrote
+fn stamp_and_verify(test_name: &str, layout: &[&str]) {
+    let output = env::temp_dir().join(format!("jira_to_gantt_{}.json5", test_name));
+    let input = fixture("ordering.csv");
+    let mut args = vec![
+        "jira-to-gantt",
+        input.to_str().unwrap(),
+        "-o",
+        output.to_str().unwrap(),
+        "--stamp",
+        // Whole floats, such as the hours in a day, must verify too
+        "--hours-per-day",
+        "8",
+    ];
+
+    args.extend_from_slice(layout);
+
+    let mut tool = JiraToGanttTool::new(&TestLogger);
+
+    tool.run(args.into_iter().map(Into::into)).unwrap();
+    tool.run(
+        [
+            "jira-to-gantt",
+            "verify",
+            output.to_str().unwrap(),
+            "--input",
+            input.to_str().unwrap(),
+        ]
+        .into_iter()
+        .map(Into::into),
+    )
+    .unwrap();
+}
+
+#[test]
+fn stamped_chart_verifies() {
+    stamp_and_verify("stamped_chart_verifies", &[]);
+}
+
+#[test]
+fn stamped_pretty_chart_verifies() {
+    stamp_and_verify("stamped_pretty_chart_verifies", &["--pretty"]);
+}
+
+#[test]
+fn stamped_compact_chart_verifies() {
+    stamp_and_verify("stamped_compact_chart_verifies", &["--compact"]);
+}
+
+#[test]
+fn stamp_keeps_field_order() {
+    let input = fixture("ordering.csv");
+    let chart = run("stamp_keeps_field_order", &[input.to_str().unwrap()]);
+    let stamped = run(
+        "stamp_keeps_field_order_stamped",
+        &[input.to_str().unwrap(), "--stamp"],
+    );
+
+    // The stamp comes after the fields of the chart, as they are written
+    // without it
+    assert!(stamped.starts_with(&format!(
+        "{},\"stamp\":{{",
+        chart.strip_suffix('}').unwrap()
+    )));
+}
+
+#[test]
+fn changed_chart_fails_verification() {
+    let output = env::temp_dir().join("jira_to_gantt_changed_chart_fails_verification.json5");
+    let input = fixture("ordering.csv");
+    let mut tool = JiraToGanttTool::new(&TestLogger);
+
+    tool.run(
+        [
+            "jira-to-gantt",
+            input.to_str().unwrap(),
+            "-o",
+            output.to_str().unwrap(),
+            "--stamp",
+            "--pretty",
+        ]
+        .into_iter()
+        .map(Into::into),
+    )
+    .unwrap();
+
+    let content = fs::read_to_string(&output).unwrap();
+
+    fs::write(
+        &output,
+        content.replacen("\"title\": \"", "\"title\": \"x", 1),
+    )
+    .unwrap();
+
+    assert!(tool
+        .run(
+            ["jira-to-gantt", "verify", output.to_str().unwrap()]
+                .into_iter()
+                .map(Into::into),
+        )
+        .is_err());
+}
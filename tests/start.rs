@@ -0,0 +1,48 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{fixture, TestLogger};
+use jira_to_gantt::{InferStart, JiraRecord, JiraToGanttTool};
+use serde_json::Value;
+use std::{env, fs};
+
+/// Starts the work on one issue on a day of its own
+struct Kickoff;
+
+impl InferStart for Kickoff {
+    fn earliest_start(&self, record: &JiraRecord, _today: NaiveDate) -> Option<NaiveDate> {
+        (record.key == "PROJ-1").then(|| NaiveDate::from_ymd_opt(2023, 2, 1).unwrap())
+    }
+}
+
+#[test]
+fn custom_start_inference_delays_issues() {
+    let output = env::temp_dir().join("jira_to_gantt_custom_start.json5");
+    let input = fixture("ordering.csv");
+    let args = [
+        "jira-to-gantt",
+        input.to_str().unwrap(),
+        "-o",
+        output.to_str().unwrap(),
+    ];
+
+    JiraToGanttTool::new(&TestLogger)
+        .with_start(&Kickoff)
+        .run(args.into_iter().map(Into::into))
+        .unwrap();
+
+    let chart: Value = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    let start = |key: &str| {
+        chart["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["id"] == key)
+            .map(|item| item["startDate"].clone())
+            .unwrap()
+    };
+
+    // The first issue of the lane still starts when it was created
+    assert_eq!(start("PROJ-10"), "2023-01-02");
+    assert_eq!(start("PROJ-1"), "2023-02-01");
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::{fixture, run, TestLogger};
+use jira_to_gantt::JiraToGanttTool;
+use serde_json::Value;
+use std::{env, fs};
+
+fn run_with_max_warnings(max: &str) -> bool {
+    let input = fixture("warnings.csv");
+    let output = env::temp_dir().join("jira_to_gantt_max_warnings.json5");
+    let args = [
+        "jira-to-gantt",
+        input.to_str().unwrap(),
+        "--max-duration",
+        "30",
+        "--max-warnings",
+        max,
+        "-o",
+        output.to_str().unwrap(),
+    ];
+
+    JiraToGanttTool::new(&TestLogger)
+        .run(args.into_iter().map(Into::into))
+        .is_ok()
+}
+
+#[test]
+fn too_many_warnings_fail_the_run() {
+    // W-1 and W-3 are both cut to 30 days
+    assert!(run_with_max_warnings("2"));
+    assert!(!run_with_max_warnings("1"));
+}
+
+#[test]
+fn largest_estimate_is_cut_to_max_duration() {
+    let input = env::temp_dir().join("jira_to_gantt_largest_estimate.csv");
+
+    fs::write(
+        &input,
+        "Issue key,Summary,Status,Assignee,Original Estimate,Created\n\
+         W-1,Typo,Open,alice,4294967295,01/Jan/23 9:00 AM\n",
+    )
+    .unwrap();
+
+    let output = run(
+        "largest_estimate_is_cut_to_max_duration",
+        &[input.to_str().unwrap(), "--max-duration", "30"],
+    );
+    let chart: Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(chart["items"][0]["duration"], 30);
+}
+
+fn run_export_of(rows: &str, options: &[&str]) -> bool {
+    let input = env::temp_dir().join(format!("jira_to_gantt_export_of_{}.csv", rows));
+    let output = env::temp_dir().join("jira_to_gantt_export_of.json5");
+    let mut tool = JiraToGanttTool::new(&TestLogger);
+
+    tool.run(
+        [
+            "jira-to-gantt",
+            "gen-fixture",
+            input.to_str().unwrap(),
+            "--rows",
+            rows,
+        ]
+        .into_iter()
+        .map(Into::into),
+    )
+    .unwrap();
+
+    tool.run(
+        [
+            "jira-to-gantt",
+            input.to_str().unwrap(),
+            "--max-warnings",
+            "0",
+            "-o",
+            output.to_str().unwrap(),
+        ]
+        .into_iter()
+        .chain(options.iter().copied())
+        .map(Into::into),
+    )
+    .is_ok()
+}
+
+#[test]
+fn export_at_jira_row_limit_is_a_warning() {
+    assert!(run_export_of("999", &[]));
+    assert!(!run_export_of("1000", &[]));
+    // The rows after the first 1000 weren't read
+    assert!(run_export_of("1001", &["--head", "1000"]));
+}